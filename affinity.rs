@@ -0,0 +1,183 @@
+use std::io;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AffinityPreset {
+    AllCores,
+    ExcludeCore0,
+    PerformanceCores,
+}
+
+impl AffinityPreset {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AffinityPreset::AllCores => "All cores",
+            AffinityPreset::ExcludeCore0 => "Exclude core 0",
+            AffinityPreset::PerformanceCores => "P-cores only",
+        }
+    }
+}
+
+pub fn preset_cores(preset: AffinityPreset, cpu_count: usize, performance_cores: Option<&[usize]>) -> Vec<bool> {
+    match preset {
+        AffinityPreset::AllCores => Vec::new(),
+        AffinityPreset::ExcludeCore0 => (0..cpu_count).map(|i| i != 0).collect(),
+        AffinityPreset::PerformanceCores => match performance_cores {
+            Some(cores) => (0..cpu_count).map(|i| cores.contains(&i)).collect(),
+            None => Vec::new(),
+        },
+    }
+}
+
+pub fn is_restricted(cores: &[bool]) -> bool {
+    !cores.is_empty() && !cores.iter().all(|c| *c)
+}
+
+// One entry per core: empty means all cores, and cores missing from a shorter list are not selected.
+pub fn selected_cores(cores: &[bool], cpu_count: usize) -> Vec<bool> {
+    if cores.is_empty() {
+        return vec![true; cpu_count];
+    }
+    let mut cores = cores.to_vec();
+    cores.resize(cpu_count, false);
+    cores
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_process_affinity(pid: u32, cores: &[bool]) -> io::Result<()> {
+    use windows::Win32::Foundation::*;
+    use windows::Win32::System::Threading::*;
+    
+    let mut mask: usize = 0;
+    for (i, _) in cores.iter().enumerate().filter(|(_, on)| **on) {
+        if i < usize::BITS as usize {
+            mask |= 1 << i;
+        }
+    }
+    if mask == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no cores selected"));
+    }
+    
+    unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION | PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+            .map_err(win32_error)?;
+        let result = SetProcessAffinityMask(handle, mask);
+        let _ = CloseHandle(handle);
+        result.map_err(win32_error)
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_process_affinity(pid: u32, cores: &[bool]) -> io::Result<()> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let mut selected = 0;
+    for (i, _) in cores.iter().enumerate().filter(|(_, on)| **on) {
+        unsafe { libc::CPU_SET(i, &mut set) };
+        selected += 1;
+    }
+    if selected == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no cores selected"));
+    }
+    
+    // sched_setaffinity only affects a single thread, so apply it to every task of the process.
+    crate::priority::for_each_thread(pid, |tid| {
+        if unsafe { libc::sched_setaffinity(tid, std::mem::size_of::<libc::cpu_set_t>(), &set) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_process_affinity(_pid: u32, _cores: &[bool]) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "CPU affinity is not supported on this platform"))
+}
+
+#[cfg(target_os = "windows")]
+pub fn performance_cores() -> Option<Vec<usize>> {
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::SystemInformation::*;
+    
+    let mut entries: Vec<(usize, u8)> = Vec::new();
+    unsafe {
+        let mut length = 0u32;
+        let _ = GetSystemCpuSetInformation(None, 0, &mut length, HANDLE::default(), 0);
+        if length == 0 {
+            return None;
+        }
+        let mut buffer = vec![0u8; length as usize];
+        let info_ptr = buffer.as_mut_ptr() as *mut SYSTEM_CPU_SET_INFORMATION;
+        if !GetSystemCpuSetInformation(Some(info_ptr), length, &mut length, HANDLE::default(), 0).as_bool() {
+            return None;
+        }
+        
+        let mut offset = 0usize;
+        while offset < length as usize {
+            let info = std::ptr::read_unaligned(buffer.as_ptr().add(offset) as *const SYSTEM_CPU_SET_INFORMATION);
+            if info.Size == 0 {
+                break;
+            }
+            if info.Type == CpuSetInformation {
+                let cpu = info.Anonymous.CpuSet;
+                if cpu.Group == 0 {
+                    entries.push((cpu.LogicalProcessorIndex as usize, cpu.EfficiencyClass));
+                }
+            }
+            offset += info.Size as usize;
+        }
+    }
+    
+    let max_class = entries.iter().map(|(_, class)| *class).max()?;
+    let min_class = entries.iter().map(|(_, class)| *class).min()?;
+    if max_class == min_class {
+        return None;
+    }
+    let mut cores: Vec<usize> = entries.into_iter().filter(|(_, class)| *class == max_class).map(|(i, _)| i).collect();
+    cores.sort();
+    Some(cores)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn performance_cores() -> Option<Vec<usize>> {
+    let list = std::fs::read_to_string("/sys/devices/cpu_core/cpus").ok()?;
+    let cores = parse_cpu_list(list.trim());
+    if cores.is_empty() { None } else { Some(cores) }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cores = Vec::new();
+    for part in list.split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                    cores.extend(start..=end);
+                }
+            }
+            None => {
+                if let Ok(core) = part.parse::<usize>() {
+                    cores.push(core);
+                }
+            }
+        }
+    }
+    cores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn empty_selection_means_all_cores() {
+        assert_eq!(selected_cores(&[], 4), [true; 4]);
+        assert!(!is_restricted(&[]));
+        assert!(!is_restricted(&[true, true]));
+    }
+    
+    #[test]
+    fn missing_cores_are_not_selected() {
+        assert_eq!(selected_cores(&[false, true], 4), [false, true, false, false]);
+        assert_eq!(selected_cores(&[true, false, true], 2), [true, false]);
+        assert!(is_restricted(&[false, true]));
+    }
+}
@@ -0,0 +1,311 @@
+use std::io;
+
+use log::info;
+
+#[derive(Clone)]
+pub struct AudioDevice {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Direction {
+    Output,
+    Input,
+}
+
+struct SavedDefaults {
+    output: Option<String>,
+    input: Option<String>,
+}
+
+pub struct AudioSwitcher {
+    saved: Option<SavedDefaults>,
+}
+
+impl AudioSwitcher {
+    pub fn new() -> Self {
+        Self { saved: None }
+    }
+    
+    pub fn switch_to_headset(&mut self, output_pattern: &str, input_pattern: &str) -> io::Result<String> {
+        let mut saved = Some(SavedDefaults {
+            output: backend::default_device(Direction::Output).ok(),
+            input: backend::default_device(Direction::Input).ok(),
+        });
+        
+        let mut switched = Vec::new();
+        for (direction, pattern) in [(Direction::Output, output_pattern), (Direction::Input, input_pattern)] {
+            if pattern.trim().is_empty() {
+                continue;
+            }
+            let devices = backend::list_devices(direction)?;
+            match devices.iter().find(|d| matches_pattern(&d.name, pattern)) {
+                Some(device) => {
+                    // Saved before the first switch, so a failing second switch still leaves the first restorable.
+                    if self.saved.is_none() {
+                        self.saved = saved.take();
+                    }
+                    backend::set_default_device(direction, &device.id)?;
+                    info!("Switched default {} to {}", direction.label(), device.name);
+                    switched.push(device.name.clone());
+                }
+                None => info!("No {} device matches '{}'", direction.label(), pattern),
+            }
+        }
+        
+        if switched.is_empty() {
+            Ok(String::from("No headset audio devices matched"))
+        } else {
+            Ok(format!("Switched to {}", switched.join(", ")))
+        }
+    }
+    
+    // Restores both directions even if one fails; whatever failed stays saved for the next attempt.
+    pub fn restore(&mut self) -> io::Result<Option<String>> {
+        let saved = match &mut self.saved {
+            Some(saved) => saved,
+            None => return Ok(None),
+        };
+        
+        let mut errors = Vec::new();
+        for (direction, id) in [(Direction::Output, &mut saved.output), (Direction::Input, &mut saved.input)] {
+            if let Some(device) = id.as_deref() {
+                match backend::set_default_device(direction, device) {
+                    Ok(()) => *id = None,
+                    Err(e) => errors.push(format!("{}: {}", direction.label(), e)),
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(io::Error::other(errors.join("; ")));
+        }
+        self.saved = None;
+        info!("Restored desktop audio devices");
+        Ok(Some(String::from("Restored desktop audio devices")))
+    }
+}
+
+impl Direction {
+    fn label(&self) -> &'static str {
+        match self {
+            Direction::Output => "output",
+            Direction::Input => "input",
+        }
+    }
+}
+
+pub fn list_devices(direction: Direction) -> io::Result<Vec<AudioDevice>> {
+    backend::list_devices(direction)
+}
+
+pub fn matches_pattern(name: &str, pattern: &str) -> bool {
+    let name = name.to_lowercase();
+    let pattern = pattern.trim().to_lowercase();
+    if !pattern.contains('*') {
+        return name.contains(&pattern);
+    }
+    
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let mut rest = name.as_str();
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(remaining) => rest = remaining,
+                None => return false,
+            }
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    parts.last().is_none_or(|last| last.is_empty() || name.ends_with(last))
+}
+
+#[cfg(target_os = "windows")]
+mod backend {
+    use std::ffi::c_void;
+    use std::io;
+    
+    use windows::core::{interface, IUnknown, IUnknown_Vtbl, GUID, HRESULT, PCWSTR};
+    use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+    use windows::Win32::Media::Audio::*;
+    use windows::Win32::System::Com::*;
+    
+    use super::{AudioDevice, Direction};
+    use crate::priority::win32_error;
+    
+    const CLSID_POLICY_CONFIG_CLIENT: GUID = GUID::from_u128(0x870af99c_171d_4f9e_af0d_e63df40c2bc9);
+    
+    // Undocumented interface used by the Windows sound control panel to change the default endpoint.
+    #[interface("f8679f50-850a-41cf-9c72-430f290290c8")]
+    unsafe trait IPolicyConfig: IUnknown {
+        fn GetMixFormat(&self, device: PCWSTR, format: *mut *mut c_void) -> HRESULT;
+        fn GetDeviceFormat(&self, device: PCWSTR, default: i32, format: *mut *mut c_void) -> HRESULT;
+        fn ResetDeviceFormat(&self, device: PCWSTR) -> HRESULT;
+        fn SetDeviceFormat(&self, device: PCWSTR, endpoint: *mut c_void, mix: *mut c_void) -> HRESULT;
+        fn GetProcessingPeriod(&self, device: PCWSTR, default: i32, default_period: *mut i64, min_period: *mut i64) -> HRESULT;
+        fn SetProcessingPeriod(&self, device: PCWSTR, period: *mut i64) -> HRESULT;
+        fn GetShareMode(&self, device: PCWSTR, mode: *mut c_void) -> HRESULT;
+        fn SetShareMode(&self, device: PCWSTR, mode: *mut c_void) -> HRESULT;
+        fn GetPropertyValue(&self, device: PCWSTR, key: *const c_void, value: *mut c_void) -> HRESULT;
+        fn SetPropertyValue(&self, device: PCWSTR, key: *const c_void, value: *mut c_void) -> HRESULT;
+        fn SetDefaultEndpoint(&self, device: PCWSTR, role: ERole) -> HRESULT;
+        fn SetEndpointVisibility(&self, device: PCWSTR, visible: i32) -> HRESULT;
+    }
+    
+    fn data_flow(direction: Direction) -> EDataFlow {
+        match direction {
+            Direction::Output => eRender,
+            Direction::Input => eCapture,
+        }
+    }
+    
+    fn enumerator() -> io::Result<IMMDeviceEnumerator> {
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).map_err(win32_error)
+        }
+    }
+    
+    unsafe fn device_id(device: &IMMDevice) -> io::Result<String> {
+        let id = device.GetId().map_err(win32_error)?;
+        let result = id.to_string().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+        CoTaskMemFree(Some(id.0 as *const c_void));
+        result
+    }
+    
+    pub fn list_devices(direction: Direction) -> io::Result<Vec<AudioDevice>> {
+        let enumerator = enumerator()?;
+        let mut devices = Vec::new();
+        unsafe {
+            let collection = enumerator
+                .EnumAudioEndpoints(data_flow(direction), DEVICE_STATE_ACTIVE)
+                .map_err(win32_error)?;
+            for i in 0..collection.GetCount().map_err(win32_error)? {
+                let device = collection.Item(i).map_err(win32_error)?;
+                let id = device_id(&device)?;
+                let name = device
+                    .OpenPropertyStore(STGM_READ)
+                    .and_then(|store| store.GetValue(&PKEY_Device_FriendlyName))
+                    .map(|value| value.to_string())
+                    .unwrap_or_else(|_| id.clone());
+                devices.push(AudioDevice { id, name });
+            }
+        }
+        Ok(devices)
+    }
+    
+    pub fn default_device(direction: Direction) -> io::Result<String> {
+        let enumerator = enumerator()?;
+        unsafe {
+            let device = enumerator
+                .GetDefaultAudioEndpoint(data_flow(direction), eConsole)
+                .map_err(win32_error)?;
+            device_id(&device)
+        }
+    }
+    
+    pub fn set_default_device(_direction: Direction, id: &str) -> io::Result<()> {
+        let wide: Vec<u16> = id.encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let policy: IPolicyConfig = CoCreateInstance(&CLSID_POLICY_CONFIG_CLIENT, None, CLSCTX_ALL)
+                .map_err(win32_error)?;
+            for role in [eConsole, eMultimedia, eCommunications] {
+                policy.SetDefaultEndpoint(PCWSTR(wide.as_ptr()), role).ok().map_err(win32_error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod backend {
+    use std::io;
+    use std::process::Command;
+    
+    use super::{AudioDevice, Direction};
+    
+    // The field labels parsed below are translated in other locales.
+    fn pactl(args: &[&str]) -> io::Result<String> {
+        let output = Command::new("pactl").args(args).env("LC_ALL", "C").output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "pactl {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+    
+    pub fn list_devices(direction: Direction) -> io::Result<Vec<AudioDevice>> {
+        let kind = match direction {
+            Direction::Output => "sinks",
+            Direction::Input => "sources",
+        };
+        let output = pactl(&["list", kind])?;
+        
+        let mut devices = Vec::new();
+        let mut id: Option<String> = None;
+        for line in output.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix("Name: ") {
+                id = Some(name.to_string());
+            } else if let Some(description) = line.strip_prefix("Description: ") {
+                if let Some(id) = id.take() {
+                    // Monitor sources mirror an output and are never a real microphone.
+                    if direction == Direction::Input && id.ends_with(".monitor") {
+                        continue;
+                    }
+                    devices.push(AudioDevice { id, name: description.to_string() });
+                }
+            }
+        }
+        Ok(devices)
+    }
+    
+    pub fn default_device(direction: Direction) -> io::Result<String> {
+        let prefix = match direction {
+            Direction::Output => "Default Sink: ",
+            Direction::Input => "Default Source: ",
+        };
+        pactl(&["info"])?
+            .lines()
+            .find_map(|line| line.strip_prefix(prefix).map(|s| s.trim().to_string()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no default device reported by pactl"))
+    }
+    
+    pub fn set_default_device(direction: Direction, id: &str) -> io::Result<()> {
+        let command = match direction {
+            Direction::Output => "set-default-sink",
+            Direction::Input => "set-default-source",
+        };
+        pactl(&[command, id]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn plain_patterns_match_anywhere() {
+        assert!(matches_pattern("Headphones (Oculus Virtual Audio Device)", "oculus"));
+        assert!(!matches_pattern("Speakers (Realtek Audio)", "oculus"));
+    }
+    
+    #[test]
+    fn wildcards_anchor_prefix_and_suffix() {
+        assert!(matches_pattern("Headset Microphone (Quest)", "headset*"));
+        assert!(!matches_pattern("USB Headset", "headset*"));
+        assert!(matches_pattern("Microphone (Index HMD)", "*hmd)"));
+        assert!(!matches_pattern("Index HMD Microphone", "*hmd"));
+        assert!(matches_pattern("Speakers (Valve Index HMD)", "*valve*hmd*"));
+        assert!(!matches_pattern("HMD Speakers (Valve)", "*valve*hmd*"));
+        assert!(!matches_pattern("Speakers (Realtek Audio)", "*quest*"));
+    }
+}
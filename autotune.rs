@@ -0,0 +1,374 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::info;
+
+use crate::config;
+use crate::settings_diff;
+use crate::{FoveatedLevel, VRSettings};
+
+const HISTORY_FILE: &str = "autotune_history.json";
+// Sessions shorter than this say nothing useful about sustained performance.
+const MIN_SAMPLES: usize = 600;
+// Quality goes down after one bad session but only comes back up after this many good ones.
+const SESSIONS_BEFORE_STEP_UP: u32 = 2;
+const RENDER_SCALE_STEP: f32 = 0.1;
+const UPSCALING_SCALE_STEP: f32 = 0.05;
+const MIN_UPSCALING_SCALE: f32 = 0.5;
+
+// Frame time columns written by PresentMon and MangoHud respectively.
+const FRAME_TIME_COLUMNS: &[&str] = &["MsBetweenPresents", "frametime"];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FrameStats {
+    pub samples: usize,
+    pub p50_ms: f32,
+    pub p95_ms: f32,
+    pub p99_ms: f32,
+}
+
+impl FrameStats {
+    pub fn from_samples(mut samples: Vec<f32>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f32| samples[((samples.len() - 1) as f32 * p).round() as usize];
+        Some(Self { samples: samples.len(), p50_ms: percentile(0.5), p95_ms: percentile(0.95), p99_ms: percentile(0.99) })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Up,
+    Hold,
+    Down,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TuneRecord {
+    pub timestamp: u64,
+    pub target_fps: u32,
+    pub stats: FrameStats,
+    pub direction: Direction,
+    pub changes: Vec<settings_diff::FieldChange>,
+    pub note: String,
+    #[serde(default)]
+    pub reverted: bool,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct TuneHistory {
+    pub records: Vec<TuneRecord>,
+    // Consecutive sessions that asked for more quality without acting on it yet.
+    pub pending_up: u32,
+}
+
+impl TuneHistory {
+    pub fn path() -> PathBuf {
+        config::path(HISTORY_FILE)
+    }
+    
+    pub fn load() -> Self {
+        config::read_with_backup(&Self::path()).unwrap_or_default()
+    }
+    
+    pub fn save(&self) -> io::Result<()> {
+        config::write_atomic(&Self::path(), serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+// Remembers where the frame time log ended when the runtime started, so only this session is measured.
+pub struct Session {
+    log: PathBuf,
+    offset: u64,
+}
+
+impl Session {
+    pub fn begin(log: &Path) -> Self {
+        let offset = fs::metadata(log).map(|m| m.len()).unwrap_or(0);
+        Self { log: log.to_path_buf(), offset }
+    }
+    
+    pub fn frame_times(&self) -> io::Result<Vec<f32>> {
+        let contents = fs::read_to_string(&self.log)?;
+        // A log that shrank was rotated or recreated, so all of it belongs to this session.
+        let offset = if (contents.len() as u64) < self.offset { 0 } else { self.offset as usize };
+        parse_frame_times(&contents, offset)
+    }
+}
+
+fn parse_frame_times(contents: &str, offset: usize) -> io::Result<Vec<f32>> {
+    let mut column = None;
+    let mut samples = Vec::new();
+    let mut position = 0;
+    // Split on '\n' only so CRLF logs keep byte positions exact; cells are trimmed anyway.
+    for line in contents.split('\n') {
+        let start = position;
+        position += line.len() + 1;
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        if let Some(index) = cells.iter().position(|cell| FRAME_TIME_COLUMNS.contains(cell)) {
+            column = Some(index);
+            continue;
+        }
+        if start < offset {
+            continue;
+        }
+        if let Some(value) = column.and_then(|index| cells.get(index)).and_then(|cell| cell.parse::<f32>().ok()) {
+            if value > 0.0 {
+                samples.push(value);
+            }
+        }
+    }
+    if column.is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("no frame time column ({}) found", FRAME_TIME_COLUMNS.join(", ")),
+        ));
+    }
+    Ok(samples)
+}
+
+pub struct Bounds {
+    pub min_render_scale: f32,
+    pub max_render_scale: f32,
+}
+
+// The frame budget is the refresh interval minus the headroom; between budget and interval the settings are held.
+pub fn decide(stats: &FrameStats, target_fps: u32, headroom_percent: u32) -> Direction {
+    let interval = 1000.0 / target_fps.max(1) as f32;
+    let budget = interval * (1.0 - headroom_percent.min(50) as f32 / 100.0);
+    if stats.p95_ms > interval {
+        Direction::Down
+    } else if stats.p95_ms < budget {
+        Direction::Up
+    } else {
+        Direction::Hold
+    }
+}
+
+// Takes one step on the first knob that can still move, cheapest quality loss first.
+pub fn step(settings: &VRSettings, direction: Direction, bounds: &Bounds) -> VRSettings {
+    let mut tuned = settings.clone();
+    match direction {
+        Direction::Down => {
+            if tuned.render_scale - RENDER_SCALE_STEP >= bounds.min_render_scale - f32::EPSILON {
+                tuned.render_scale = round(tuned.render_scale - RENDER_SCALE_STEP);
+            } else if let Some(level) = tuned.foveated_rendering.then(|| stronger(&tuned.foveated_level)).flatten() {
+                tuned.foveated_level = level;
+            } else if tuned.upscaling_enabled && tuned.upscaling_scale - UPSCALING_SCALE_STEP >= MIN_UPSCALING_SCALE - f32::EPSILON {
+                tuned.upscaling_scale = round(tuned.upscaling_scale - UPSCALING_SCALE_STEP);
+            }
+        }
+        Direction::Up => {
+            if tuned.upscaling_enabled && tuned.upscaling_scale + UPSCALING_SCALE_STEP <= 1.0 + f32::EPSILON {
+                tuned.upscaling_scale = round(tuned.upscaling_scale + UPSCALING_SCALE_STEP);
+            } else if let Some(level) = tuned.foveated_rendering.then(|| weaker(&tuned.foveated_level)).flatten() {
+                tuned.foveated_level = level;
+            } else if tuned.render_scale + RENDER_SCALE_STEP <= bounds.max_render_scale + f32::EPSILON {
+                tuned.render_scale = round(tuned.render_scale + RENDER_SCALE_STEP);
+            }
+        }
+        Direction::Hold => {}
+    }
+    tuned
+}
+
+fn round(value: f32) -> f32 {
+    (value * 100.0).round() / 100.0
+}
+
+fn stronger(level: &FoveatedLevel) -> Option<FoveatedLevel> {
+    match level {
+        FoveatedLevel::Off => Some(FoveatedLevel::Low),
+        FoveatedLevel::Low => Some(FoveatedLevel::Medium),
+        FoveatedLevel::Medium => Some(FoveatedLevel::High),
+        FoveatedLevel::High => Some(FoveatedLevel::HighTop),
+        FoveatedLevel::HighTop => None,
+    }
+}
+
+fn weaker(level: &FoveatedLevel) -> Option<FoveatedLevel> {
+    match level {
+        FoveatedLevel::Off => None,
+        FoveatedLevel::Low => Some(FoveatedLevel::Off),
+        FoveatedLevel::Medium => Some(FoveatedLevel::Low),
+        FoveatedLevel::High => Some(FoveatedLevel::Medium),
+        FoveatedLevel::HighTop => Some(FoveatedLevel::High),
+    }
+}
+
+// Measures the finished session, records it in `history` and returns the settings to use for the next one.
+// The caller saves the history, since only the instance holding the settings lock may write it.
+pub fn finish_session(
+    session: &Session,
+    settings: &VRSettings,
+    bounds: &Bounds,
+    history: &mut TuneHistory,
+) -> io::Result<Option<VRSettings>> {
+    let samples = session.frame_times()?;
+    if samples.len() < MIN_SAMPLES {
+        info!("Auto-tune skipped: only {} frame(s) logged this session", samples.len());
+        return Ok(None);
+    }
+    let stats = match FrameStats::from_samples(samples) {
+        Some(stats) => stats,
+        None => return Ok(None),
+    };
+    
+    let target_fps = settings.custom_fps;
+    let mut direction = decide(&stats, target_fps, settings.autotune_headroom_percent);
+    let note = match direction {
+        Direction::Up => {
+            history.pending_up += 1;
+            if history.pending_up < SESSIONS_BEFORE_STEP_UP {
+                direction = Direction::Hold;
+                format!("headroom seen {} of {} sessions", history.pending_up, SESSIONS_BEFORE_STEP_UP)
+            } else {
+                history.pending_up = 0;
+                String::from("consistent headroom, raising quality")
+            }
+        }
+        Direction::Down => {
+            history.pending_up = 0;
+            String::from("missed frames, lowering quality")
+        }
+        Direction::Hold => {
+            history.pending_up = 0;
+            String::from("within budget")
+        }
+    };
+    
+    let tuned = step(settings, direction, bounds);
+    let changes = settings_diff::changes(&settings_diff::to_fields(settings), &settings_diff::to_fields(&tuned));
+    let note = if direction != Direction::Hold && changes.is_empty() {
+        format!("{}, but every setting is at its bound", note)
+    } else {
+        note
+    };
+    info!(
+        "Auto-tune: p50 {:.2} ms, p95 {:.2} ms, p99 {:.2} ms at {} fps target: {} ({} change(s))",
+        stats.p50_ms,
+        stats.p95_ms,
+        stats.p99_ms,
+        target_fps,
+        note,
+        changes.len()
+    );
+    
+    let applied = !changes.is_empty();
+    history.records.push(TuneRecord {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        target_fps,
+        stats,
+        direction,
+        changes,
+        note,
+        reverted: false,
+    });
+    Ok(if applied { Some(tuned) } else { None })
+}
+
+// Puts back the values a record replaced, leaving everything else as it is now.
+pub fn revert(settings: &VRSettings, record: &TuneRecord) -> serde_json::Result<VRSettings> {
+    let fields: settings_diff::Fields = record.changes.iter().map(|c| (c.field.clone(), c.from.clone())).collect();
+    settings_diff::with_fields(settings, &fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn stats(p95_ms: f32) -> FrameStats {
+        FrameStats { samples: MIN_SAMPLES, p50_ms: p95_ms, p95_ms, p99_ms: p95_ms }
+    }
+    
+    #[test]
+    fn percentiles_from_samples() {
+        assert!(FrameStats::from_samples(Vec::new()).is_none());
+        
+        let samples: Vec<f32> = (1..=100).rev().map(|ms| ms as f32).collect();
+        let stats = FrameStats::from_samples(samples).unwrap();
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.p50_ms, 51.0);
+        assert_eq!(stats.p95_ms, 95.0);
+        assert_eq!(stats.p99_ms, 99.0);
+    }
+    
+    #[test]
+    fn decide_against_interval_and_headroom() {
+        // 90 fps is an 11.1 ms interval; 10 % headroom leaves a 10 ms budget.
+        assert!(decide(&stats(12.0), 90, 10) == Direction::Down);
+        assert!(decide(&stats(10.5), 90, 10) == Direction::Hold);
+        assert!(decide(&stats(9.0), 90, 10) == Direction::Up);
+        // Headroom is capped at half the interval, so a silly value cannot push the budget to zero.
+        assert!(decide(&stats(6.0), 90, 100) == Direction::Hold);
+        assert!(decide(&stats(5.0), 90, 100) == Direction::Up);
+    }
+    
+    #[test]
+    fn step_down_uses_cheapest_knob_first() {
+        let bounds = Bounds { min_render_scale: 1.0, max_render_scale: 1.5 };
+        let settings = VRSettings {
+            render_scale: 1.1,
+            foveated_rendering: true,
+            foveated_level: FoveatedLevel::High,
+            upscaling_enabled: true,
+            upscaling_scale: 0.8,
+            ..Default::default()
+        };
+        
+        let tuned = step(&settings, Direction::Down, &bounds);
+        assert_eq!(tuned.render_scale, 1.0);
+        
+        let tuned = step(&tuned, Direction::Down, &bounds);
+        assert_eq!(tuned.render_scale, 1.0);
+        assert!(tuned.foveated_level == FoveatedLevel::HighTop);
+        
+        let tuned = step(&tuned, Direction::Down, &bounds);
+        assert_eq!(tuned.upscaling_scale, 0.75);
+        
+        assert!(step(&tuned, Direction::Hold, &bounds) == tuned);
+    }
+    
+    #[test]
+    fn step_up_restores_in_reverse_order() {
+        let bounds = Bounds { min_render_scale: 1.0, max_render_scale: 1.2 };
+        let settings = VRSettings {
+            render_scale: 1.2,
+            foveated_rendering: true,
+            foveated_level: FoveatedLevel::Low,
+            upscaling_enabled: true,
+            upscaling_scale: 0.95,
+            ..Default::default()
+        };
+        
+        let tuned = step(&settings, Direction::Up, &bounds);
+        assert_eq!(tuned.upscaling_scale, 1.0);
+        let tuned = step(&tuned, Direction::Up, &bounds);
+        assert!(tuned.foveated_level == FoveatedLevel::Off);
+        // Everything is at its best already, and the render scale is at its upper bound.
+        let tuned = step(&tuned, Direction::Up, &bounds);
+        assert_eq!(tuned.render_scale, 1.2);
+    }
+    
+    #[test]
+    fn parse_frame_times_from_offset() {
+        let header = "Application,ProcessID,MsBetweenPresents\r\n";
+        let old = "game.exe,1,20.0\r\n";
+        let contents = format!("{}{}game.exe,1,11.5\r\ngame.exe,1,0\r\ngame.exe,1,n/a\r\ngame.exe,1,12.25\r\n", header, old);
+        
+        let all = parse_frame_times(&contents, 0).unwrap();
+        assert_eq!(all, vec![20.0, 11.5, 12.25]);
+        let session = parse_frame_times(&contents, header.len() + old.len()).unwrap();
+        assert_eq!(session, vec![11.5, 12.25]);
+        
+        let mangohud = "fps,frametime,cpu_load\n90,11.1,20\n";
+        assert_eq!(parse_frame_times(mangohud, 0).unwrap(), vec![11.1]);
+        
+        assert_eq!(parse_frame_times("a,b\n1,2\n", 0).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+use sysinfo::System;
+
+use log::LevelFilter;
+use simplelog::{Config, SimpleLogger};
+
+use crate::config;
+use crate::elevation;
+use crate::scripting;
+use crate::support_bundle::{self, BundleOptions};
+use crate::VRSettings;
+
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.first().map(String::as_str) {
+        Some("--support-bundle") => Some(support_bundle_command(&args[1..])),
+        Some("--run-script") => Some(run_script_command(&args[1..])),
+        Some("--helper") => match (args.get(1), args.get(2)) {
+            (Some(request), Some(result)) => Some(elevation::run_helper(Path::new(request), Path::new(result))),
+            _ => {
+                eprintln!("--helper requires a request and a result path");
+                Some(2)
+            }
+        },
+        Some("--help") | Some("-h") => {
+            print_usage();
+            Some(0)
+        }
+        _ => None,
+    }
+}
+
+fn support_bundle_command(args: &[String]) -> i32 {
+    let mut options = BundleOptions::default();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--redact" => options.redact = true,
+            "--output" | "-o" => match iter.next() {
+                Some(path) => options.output = PathBuf::from(path),
+                None => {
+                    eprintln!("--output requires a path");
+                    return 2;
+                }
+            },
+            other => {
+                eprintln!("Unknown option: {}", other);
+                print_usage();
+                return 2;
+            }
+        }
+    }
+    
+    match support_bundle::create_bundle(&options) {
+        Ok(path) => {
+            println!("Support bundle written to {}", path.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to create support bundle: {}", e);
+            1
+        }
+    }
+}
+
+// Runs one apply script against saved settings without the GUI, printing each write it makes.
+fn run_script_command(args: &[String]) -> i32 {
+    let mut script = None;
+    let mut settings_path = config::settings_path();
+    let mut dry_run = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--settings" => match iter.next() {
+                Some(path) => settings_path = PathBuf::from(path),
+                None => {
+                    eprintln!("--settings requires a path");
+                    return 2;
+                }
+            },
+            other if script.is_none() && !other.starts_with("--") => script = Some(PathBuf::from(other)),
+            other => {
+                eprintln!("Unknown option: {}", other);
+                print_usage();
+                return 2;
+            }
+        }
+    }
+    let script = match script {
+        Some(script) => script,
+        None => {
+            eprintln!("--run-script requires a script path");
+            return 2;
+        }
+    };
+    
+    // Script print and log calls go to the console instead of vr_suite.log.
+    let _ = SimpleLogger::init(LevelFilter::Info, Config::default());
+    let settings: VRSettings = config::read_with_backup(&settings_path).unwrap_or_default();
+    let mut sys = System::new();
+    sys.refresh_processes();
+    let run = scripting::run_file(&script, scripting::ScriptContext::new(&settings, &sys, dry_run));
+    let mut failed = false;
+    for step in &run.steps {
+        match &step.result {
+            Ok(()) => println!("ok    {}", step.target),
+            Err(e) => {
+                failed = true;
+                println!("fail  {}: {}", step.target, e);
+            }
+        }
+    }
+    match run.error {
+        Some(error) => {
+            eprintln!("Script failed: {}", error);
+            1
+        }
+        None if failed => 1,
+        None => 0,
+    }
+}
+
+fn print_usage() {
+    println!("Usage:");
+    println!("  vr_suite                                   Start the GUI");
+    println!("  vr_suite --support-bundle [--redact] [--output <file.zip>]");
+    println!("  vr_suite --run-script <script.rhai> [--dry-run] [--settings <settings.json>]");
+}
@@ -0,0 +1,215 @@
+use fs2::FileExt;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+
+const APP_DIR: &str = "vr_suite";
+const LOCK_FILE: &str = "vr_suite.lock";
+pub const SETTINGS_FILE: &str = "settings.json";
+const LOG_DIR: &str = "logs";
+
+// Files that older builds kept next to the executable's working directory.
+const LEGACY_FILES: &[&str] = &[SETTINGS_FILE, "autotune_history.json"];
+const LEGACY_DIRS: &[&str] = &["profiles"];
+
+pub fn config_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("VR_SUITE_CONFIG_DIR") {
+        if !dir.is_empty() {
+            return PathBuf::from(dir);
+        }
+    }
+    platform_config_dir().map(|dir| dir.join(APP_DIR)).unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(target_os = "windows")]
+fn platform_config_dir() -> Option<PathBuf> {
+    std::env::var("APPDATA").ok().filter(|dir| !dir.is_empty()).map(PathBuf::from)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn platform_config_dir() -> Option<PathBuf> {
+    match std::env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => std::env::var("HOME").ok().map(|home| PathBuf::from(home).join(".config")),
+    }
+}
+
+pub fn path(name: &str) -> PathBuf {
+    config_dir().join(name)
+}
+
+pub fn settings_path() -> PathBuf {
+    path(SETTINGS_FILE)
+}
+
+pub fn log_dir() -> PathBuf {
+    path(LOG_DIR)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+// Moves files from the working directory into the config directory the first time they are missing there.
+pub fn migrate_legacy() -> io::Result<()> {
+    let dir = config_dir();
+    fs::create_dir_all(&dir)?;
+    if fs::canonicalize(&dir)? == fs::canonicalize(".")? {
+        return Ok(());
+    }
+    for name in LEGACY_FILES {
+        let (from, to) = (Path::new(name), dir.join(name));
+        if from.is_file() && !to.exists() {
+            fs::copy(from, &to)?;
+            info!("Migrated {} to {}", name, to.display());
+        }
+    }
+    for name in LEGACY_DIRS {
+        let (from, to) = (Path::new(name), dir.join(name));
+        if from.is_dir() && !to.exists() {
+            fs::create_dir_all(&to)?;
+            for entry in fs::read_dir(from)?.flatten() {
+                if entry.path().is_file() {
+                    fs::copy(entry.path(), to.join(entry.file_name()))?;
+                }
+            }
+            info!("Migrated {} to {}", name, to.display());
+        }
+    }
+    Ok(())
+}
+
+// Writes to a temp file, keeps the previous contents as .bak, then renames into place so the file is never half-written.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp = path.with_file_name(temp_name);
+    
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+    
+    if path.exists() {
+        if let Err(e) = fs::copy(path, backup_path(path)) {
+            warn!("Failed to back up {}: {}", path.display(), e);
+        }
+    }
+    fs::rename(&temp, path)
+}
+
+// Falls back to the .bak copy when the file is missing or does not parse.
+pub fn read_with_backup<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let read = |path: &Path| -> Result<T, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&contents).map_err(|e| e.to_string())
+    };
+    match read(path) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            let backup = backup_path(path);
+            if !backup.exists() {
+                return None;
+            }
+            if path.exists() {
+                warn!("Failed to read {}: {}, trying {}", path.display(), e, backup.display());
+            }
+            match read(&backup) {
+                Ok(value) => {
+                    info!("Restored {} from {}", path.display(), backup.display());
+                    Some(value)
+                }
+                Err(e) => {
+                    warn!("Failed to read {}: {}", backup.display(), e);
+                    None
+                }
+            }
+        }
+    }
+}
+
+// Held for the life of the process; a second instance runs without it and refuses to save.
+pub struct InstanceLock {
+    _file: fs::File,
+}
+
+impl InstanceLock {
+    pub fn acquire() -> io::Result<Option<Self>> {
+        let dir = config_dir();
+        fs::create_dir_all(&dir)?;
+        let mut file = fs::OpenOptions::new().read(true).write(true).create(true).truncate(false).open(dir.join(LOCK_FILE))?;
+        match file.try_lock_exclusive() {
+            Ok(()) => {
+                file.set_len(0)?;
+                write!(file, "{}", std::process::id())?;
+                Ok(Some(Self { _file: file }))
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// The pid the lock holder wrote, for telling the user which instance to close.
+pub fn lock_owner() -> String {
+    fs::read_to_string(config_dir().join(LOCK_FILE)).unwrap_or_default().trim().to_string()
+}
+
+pub fn locked_error() -> io::Error {
+    io::Error::other("another instance holds the settings lock; close it to save here")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vr_suite_config_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn write_keeps_the_previous_contents_as_backup() {
+        let dir = temp_dir("write");
+        let path = dir.join("settings.json");
+        
+        write_atomic(&path, b"[1]").unwrap();
+        assert!(!backup_path(&path).exists());
+        
+        write_atomic(&path, b"[2]").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"[2]");
+        assert_eq!(fs::read(backup_path(&path)).unwrap(), b"[1]");
+        assert!(!dir.join("settings.json.tmp").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn corrupt_file_falls_back_to_backup() {
+        let dir = temp_dir("corrupt");
+        let path = dir.join("settings.json");
+        write_atomic(&path, b"[1]").unwrap();
+        write_atomic(&path, b"[2]").unwrap();
+        
+        fs::write(&path, b"[2").unwrap();
+        assert_eq!(read_with_backup::<Vec<u32>>(&path), Some(vec![1]));
+        
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read_with_backup::<Vec<u32>>(&path), Some(vec![1]));
+        
+        fs::write(backup_path(&path), b"not json").unwrap();
+        assert_eq!(read_with_backup::<Vec<u32>>(&path), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,388 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+
+use crate::report::ApplyReport;
+
+// The only operations the elevated helper will perform. Anything else in a request is rejected.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum HelperCommand {
+    #[cfg(target_os = "windows")]
+    SetOpenXrRuntime { runtime: String },
+    #[cfg(target_os = "windows")]
+    OculusKiller { enable: bool, dash_dir: std::path::PathBuf, replacement: Option<std::path::PathBuf> },
+    #[cfg(target_os = "linux")]
+    SetPowerPlan { plan: crate::PowerPlan },
+    #[cfg(target_os = "linux")]
+    RestorePowerState { state: crate::power::PowerState },
+    #[cfg(target_os = "linux")]
+    SetProcessPriority { pid: u32, cpu: crate::CpuPriority, io: crate::IoPriority },
+}
+
+impl HelperCommand {
+    fn describe(&self) -> String {
+        match self {
+            #[cfg(target_os = "windows")]
+            HelperCommand::SetOpenXrRuntime { runtime } => format!("set OpenXR runtime to {}", runtime),
+            #[cfg(target_os = "windows")]
+            HelperCommand::OculusKiller { enable, .. } => format!("{} OculusKiller", if *enable { "enable" } else { "disable" }),
+            #[cfg(target_os = "linux")]
+            HelperCommand::SetPowerPlan { .. } => String::from("set CPU governor"),
+            #[cfg(target_os = "linux")]
+            HelperCommand::RestorePowerState { .. } => String::from("restore CPU governor"),
+            #[cfg(target_os = "linux")]
+            HelperCommand::SetProcessPriority { pid, .. } => format!("set priority of pid {}", pid),
+        }
+    }
+    
+    fn validate(&self) -> io::Result<()> {
+        match self {
+            #[cfg(target_os = "windows")]
+            HelperCommand::SetOpenXrRuntime { runtime } => {
+                if runtime != "oculus" && runtime != "steamvr" {
+                    return Err(invalid(format!("unknown OpenXR runtime '{}'", runtime)));
+                }
+            }
+            #[cfg(target_os = "windows")]
+            HelperCommand::OculusKiller { dash_dir, replacement, .. } => {
+                let tail: Vec<String> = dash_dir
+                    .components()
+                    .rev()
+                    .take(3)
+                    .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+                    .collect();
+                if tail != ["bin", "dash", "oculus-dash"] || !dash_dir.is_dir() {
+                    return Err(invalid(format!("{} is not an OculusDash directory", dash_dir.display())));
+                }
+                if let Some(replacement) = replacement {
+                    let is_exe = replacement.extension().is_some_and(|e| e.eq_ignore_ascii_case("exe"));
+                    if !is_exe || !replacement.is_file() {
+                        return Err(invalid(format!("{} is not an executable", replacement.display())));
+                    }
+                }
+            }
+            #[cfg(target_os = "linux")]
+            HelperCommand::SetPowerPlan { .. } => {}
+            #[cfg(target_os = "linux")]
+            HelperCommand::RestorePowerState { state } => state.validate(&crate::power::CpuFreq::new())?,
+            #[cfg(target_os = "linux")]
+            HelperCommand::SetProcessPriority { pid, .. } => {
+                let comm = fs::read_to_string(format!("/proc/{}/comm", pid))?;
+                if !crate::VR_PROCESSES.iter().any(|name| comm.trim() == *name) {
+                    return Err(invalid(format!("pid {} ({}) is not a VR process", pid, comm.trim())));
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    fn execute(&self, report: &mut ApplyReport) {
+        match self {
+            #[cfg(target_os = "windows")]
+            HelperCommand::SetOpenXrRuntime { runtime } => {
+                let path = "SOFTWARE\\Khronos\\OpenXR\\1";
+                report.record(
+                    "OpenXR runtime",
+                    crate::registry::display_path(crate::registry::Hive::LocalMachine, path, "ActiveRuntime"),
+                    crate::registry::write_value(crate::registry::Hive::LocalMachine, path, "ActiveRuntime", runtime),
+                );
+            }
+            #[cfg(target_os = "windows")]
+            HelperCommand::OculusKiller { enable, dash_dir, replacement } => {
+                let swap = crate::file_swap::FileSwap::new(dash_dir, "OculusDash.exe");
+                let services = crate::services::ServiceController::new();
+                crate::run_oculus_killer(&swap, *enable, replacement.as_deref(), &services, report);
+            }
+            #[cfg(target_os = "linux")]
+            HelperCommand::SetPowerPlan { plan } => {
+                // The app saved the previous state before its own attempt, and undoes it with RestorePowerState.
+                let mut power = crate::power::PowerManager::new(crate::power::CpuFreq::new());
+                report.record("Power plan", "cpufreq", power.apply(plan));
+            }
+            #[cfg(target_os = "linux")]
+            HelperCommand::RestorePowerState { state } => {
+                let power = crate::power::PowerManager::new(crate::power::CpuFreq::new());
+                report.record("Restore power plan", "cpufreq", power.restore_state(state));
+            }
+            #[cfg(target_os = "linux")]
+            HelperCommand::SetProcessPriority { pid, cpu, io } => {
+                report.record("CPU priority", format!("pid {}", pid), crate::priority::set_process_priority(*pid, cpu, io));
+            }
+        }
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(target_os = "windows")]
+pub fn is_elevated() -> bool {
+    use windows::Win32::Foundation::*;
+    use windows::Win32::Security::*;
+    use windows::Win32::System::Threading::*;
+    
+    unsafe {
+        let mut token = HANDLE::default();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
+            return false;
+        }
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut length = 0u32;
+        let result = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut std::ffi::c_void),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut length,
+        );
+        let _ = CloseHandle(token);
+        result.is_ok() && elevation.TokenIsElevated != 0
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_elevated() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+pub fn privilege_name() -> &'static str {
+    if cfg!(target_os = "windows") { "administrator" } else { "root" }
+}
+
+const HELPER_DIR_PREFIX: &str = "vr_suite_helper_";
+const REQUEST_FILE: &str = "request.json";
+const RESULT_FILE: &str = "result.json";
+
+// Runs the commands in a separately launched, elevated copy of this executable and returns its report.
+pub fn run_elevated(commands: &[HelperCommand]) -> io::Result<ApplyReport> {
+    let exe = std::env::current_exe()?;
+    let dir = create_private_dir()?;
+    let request = dir.join(REQUEST_FILE);
+    let result = dir.join(RESULT_FILE);
+    
+    let report = write_new(&request, serde_json::to_string(commands)?.as_bytes())
+        // Created up front so the file stays owned by us after the helper writes to it.
+        .and_then(|_| write_new(&result, b""))
+        .and_then(|_| {
+            let descriptions: Vec<String> = commands.iter().map(HelperCommand::describe).collect();
+            info!("Requesting elevation to {}", descriptions.join(", "));
+            launch_elevated(&exe, &request, &result)
+        })
+        .and_then(|_| {
+            let contents = fs::read_to_string(&result)?;
+            serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        });
+    let _ = fs::remove_dir_all(&dir);
+    report
+}
+
+// A fresh directory only we can write to, so nobody can plant or redirect the helper's files.
+fn create_private_dir() -> io::Result<PathBuf> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!("{}{}_{}", HELPER_DIR_PREFIX, std::process::id(), nanos));
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(&dir)?;
+    Ok(dir)
+}
+
+fn write_new(path: &Path, contents: &[u8]) -> io::Result<()> {
+    fs::OpenOptions::new().write(true).create_new(true).open(path)?.write_all(contents)
+}
+
+// The helper only touches files laid out by run_elevated: both in one private helper directory.
+fn check_helper_paths(request: &Path, result: &Path) -> io::Result<()> {
+    let dir = result.parent().unwrap_or(Path::new(""));
+    let in_helper_dir = dir.file_name().is_some_and(|name| name.to_string_lossy().starts_with(HELPER_DIR_PREFIX));
+    if !in_helper_dir
+        || request.parent() != Some(dir)
+        || request.file_name() != Some(REQUEST_FILE.as_ref())
+        || result.file_name() != Some(RESULT_FILE.as_ref())
+    {
+        return Err(invalid(format!("{} and {} are not helper files", request.display(), result.display())));
+    }
+    
+    let metadata = fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() {
+        return Err(invalid(format!("{} is not a directory", dir.display())));
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if metadata.uid() != invoking_uid() || metadata.mode() & 0o077 != 0 {
+            return Err(invalid(format!("{} is not private to the invoking user", dir.display())));
+        }
+    }
+    Ok(())
+}
+
+// pkexec records who asked for elevation; without it the helper was started directly.
+#[cfg(not(target_os = "windows"))]
+fn invoking_uid() -> u32 {
+    std::env::var("PKEXEC_UID")
+        .ok()
+        .and_then(|uid| uid.parse().ok())
+        .unwrap_or_else(|| unsafe { libc::getuid() })
+}
+
+// Opens the existing result file without following links and checks it before truncating.
+fn open_result(result: &Path) -> io::Result<fs::File> {
+    if !fs::symlink_metadata(result)?.is_file() {
+        return Err(invalid(format!("{} is not a regular file", result.display())));
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(libc::O_NOFOLLOW);
+    }
+    let file = options.open(result)?;
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Err(invalid(format!("{} is not a regular file", result.display())));
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if metadata.uid() != invoking_uid() || metadata.nlink() != 1 {
+            return Err(invalid(format!("{} is not owned by the invoking user", result.display())));
+        }
+    }
+    file.set_len(0)?;
+    Ok(file)
+}
+
+#[cfg(target_os = "windows")]
+fn launch_elevated(exe: &Path, request: &Path, result: &Path) -> io::Result<()> {
+    let quote = |p: &Path| format!("'{}'", p.display().to_string().replace('\'', "''"));
+    // Start-Process joins the argument list with spaces, so paths need their own double quotes.
+    let argument = |p: &Path| format!("'\"{}\"'", p.display().to_string().replace('\'', "''"));
+    let script = format!(
+        "$p = Start-Process -FilePath {} -ArgumentList '--helper',{},{} -Verb RunAs -Wait -PassThru -WindowStyle Hidden; exit $p.ExitCode",
+        quote(exe),
+        argument(request),
+        argument(result)
+    );
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .status()?;
+    helper_status(status.code())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn launch_elevated(exe: &Path, request: &Path, result: &Path) -> io::Result<()> {
+    let status = Command::new("pkexec")
+        .arg(exe)
+        .arg("--helper")
+        .arg(request)
+        .arg(result)
+        .status()?;
+    // pkexec exits with 126 when the dialog is dismissed and 127 when authorization fails.
+    match status.code() {
+        Some(126) | Some(127) => Err(io::Error::new(io::ErrorKind::PermissionDenied, "authorization was refused")),
+        code => helper_status(code),
+    }
+}
+
+fn helper_status(code: Option<i32>) -> io::Result<()> {
+    match code {
+        Some(0) => Ok(()),
+        Some(code) => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("elevated helper did not run (exit code {}), elevation may have been cancelled", code),
+        )),
+        None => Err(io::Error::other("elevated helper was terminated")),
+    }
+}
+
+// Entry point for `--helper <request> <result>`, running with elevated rights.
+pub fn run_helper(request: &Path, result: &Path) -> i32 {
+    if let Err(e) = check_helper_paths(request, result) {
+        eprintln!("Refusing helper request: {}", e);
+        return 2;
+    }
+    
+    let commands: Vec<HelperCommand> = match fs::read_to_string(request)
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
+    {
+        Ok(commands) => commands,
+        Err(e) => {
+            eprintln!("Invalid helper request: {}", e);
+            return 2;
+        }
+    };
+    
+    let mut report = ApplyReport::new();
+    for command in &commands {
+        match command.validate() {
+            Ok(()) => command.execute(&mut report),
+            Err(e) => {
+                warn!("Rejected helper command: {}", e);
+                report.record("Privileged helper", command.describe(), Err(e));
+            }
+        }
+    }
+    
+    let written = serde_json::to_string(&report)
+        .map_err(io::Error::from)
+        .and_then(|json| open_result(result)?.write_all(json.as_bytes()));
+    match written {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Failed to write helper result: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn helper_accepts_only_its_private_files() {
+        let dir = create_private_dir().unwrap();
+        let request = dir.join(REQUEST_FILE);
+        let result = dir.join(RESULT_FILE);
+        write_new(&request, b"[]").unwrap();
+        write_new(&result, b"").unwrap();
+        // create_new refuses a file that is already there instead of reusing it.
+        assert!(write_new(&result, b"").is_err());
+        
+        check_helper_paths(&request, &result).unwrap();
+        open_result(&result).unwrap().write_all(b"{}").unwrap();
+        
+        assert!(check_helper_paths(&request, &std::env::temp_dir().join(RESULT_FILE)).is_err());
+        assert!(check_helper_paths(&request, &dir.join("passwd")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn helper_refuses_shared_dirs_and_links() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        let dir = create_private_dir().unwrap();
+        let target = dir.join("target");
+        fs::write(&target, "keep").unwrap();
+        std::os::unix::fs::symlink(&target, dir.join(RESULT_FILE)).unwrap();
+        assert!(open_result(&dir.join(RESULT_FILE)).is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "keep");
+        
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(check_helper_paths(&dir.join(REQUEST_FILE), &dir.join(RESULT_FILE)).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
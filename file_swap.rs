@@ -0,0 +1,470 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SwapManifest {
+    pub file_name: String,
+    pub original_sha256: String,
+    pub original_size: u64,
+    // None when the original was removed without installing a replacement.
+    pub installed_sha256: Option<String>,
+    pub swapped_at: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SwapStatus {
+    Original,
+    Swapped,
+    ReplacedByUpdate,
+    Missing,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SwapStep {
+    ReadState,
+    ReadOriginal,
+    WriteBackup,
+    VerifyBackup,
+    ReadReplacement,
+    InstallReplacement,
+    VerifyReplacement,
+    RemoveOriginal,
+    WriteManifest,
+    ReadBackup,
+    RestoreOriginal,
+    VerifyRestore,
+    Cleanup,
+}
+
+impl SwapStep {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SwapStep::ReadState => "reading current state",
+            SwapStep::ReadOriginal => "reading original file",
+            SwapStep::WriteBackup => "writing backup",
+            SwapStep::VerifyBackup => "verifying backup",
+            SwapStep::ReadReplacement => "reading replacement",
+            SwapStep::InstallReplacement => "installing replacement",
+            SwapStep::VerifyReplacement => "verifying replacement",
+            SwapStep::RemoveOriginal => "removing original file",
+            SwapStep::WriteManifest => "writing manifest",
+            SwapStep::ReadBackup => "reading backup",
+            SwapStep::RestoreOriginal => "restoring original file",
+            SwapStep::VerifyRestore => "verifying restored file",
+            SwapStep::Cleanup => "removing backup and manifest",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SwapError {
+    pub step: SwapStep,
+    pub error: io::Error,
+    pub rolled_back: bool,
+}
+
+impl fmt::Display for SwapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed while {}: {}", self.step.label(), self.error)?;
+        if self.rolled_back {
+            write!(f, " (original file restored)")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SwapError {}
+
+fn at(step: SwapStep) -> impl FnOnce(io::Error) -> SwapError {
+    move |error| SwapError { step, error, rolled_back: false }
+}
+
+pub struct FileSwap {
+    dir: PathBuf,
+    file_name: String,
+}
+
+impl FileSwap {
+    pub fn new(dir: impl Into<PathBuf>, file_name: &str) -> Self {
+        Self { dir: dir.into(), file_name: file_name.to_string() }
+    }
+    
+    pub fn target(&self) -> PathBuf {
+        self.dir.join(&self.file_name)
+    }
+    
+    pub fn backup(&self) -> PathBuf {
+        self.dir.join(format!("{}.bak", self.file_name))
+    }
+    
+    pub fn manifest_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.swap.json", self.file_name))
+    }
+    
+    fn temp_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.tmp", self.file_name))
+    }
+    
+    pub fn manifest(&self) -> io::Result<Option<SwapManifest>> {
+        match fs::read_to_string(self.manifest_path()) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    
+    pub fn status(&self) -> io::Result<SwapStatus> {
+        let current = hash_if_exists(&self.target())?;
+        let manifest = match self.manifest()? {
+            Some(manifest) => manifest,
+            None => {
+                return Ok(if current.is_some() { SwapStatus::Original } else { SwapStatus::Missing });
+            }
+        };
+        
+        // Anything other than what we installed means an update wrote a fresh copy.
+        if current == manifest.installed_sha256 {
+            Ok(SwapStatus::Swapped)
+        } else {
+            Ok(SwapStatus::ReplacedByUpdate)
+        }
+    }
+    
+    pub fn install(&self, replacement: Option<&Path>) -> Result<String, SwapError> {
+        match self.status().map_err(at(SwapStep::ReadState))? {
+            SwapStatus::Swapped => return self.switch_replacement(replacement),
+            SwapStatus::Missing if self.backup().exists() => {
+                return Ok(format!("{} is already removed", self.file_name));
+            }
+            SwapStatus::Missing => {
+                return Err(at(SwapStep::ReadOriginal)(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} not found in {}", self.file_name, self.dir.display()),
+                )));
+            }
+            SwapStatus::ReplacedByUpdate => {
+                info!("{} was replaced by an update, backing up the new version", self.file_name);
+            }
+            SwapStatus::Original => {}
+        }
+        
+        let original = fs::read(self.target()).map_err(at(SwapStep::ReadOriginal))?;
+        let original_sha256 = sha256_hex(&original);
+        
+        write_verified(&self.temp_path(), &self.backup(), &original, &original_sha256)
+            .map_err(|(step, error)| SwapError {
+                step: if step == SwapStep::VerifyReplacement { SwapStep::VerifyBackup } else { SwapStep::WriteBackup },
+                error,
+                rolled_back: false,
+            })?;
+        
+        let installed = match self.replace_target(replacement) {
+            Ok(installed) => installed,
+            Err((step, error)) => return Err(self.roll_back(step, error, &original_sha256)),
+        };
+        
+        let manifest = SwapManifest {
+            file_name: self.file_name.clone(),
+            original_sha256: original_sha256.clone(),
+            original_size: original.len() as u64,
+            installed_sha256: installed.clone(),
+            swapped_at: unix_now(),
+        };
+        if let Err(error) = self.write_manifest(&manifest) {
+            return Err(self.roll_back(SwapStep::WriteManifest, error, &original_sha256));
+        }
+        Ok(self.installed_message(installed))
+    }
+    
+    // Whether the file in place is the one installing `replacement` would put there.
+    pub fn installed_matches(&self, replacement: Option<&Path>) -> io::Result<bool> {
+        let installed = self.manifest()?.and_then(|manifest| manifest.installed_sha256);
+        Ok(installed == replacement_hash(replacement)?)
+    }
+    
+    // Already swapped, so the backup still holds the original and only the replacement changes.
+    fn switch_replacement(&self, replacement: Option<&Path>) -> Result<String, SwapError> {
+        let Some(mut manifest) = self.manifest().map_err(at(SwapStep::ReadState))? else {
+            return Ok(format!("{} is already replaced", self.file_name));
+        };
+        if manifest.installed_sha256 == replacement_hash(replacement).map_err(at(SwapStep::ReadReplacement))? {
+            return Ok(format!("{} is already replaced", self.file_name));
+        }
+        
+        let installed = match self.replace_target(replacement) {
+            Ok(installed) => installed,
+            Err((step, error)) => return Err(self.roll_back(step, error, &manifest.original_sha256)),
+        };
+        manifest.installed_sha256 = installed.clone();
+        manifest.swapped_at = unix_now();
+        if let Err(error) = self.write_manifest(&manifest) {
+            return Err(self.roll_back(SwapStep::WriteManifest, error, &manifest.original_sha256));
+        }
+        Ok(self.installed_message(installed))
+    }
+    
+    fn installed_message(&self, installed: Option<String>) -> String {
+        match installed {
+            Some(hash) => format!("{} replaced (sha256 {})", self.file_name, short_hash(&hash)),
+            None => format!("{} disabled, original kept as {}", self.file_name, self.backup().display()),
+        }
+    }
+    
+    pub fn restore(&self) -> Result<String, SwapError> {
+        let manifest = self.manifest().map_err(at(SwapStep::ReadState))?;
+        let backup = self.backup();
+        
+        let expected = match &manifest {
+            Some(manifest) => {
+                if self.status().map_err(at(SwapStep::ReadState))? == SwapStatus::ReplacedByUpdate {
+                    // The update already put a newer original in place; the backup is stale.
+                    self.clean_up().map_err(at(SwapStep::Cleanup))?;
+                    return Ok(format!("{} was already restored by an update", self.file_name));
+                }
+                Some(manifest.original_sha256.clone())
+            }
+            // Backups made before manifests existed carry no hash to verify against.
+            None if backup.exists() => None,
+            None => return Ok(format!("{} is not replaced", self.file_name)),
+        };
+        
+        let data = fs::read(&backup).map_err(at(SwapStep::ReadBackup))?;
+        let hash = sha256_hex(&data);
+        if let Some(expected) = &expected {
+            if &hash != expected {
+                return Err(at(SwapStep::VerifyBackup)(hash_mismatch(&backup, expected, &hash)));
+            }
+        }
+        
+        write_verified(&self.temp_path(), &self.target(), &data, &hash).map_err(|(step, error)| SwapError {
+            step: if step == SwapStep::VerifyReplacement { SwapStep::VerifyRestore } else { SwapStep::RestoreOriginal },
+            error,
+            rolled_back: false,
+        })?;
+        
+        self.clean_up().map_err(at(SwapStep::Cleanup))?;
+        Ok(format!("{} restored", self.file_name))
+    }
+    
+    fn replace_target(&self, replacement: Option<&Path>) -> Result<Option<String>, (SwapStep, io::Error)> {
+        match replacement {
+            Some(path) => {
+                let data = fs::read(path).map_err(|e| (SwapStep::ReadReplacement, e))?;
+                let hash = sha256_hex(&data);
+                write_verified(&self.temp_path(), &self.target(), &data, &hash)?;
+                Ok(Some(hash))
+            }
+            None => match fs::remove_file(self.target()) {
+                // Switching from a replacement to none may find the target already gone.
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err((SwapStep::RemoveOriginal, e)),
+                _ => Ok(None),
+            },
+        }
+    }
+    
+    fn write_manifest(&self, manifest: &SwapManifest) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(manifest)?;
+        let temp = self.dir.join(format!("{}.swap.json.tmp", self.file_name));
+        fs::write(&temp, json)?;
+        fs::rename(&temp, self.manifest_path())
+    }
+    
+    fn roll_back(&self, step: SwapStep, error: io::Error, original_sha256: &str) -> SwapError {
+        warn!("{} swap failed while {}: {}, rolling back", self.file_name, step.label(), error);
+        let rolled_back = fs::read(self.backup())
+            .and_then(|data| {
+                write_verified(&self.temp_path(), &self.target(), &data, original_sha256).map_err(|(_, e)| e)
+            })
+            .map_err(|e| warn!("Rolling back {} failed: {}", self.file_name, e))
+            .is_ok();
+        if rolled_back {
+            let _ = self.clean_up();
+        } else {
+            let _ = fs::remove_file(self.manifest_path());
+        }
+        SwapError { step, error, rolled_back }
+    }
+    
+    fn clean_up(&self) -> io::Result<()> {
+        for path in [self.backup(), self.manifest_path()] {
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+// Writes to a temp file, checks the hash, then renames into place so the destination is never half-written.
+fn write_verified(temp: &Path, dest: &Path, data: &[u8], expected: &str) -> Result<(), (SwapStep, io::Error)> {
+    fs::write(temp, data).map_err(|e| (SwapStep::InstallReplacement, e))?;
+    let written = fs::read(temp).map_err(|e| (SwapStep::VerifyReplacement, e))?;
+    let hash = sha256_hex(&written);
+    if hash != expected {
+        let _ = fs::remove_file(temp);
+        return Err((SwapStep::VerifyReplacement, hash_mismatch(temp, expected, &hash)));
+    }
+    fs::rename(temp, dest).map_err(|e| (SwapStep::InstallReplacement, e))?;
+    
+    let installed = fs::read(dest).map_err(|e| (SwapStep::VerifyReplacement, e))?;
+    let hash = sha256_hex(&installed);
+    if hash != expected {
+        return Err((SwapStep::VerifyReplacement, hash_mismatch(dest, expected, &hash)));
+    }
+    Ok(())
+}
+
+fn hash_if_exists(path: &Path) -> io::Result<Option<String>> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(sha256_hex(&data))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn replacement_hash(replacement: Option<&Path>) -> io::Result<Option<String>> {
+    replacement.map(|path| fs::read(path).map(|data| sha256_hex(&data))).transpose()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn hash_mismatch(path: &Path, expected: &str, actual: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} has sha256 {}, expected {}", path.display(), short_hash(actual), short_hash(expected)),
+    )
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn fake_dash(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vr_suite_swap_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("OculusDash.exe"), b"original dash").unwrap();
+        fs::write(dir.join("replacement.exe"), b"replacement").unwrap();
+        dir
+    }
+    
+    #[test]
+    fn install_and_restore_replacement() {
+        let dir = fake_dash("replace");
+        let swap = FileSwap::new(&dir, "OculusDash.exe");
+        assert_eq!(swap.status().unwrap(), SwapStatus::Original);
+        
+        swap.install(Some(&dir.join("replacement.exe"))).unwrap();
+        assert_eq!(swap.status().unwrap(), SwapStatus::Swapped);
+        assert_eq!(fs::read(swap.target()).unwrap(), b"replacement");
+        assert_eq!(fs::read(swap.backup()).unwrap(), b"original dash");
+        
+        // A second install is a no-op rather than backing up the replacement.
+        swap.install(Some(&dir.join("replacement.exe"))).unwrap();
+        assert_eq!(fs::read(swap.backup()).unwrap(), b"original dash");
+        
+        swap.restore().unwrap();
+        assert_eq!(swap.status().unwrap(), SwapStatus::Original);
+        assert_eq!(fs::read(swap.target()).unwrap(), b"original dash");
+        assert!(!swap.backup().exists());
+        assert!(!swap.manifest_path().exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn changed_replacement_is_installed_over_the_swap() {
+        let dir = fake_dash("switch");
+        let swap = FileSwap::new(&dir, "OculusDash.exe");
+        swap.install(Some(&dir.join("replacement.exe"))).unwrap();
+        
+        fs::write(dir.join("other.exe"), b"other replacement").unwrap();
+        assert!(swap.installed_matches(Some(&dir.join("replacement.exe"))).unwrap());
+        assert!(!swap.installed_matches(Some(&dir.join("other.exe"))).unwrap());
+        
+        swap.install(Some(&dir.join("other.exe"))).unwrap();
+        assert_eq!(swap.status().unwrap(), SwapStatus::Swapped);
+        assert_eq!(fs::read(swap.target()).unwrap(), b"other replacement");
+        assert_eq!(fs::read(swap.backup()).unwrap(), b"original dash");
+        
+        swap.install(None).unwrap();
+        assert!(!swap.target().exists());
+        assert_eq!(swap.status().unwrap(), SwapStatus::Swapped);
+        
+        swap.restore().unwrap();
+        assert_eq!(fs::read(swap.target()).unwrap(), b"original dash");
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn install_without_replacement_removes_target() {
+        let dir = fake_dash("remove");
+        let swap = FileSwap::new(&dir, "OculusDash.exe");
+        
+        swap.install(None).unwrap();
+        assert!(!swap.target().exists());
+        assert_eq!(swap.status().unwrap(), SwapStatus::Swapped);
+        
+        swap.restore().unwrap();
+        assert_eq!(fs::read(swap.target()).unwrap(), b"original dash");
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn update_replacing_the_swap_is_detected() {
+        let dir = fake_dash("update");
+        let swap = FileSwap::new(&dir, "OculusDash.exe");
+        swap.install(Some(&dir.join("replacement.exe"))).unwrap();
+        
+        fs::write(swap.target(), b"updated dash").unwrap();
+        assert_eq!(swap.status().unwrap(), SwapStatus::ReplacedByUpdate);
+        
+        // Restoring keeps the updated file and drops the stale backup.
+        swap.restore().unwrap();
+        assert_eq!(fs::read(swap.target()).unwrap(), b"updated dash");
+        assert!(!swap.backup().exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn corrupt_backup_is_not_restored() {
+        let dir = fake_dash("corrupt");
+        let swap = FileSwap::new(&dir, "OculusDash.exe");
+        swap.install(Some(&dir.join("replacement.exe"))).unwrap();
+        
+        fs::write(swap.backup(), b"tampered").unwrap();
+        let error = swap.restore().unwrap_err();
+        assert_eq!(error.step, SwapStep::VerifyBackup);
+        assert_eq!(fs::read(swap.target()).unwrap(), b"replacement");
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn missing_target_is_reported() {
+        let dir = fake_dash("missing");
+        fs::remove_file(dir.join("OculusDash.exe")).unwrap();
+        let swap = FileSwap::new(&dir, "OculusDash.exe");
+        assert_eq!(swap.status().unwrap(), SwapStatus::Missing);
+        assert_eq!(swap.install(None).unwrap_err().step, SwapStep::ReadOriginal);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
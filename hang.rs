@@ -0,0 +1,68 @@
+#[cfg(target_os = "linux")]
+use std::fs;
+
+// A process only counts as frozen when one of its visible windows stops pumping messages,
+// so windowless services like OVRServer never do.
+#[cfg(target_os = "windows")]
+pub fn is_frozen(pid: u32) -> bool {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{EnumWindows, GetWindowThreadProcessId, IsHungAppWindow, IsWindowVisible};
+    
+    struct Search {
+        pid: u32,
+        hung: bool,
+    }
+    
+    unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        let mut owner = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut owner));
+        if owner == search.pid && IsWindowVisible(hwnd).as_bool() && IsHungAppWindow(hwnd).as_bool() {
+            search.hung = true;
+            return BOOL(0);
+        }
+        BOOL(1)
+    }
+    
+    let mut search = Search { pid, hung: false };
+    // Stopping the enumeration early makes EnumWindows report an error, so its result says nothing.
+    let _ = unsafe { EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize)) };
+    search.hung
+}
+
+// Stopped by a signal or a debugger. Uninterruptible sleep (D) is usually a short disk wait, so it does not count.
+#[cfg(target_os = "linux")]
+pub fn is_frozen(pid: u32) -> bool {
+    fs::read_to_string(format!("/proc/{}/stat", pid))
+        .ok()
+        .and_then(|stat| stat_state(&stat))
+        .is_some_and(|state| matches!(state, 'T' | 't'))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn is_frozen(_pid: u32) -> bool {
+    false
+}
+
+// The state follows the command name, which is in parentheses and may itself contain them.
+#[cfg(target_os = "linux")]
+fn stat_state(stat: &str) -> Option<char> {
+    stat.rsplit_once(')')?.1.trim_start().chars().next()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn state_is_read_after_the_command_name() {
+        assert_eq!(stat_state("1234 (vrserver) S 1 1234 1234 0 -1"), Some('S'));
+        assert_eq!(stat_state("1234 (odd) T name) T 1 1234"), Some('T'));
+        assert_eq!(stat_state("garbage"), None);
+    }
+    
+    #[test]
+    fn this_process_is_not_frozen() {
+        assert!(!is_frozen(std::process::id()));
+    }
+}
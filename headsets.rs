@@ -0,0 +1,271 @@
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+pub struct EncodePreset {
+    pub label: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub bitrate_mbps: u32,
+}
+
+// Encode limits for headsets that stream over Oculus Link / Air Link.
+pub struct LinkEncoding {
+    pub width: RangeInclusive<u32>,
+    pub height: RangeInclusive<u32>,
+    pub presets: &'static [EncodePreset],
+}
+
+pub struct HeadsetModel {
+    pub id: &'static str,
+    pub name: &'static str,
+    // Native panel resolution per eye.
+    pub panel_width: u32,
+    pub panel_height: u32,
+    pub refresh_rates: &'static [u32],
+    pub link: Option<LinkEncoding>,
+}
+
+pub const DEFAULT_HEADSET: &str = "quest3";
+
+// Used when no headset model matches, mirroring the original fixed slider ranges.
+const FALLBACK_WIDTH: RangeInclusive<u32> = 1440..=3664;
+const FALLBACK_HEIGHT: RangeInclusive<u32> = 1584..=1920;
+
+pub const HEADSETS: &[HeadsetModel] = &[
+    HeadsetModel {
+        id: "quest2",
+        name: "Meta Quest 2",
+        panel_width: 1832,
+        panel_height: 1920,
+        refresh_rates: &[72, 80, 90, 120],
+        link: Some(LinkEncoding {
+            width: 1440..=3664,
+            height: 1584..=1920,
+            presets: &[
+                EncodePreset { label: "Performance", width: 2784, height: 1584, bitrate_mbps: 150 },
+                EncodePreset { label: "Balanced", width: 3216, height: 1728, bitrate_mbps: 200 },
+                EncodePreset { label: "Quality", width: 3664, height: 1920, bitrate_mbps: 350 },
+            ],
+        }),
+    },
+    HeadsetModel {
+        id: "quest3",
+        name: "Meta Quest 3",
+        panel_width: 2064,
+        panel_height: 2208,
+        refresh_rates: &[72, 80, 90, 120],
+        link: Some(LinkEncoding {
+            width: 1440..=4128,
+            height: 1584..=2208,
+            presets: &[
+                EncodePreset { label: "Performance", width: 3216, height: 1728, bitrate_mbps: 200 },
+                EncodePreset { label: "Balanced", width: 3664, height: 1920, bitrate_mbps: 300 },
+                EncodePreset { label: "Quality", width: 4128, height: 2208, bitrate_mbps: 400 },
+            ],
+        }),
+    },
+    HeadsetModel {
+        id: "quest3s",
+        name: "Meta Quest 3S",
+        panel_width: 1832,
+        panel_height: 1920,
+        refresh_rates: &[72, 80, 90, 120],
+        link: Some(LinkEncoding {
+            width: 1440..=3664,
+            height: 1584..=1920,
+            presets: &[
+                EncodePreset { label: "Performance", width: 2784, height: 1584, bitrate_mbps: 150 },
+                EncodePreset { label: "Balanced", width: 3216, height: 1728, bitrate_mbps: 200 },
+                EncodePreset { label: "Quality", width: 3664, height: 1920, bitrate_mbps: 300 },
+            ],
+        }),
+    },
+    HeadsetModel {
+        id: "quest_pro",
+        name: "Meta Quest Pro",
+        panel_width: 1800,
+        panel_height: 1920,
+        refresh_rates: &[72, 90],
+        link: Some(LinkEncoding {
+            width: 1440..=3600,
+            height: 1584..=1920,
+            presets: &[
+                EncodePreset { label: "Performance", width: 2784, height: 1584, bitrate_mbps: 150 },
+                EncodePreset { label: "Balanced", width: 3216, height: 1728, bitrate_mbps: 250 },
+                EncodePreset { label: "Quality", width: 3600, height: 1920, bitrate_mbps: 350 },
+            ],
+        }),
+    },
+    HeadsetModel {
+        id: "rift_s",
+        name: "Oculus Rift S",
+        panel_width: 1280,
+        panel_height: 1440,
+        refresh_rates: &[80],
+        link: None,
+    },
+    HeadsetModel {
+        id: "index",
+        name: "Valve Index",
+        panel_width: 1440,
+        panel_height: 1600,
+        refresh_rates: &[80, 90, 120, 144],
+        link: None,
+    },
+    HeadsetModel {
+        id: "vive_pro2",
+        name: "HTC Vive Pro 2",
+        panel_width: 2448,
+        panel_height: 2448,
+        refresh_rates: &[90, 120],
+        link: None,
+    },
+    HeadsetModel {
+        id: "pico4",
+        name: "Pico 4",
+        panel_width: 2160,
+        panel_height: 2160,
+        refresh_rates: &[72, 90],
+        link: None,
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static HeadsetModel> {
+    HEADSETS.iter().find(|model| model.id == id)
+}
+
+pub fn find_or_default(id: &str) -> &'static HeadsetModel {
+    find(id).or_else(|| find(DEFAULT_HEADSET)).unwrap_or(&HEADSETS[0])
+}
+
+impl HeadsetModel {
+    pub fn encode_width_range(&self) -> RangeInclusive<u32> {
+        self.link.as_ref().map_or(FALLBACK_WIDTH, |link| link.width.clone())
+    }
+    
+    pub fn encode_height_range(&self) -> RangeInclusive<u32> {
+        self.link.as_ref().map_or(FALLBACK_HEIGHT, |link| link.height.clone())
+    }
+    
+    pub fn presets(&self) -> &'static [EncodePreset] {
+        match &self.link {
+            Some(link) => link.presets,
+            None => &[],
+        }
+    }
+    
+    pub fn max_refresh_rate(&self) -> u32 {
+        self.refresh_rates.iter().copied().max().unwrap_or(90)
+    }
+}
+
+// USB vendors that ship headsets. Valve and HTC also make controllers and phones, so their product name must match too.
+const HEADSET_VENDORS: &[(&str, &str, Option<&str>)] = &[
+    ("2833", "Meta", None),
+    ("2d40", "Pico", None),
+    ("28de", "Valve", Some("hmd")),
+    ("0bb4", "HTC", Some("vive")),
+];
+
+fn headset_vendor(vendor_id: &str, product: &str) -> Option<&'static str> {
+    let vendor_id = vendor_id.trim().to_lowercase();
+    HEADSET_VENDORS
+        .iter()
+        .find(|(id, _, keyword)| *id == vendor_id && keyword.is_none_or(|k| product.to_lowercase().contains(k)))
+        .map(|(_, vendor, _)| *vendor)
+}
+
+// Names of the headsets currently plugged in over USB.
+#[cfg(target_os = "windows")]
+pub fn connected() -> Vec<String> {
+    let output = match std::process::Command::new("pnputil").args(["/enum-devices", "/connected"]).output() {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut headsets = Vec::new();
+    let mut vendor_id = None;
+    for line in text.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        if key == "Instance ID" {
+            vendor_id = value.to_uppercase().find("VID_").map(|i| value[i + 4..].chars().take(4).collect::<String>());
+        } else if key == "Device Description" {
+            if let Some(vendor) = vendor_id.take().and_then(|id| headset_vendor(&id, value)) {
+                let name = format!("{} {}", vendor, value);
+                if !headsets.contains(&name) {
+                    headsets.push(name);
+                }
+            }
+        }
+    }
+    headsets
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn connected() -> Vec<String> {
+    let mut headsets = Vec::new();
+    for entry in std::fs::read_dir("/sys/bus/usb/devices").into_iter().flatten().flatten() {
+        let vendor_id = match std::fs::read_to_string(entry.path().join("idVendor")) {
+            Ok(id) => id,
+            Err(_) => continue,
+        };
+        let product = std::fs::read_to_string(entry.path().join("product")).unwrap_or_default();
+        if let Some(vendor) = headset_vendor(&vendor_id, &product) {
+            let product = product.trim();
+            let name = if product.is_empty() { format!("{} headset", vendor) } else { format!("{} {}", vendor, product) };
+            if !headsets.contains(&name) {
+                headsets.push(name);
+            }
+        }
+    }
+    headsets
+}
+
+// Polls `connected` off the UI thread and reports the list whenever it changes.
+pub struct ConnectionMonitor {
+    receiver: mpsc::Receiver<Vec<String>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ConnectionMonitor {
+    pub fn start(interval: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        std::thread::spawn(move || {
+            let mut last = None;
+            loop {
+                let headsets = connected();
+                if last.as_ref() != Some(&headsets) {
+                    if sender.send(headsets.clone()).is_err() {
+                        return;
+                    }
+                    last = Some(headsets);
+                }
+                let started = Instant::now();
+                while started.elapsed() < interval {
+                    if stopped.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    std::thread::sleep(Duration::from_millis(250));
+                }
+            }
+        });
+        Self { receiver, stop }
+    }
+    
+    pub fn poll(&self) -> Option<Vec<String>> {
+        self.receiver.try_iter().last()
+    }
+}
+
+impl Drop for ConnectionMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
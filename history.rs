@@ -0,0 +1,209 @@
+use std::time::{Duration, Instant};
+
+use crate::settings_diff;
+use crate::VRSettings;
+
+const MAX_UNDO: usize = 100;
+// Edits to the same fields within this window, such as a slider drag, become one undo step.
+const COALESCE_WINDOW: Duration = Duration::from_millis(750);
+
+pub struct SettingsHistory {
+    undo: Vec<VRSettings>,
+    redo: Vec<VRSettings>,
+    current: VRSettings,
+    last_edit: Option<(Instant, Vec<String>)>,
+    applied: VRSettings,
+    unapplied: Vec<String>,
+}
+
+impl SettingsHistory {
+    pub fn new(settings: &VRSettings) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: settings.clone(),
+            last_edit: None,
+            applied: settings.clone(),
+            unapplied: Vec::new(),
+        }
+    }
+    
+    // Called once per frame with whatever the UI left in the settings.
+    pub fn observe(&mut self, settings: &VRSettings) {
+        if *settings == self.current {
+            return;
+        }
+        let fields = settings_diff::changed_fields(
+            &settings_diff::to_fields(&self.current),
+            &settings_diff::to_fields(settings),
+        );
+        let now = Instant::now();
+        let coalesce = match &self.last_edit {
+            Some((at, last_fields)) => now.duration_since(*at) < COALESCE_WINDOW && *last_fields == fields,
+            None => false,
+        };
+        if !coalesce {
+            self.undo.push(self.current.clone());
+            if self.undo.len() > MAX_UNDO {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.current = settings.clone();
+        self.last_edit = Some((now, fields));
+        self.update_unapplied();
+    }
+    
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    
+    pub fn undo(&mut self, settings: &mut VRSettings) -> bool {
+        match self.undo.pop() {
+            Some(previous) => {
+                self.redo.push(std::mem::replace(&mut self.current, previous));
+                self.restore(settings);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn redo(&mut self, settings: &mut VRSettings) -> bool {
+        match self.redo.pop() {
+            Some(next) => {
+                self.undo.push(std::mem::replace(&mut self.current, next));
+                self.restore(settings);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    fn restore(&mut self, settings: &mut VRSettings) {
+        *settings = self.current.clone();
+        self.last_edit = None;
+        self.update_unapplied();
+    }
+    
+    pub fn mark_applied(&mut self, settings: &VRSettings) {
+        self.applied = settings.clone();
+        self.update_unapplied();
+    }
+    
+    pub fn applied(&self) -> &VRSettings {
+        &self.applied
+    }
+    
+    // Settings fields that differ from what was last applied.
+    pub fn unapplied(&self) -> &[String] {
+        &self.unapplied
+    }
+    
+    fn update_unapplied(&mut self) {
+        self.unapplied = settings_diff::changed_fields(
+            &settings_diff::to_fields(&self.applied),
+            &settings_diff::to_fields(&self.current),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn with_scale(scale: f32) -> VRSettings {
+        VRSettings { render_scale: scale, ..VRSettings::default() }
+    }
+    
+    #[test]
+    fn slider_drag_is_one_undo_step() {
+        let mut settings = with_scale(1.0);
+        let mut history = SettingsHistory::new(&settings);
+        for scale in [1.1, 1.2, 1.3] {
+            settings.render_scale = scale;
+            history.observe(&settings);
+        }
+        
+        assert!(history.undo(&mut settings));
+        assert_eq!(settings.render_scale, 1.0);
+        assert!(!history.can_undo());
+        
+        assert!(history.redo(&mut settings));
+        assert_eq!(settings.render_scale, 1.3);
+        assert!(!history.can_redo());
+    }
+    
+    #[test]
+    fn separate_edits_are_separate_steps() {
+        let mut settings = with_scale(1.0);
+        let mut history = SettingsHistory::new(&settings);
+        settings.render_scale = 1.2;
+        history.observe(&settings);
+        settings.asw_enabled = !settings.asw_enabled;
+        history.observe(&settings);
+        // The same field again, but after the coalescing window.
+        history.last_edit = history.last_edit.take().map(|(at, fields)| (at - COALESCE_WINDOW, fields));
+        settings.asw_enabled = !settings.asw_enabled;
+        history.observe(&settings);
+        
+        let mut steps = 0;
+        while history.undo(&mut settings) {
+            steps += 1;
+        }
+        assert_eq!(steps, 3);
+        assert!(settings == with_scale(1.0));
+    }
+    
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut settings = with_scale(1.0);
+        let mut history = SettingsHistory::new(&settings);
+        settings.render_scale = 1.5;
+        history.observe(&settings);
+        history.undo(&mut settings);
+        assert!(history.can_redo());
+        
+        settings.render_scale = 0.8;
+        history.observe(&settings);
+        assert!(!history.can_redo());
+    }
+    
+    #[test]
+    fn undo_depth_is_capped() {
+        let mut settings = with_scale(1.0);
+        let mut history = SettingsHistory::new(&settings);
+        for i in 0..MAX_UNDO + 10 {
+            // Alternating fields keeps every edit its own step.
+            if i % 2 == 0 {
+                settings.render_scale += 0.01;
+            } else {
+                settings.encode_bitrate_mbps += 1;
+            }
+            history.observe(&settings);
+        }
+        assert_eq!(history.undo.len(), MAX_UNDO);
+    }
+    
+    #[test]
+    fn unapplied_fields_follow_edits_and_undo() {
+        let mut settings = with_scale(1.0);
+        let mut history = SettingsHistory::new(&settings);
+        assert!(history.unapplied().is_empty());
+        
+        settings.render_scale = 1.4;
+        history.observe(&settings);
+        assert_eq!(history.unapplied(), ["render_scale"]);
+        
+        history.mark_applied(&settings);
+        assert!(history.unapplied().is_empty());
+        assert_eq!(history.applied().render_scale, 1.4);
+        
+        history.undo(&mut settings);
+        assert_eq!(history.unapplied(), ["render_scale"]);
+    }
+}
@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    RuntimeStarted,
+    RuntimeStopped,
+    ProcessFrozen,
+    ProcessRestarted,
+    ProfileApplied,
+    GameLaunched,
+    GameExited,
+    HeadsetConnected,
+    HeadsetDisconnected,
+}
+
+impl HookEvent {
+    pub const ALL: &'static [HookEvent] = &[
+        HookEvent::RuntimeStarted,
+        HookEvent::RuntimeStopped,
+        HookEvent::ProcessFrozen,
+        HookEvent::ProcessRestarted,
+        HookEvent::ProfileApplied,
+        HookEvent::GameLaunched,
+        HookEvent::GameExited,
+        HookEvent::HeadsetConnected,
+        HookEvent::HeadsetDisconnected,
+    ];
+    
+    // Passed to the command as VR_SUITE_EVENT and in the JSON on stdin.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::RuntimeStarted => "runtime_started",
+            HookEvent::RuntimeStopped => "runtime_stopped",
+            HookEvent::ProcessFrozen => "process_frozen",
+            HookEvent::ProcessRestarted => "process_restarted",
+            HookEvent::ProfileApplied => "profile_applied",
+            HookEvent::GameLaunched => "game_launched",
+            HookEvent::GameExited => "game_exited",
+            HookEvent::HeadsetConnected => "headset_connected",
+            HookEvent::HeadsetDisconnected => "headset_disconnected",
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            HookEvent::RuntimeStarted => "Runtime started",
+            HookEvent::RuntimeStopped => "Runtime stopped",
+            HookEvent::ProcessFrozen => "Process frozen",
+            HookEvent::ProcessRestarted => "Process restarted",
+            HookEvent::ProfileApplied => "Profile applied",
+            HookEvent::GameLaunched => "Game launched",
+            HookEvent::GameExited => "Game exited",
+            HookEvent::HeadsetConnected => "Headset connected",
+            HookEvent::HeadsetDisconnected => "Headset disconnected",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Hook {
+    pub event: HookEvent,
+    pub command: String,
+    pub timeout_seconds: u32,
+    pub enabled: bool,
+}
+
+impl Default for Hook {
+    fn default() -> Self {
+        Self {
+            event: HookEvent::RuntimeStarted,
+            command: String::from(""),
+            timeout_seconds: 30,
+            enabled: true,
+        }
+    }
+}
+
+// Runs every enabled hook for the event on its own thread; details become VR_SUITE_<KEY> variables.
+pub fn fire(hooks: &[Hook], event: HookEvent, details: &[(&str, String)]) {
+    let matching: Vec<&Hook> = hooks
+        .iter()
+        .filter(|hook| hook.enabled && hook.event == event && !hook.command.trim().is_empty())
+        .collect();
+    if matching.is_empty() {
+        return;
+    }
+    
+    let mut payload = Map::new();
+    payload.insert(String::from("event"), Value::from(event.name()));
+    payload.insert(
+        String::from("timestamp"),
+        Value::from(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)),
+    );
+    for (key, value) in details {
+        payload.insert(key.to_string(), Value::from(value.clone()));
+    }
+    let payload = Value::Object(payload).to_string();
+    let env: Vec<(String, String)> = details
+        .iter()
+        .map(|(key, value)| (format!("VR_SUITE_{}", key.to_uppercase()), value.clone()))
+        .collect();
+    
+    for hook in matching {
+        let hook = hook.clone();
+        let (payload, env) = (payload.clone(), env.clone());
+        thread::spawn(move || run(&hook, event, &payload, &env));
+    }
+}
+
+fn shell(command: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        // Its own process group, so a timeout also reaches whatever the hook started.
+        #[cfg(target_os = "linux")]
+        std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+        shell
+    }
+}
+
+// Everything a hook started, so a timed-out hook does not leave its children running.
+#[cfg(target_os = "windows")]
+struct ProcessTree(Option<windows::Win32::Foundation::HANDLE>);
+
+#[cfg(target_os = "windows")]
+impl ProcessTree {
+    // Anything the shell starts before it joins the job escapes it; cmd takes far longer than that to run a command.
+    fn new(child: &Child) -> Self {
+        use std::os::windows::io::AsRawHandle;
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::{CloseHandle, HANDLE};
+        use windows::Win32::System::JobObjects::{AssignProcessToJobObject, CreateJobObjectW};
+        
+        let job = unsafe {
+            let job = match CreateJobObjectW(None, PCWSTR::null()) {
+                Ok(job) => job,
+                Err(e) => {
+                    warn!("Failed to create a job object for a hook: {}", e);
+                    return Self(None);
+                }
+            };
+            if let Err(e) = AssignProcessToJobObject(job, HANDLE(child.as_raw_handle() as isize)) {
+                warn!("Failed to add a hook to its job object: {}", e);
+                let _ = CloseHandle(job);
+                return Self(None);
+            }
+            job
+        };
+        Self(Some(job))
+    }
+    
+    fn kill(&self, child: &mut Child) {
+        if let Some(job) = self.0 {
+            let _ = unsafe { windows::Win32::System::JobObjects::TerminateJobObject(job, 1) };
+        }
+        let _ = child.kill();
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        if let Some(job) = self.0 {
+            let _ = unsafe { windows::Win32::Foundation::CloseHandle(job) };
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+struct ProcessTree;
+
+#[cfg(not(target_os = "windows"))]
+impl ProcessTree {
+    fn new(_child: &Child) -> Self {
+        Self
+    }
+    
+    fn kill(&self, child: &mut Child) {
+        // The group id is the shell's pid, see shell().
+        #[cfg(target_os = "linux")]
+        unsafe {
+            libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+        }
+        let _ = child.kill();
+    }
+}
+
+fn run(hook: &Hook, event: HookEvent, payload: &str, env: &[(String, String)]) {
+    info!("Running {} hook: {}", event.name(), hook.command);
+    let child = shell(&hook.command)
+        .env("VR_SUITE_EVENT", event.name())
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("Failed to start {} hook '{}': {}", event.name(), hook.command, e);
+            return;
+        }
+    };
+    
+    let tree = ProcessTree::new(&child);
+    
+    // A hook that ignores stdin must not fail the event.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(payload.as_bytes());
+    }
+    let stdout = child.stdout.take().map(|out| log_lines(out, event, false));
+    let stderr = child.stderr.take().map(|err| log_lines(err, event, true));
+    
+    let timeout = Duration::from_secs(hook.timeout_seconds.max(1) as u64);
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if started.elapsed() >= timeout => {
+                warn!("{} hook '{}' timed out after {} s, killing it", event.name(), hook.command, timeout.as_secs());
+                tree.kill(&mut child);
+                let _ = child.wait();
+                break None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                warn!("Failed to wait for {} hook '{}': {}", event.name(), hook.command, e);
+                break None;
+            }
+        }
+    };
+    // After a timeout something that escaped the kill may still hold the pipes open, so the readers are left to finish alone.
+    if status.is_some() {
+        for reader in [stdout, stderr].into_iter().flatten() {
+            let _ = reader.join();
+        }
+    }
+    match status {
+        Some(status) if status.success() => info!("{} hook '{}' finished", event.name(), hook.command),
+        Some(status) => warn!("{} hook '{}' exited with {}", event.name(), hook.command, status),
+        None => {}
+    }
+}
+
+fn log_lines(output: impl Read + Send + 'static, event: HookEvent, is_stderr: bool) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            if is_stderr {
+                warn!("[{} hook] {}", event.name(), line);
+            } else {
+                info!("[{} hook] {}", event.name(), line);
+            }
+        }
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::fs;
+    
+    #[test]
+    fn timeout_kills_what_the_hook_started() {
+        let marker = std::env::temp_dir().join(format!("vr_suite_hook_{}", std::process::id()));
+        let _ = fs::remove_file(&marker);
+        let hook = Hook {
+            command: format!("(sleep 3; touch {}) & sleep 30", marker.display()),
+            timeout_seconds: 1,
+            ..Default::default()
+        };
+        
+        let started = Instant::now();
+        run(&hook, HookEvent::RuntimeStarted, "{}", &[]);
+        assert!(started.elapsed() < Duration::from_secs(3));
+        
+        thread::sleep(Duration::from_secs(3));
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,549 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use log::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum HotkeyAction {
+    RestartOvrServer,
+    CycleAswMode,
+    SwitchProfile,
+    ToggleRecording,
+    KillOculusClient,
+}
+
+impl HotkeyAction {
+    pub const ALL: &'static [HotkeyAction] = &[
+        HotkeyAction::RestartOvrServer,
+        HotkeyAction::CycleAswMode,
+        HotkeyAction::SwitchProfile,
+        HotkeyAction::ToggleRecording,
+        HotkeyAction::KillOculusClient,
+    ];
+    
+    // Also the shortcut id handed to the desktop portal.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HotkeyAction::RestartOvrServer => "restart_ovr_server",
+            HotkeyAction::CycleAswMode => "cycle_asw_mode",
+            HotkeyAction::SwitchProfile => "switch_profile",
+            HotkeyAction::ToggleRecording => "toggle_recording",
+            HotkeyAction::KillOculusClient => "kill_oculus_client",
+        }
+    }
+    
+    pub fn label(&self) -> &'static str {
+        match self {
+            HotkeyAction::RestartOvrServer => "Restart OVRServer_x64.exe",
+            HotkeyAction::CycleAswMode => "Cycle ASW mode",
+            HotkeyAction::SwitchProfile => "Switch game profile",
+            HotkeyAction::ToggleRecording => "Toggle frame recording",
+            HotkeyAction::KillOculusClient => "Kill OculusClient.exe",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Hotkey {
+    pub action: HotkeyAction,
+    // Written like "Ctrl+Alt+F9"; see Binding::parse.
+    pub binding: String,
+    pub enabled: bool,
+}
+
+impl Default for Hotkey {
+    fn default() -> Self {
+        Self {
+            action: HotkeyAction::RestartOvrServer,
+            binding: String::from(""),
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Binding {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub super_key: bool,
+    // "A"-"Z", "0"-"9" or "F1"-"F24".
+    pub key: String,
+}
+
+impl Binding {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut binding = Binding { ctrl: false, alt: false, shift: false, super_key: false, key: String::from("") };
+        for part in text.split('+').map(str::trim) {
+            if !binding.key.is_empty() {
+                return Err(format!("'{}' comes after the key; modifiers go first", part));
+            }
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => binding.ctrl = true,
+                "alt" => binding.alt = true,
+                "shift" => binding.shift = true,
+                "super" | "win" | "meta" | "logo" => binding.super_key = true,
+                _ => binding.key = parse_key(part)?,
+            }
+        }
+        if binding.key.is_empty() {
+            return Err(String::from("no key given"));
+        }
+        // A plain letter or digit would swallow normal typing everywhere.
+        if binding.function_key().is_none() && !(binding.ctrl || binding.alt || binding.super_key) {
+            return Err(String::from("letters and digits need Ctrl, Alt or Super"));
+        }
+        Ok(binding)
+    }
+    
+    // The function key number, for F1-F24.
+    fn function_key(&self) -> Option<u32> {
+        self.key.strip_prefix('F').and_then(|n| n.parse().ok())
+    }
+}
+
+fn parse_key(part: &str) -> Result<String, String> {
+    let key = part.to_uppercase();
+    // "Num5" is a number pad digit; there are no number pad letters.
+    let key = key.strip_prefix("NUM").filter(|k| k.len() == 1 && k.bytes().all(|b| b.is_ascii_digit())).unwrap_or(&key).to_string();
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Ok(key),
+        (Some('F'), Some(_)) => match key[1..].parse::<u32>() {
+            Ok(n) if (1..=24).contains(&n) => Ok(key),
+            _ => Err(format!("unknown key '{}'", part)),
+        },
+        _ => Err(format!("unknown key '{}'", part)),
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = [(self.ctrl, "Ctrl"), (self.alt, "Alt"), (self.shift, "Shift"), (self.super_key, "Super")];
+        for (held, name) in modifiers {
+            if held {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+pub enum HotkeyEvent {
+    Pressed(HotkeyAction),
+    // A binding could not be registered, usually because another program owns it.
+    Failed(String),
+}
+
+pub struct HotkeyListener {
+    pub bindings: Vec<(HotkeyAction, Binding)>,
+    pub backend: &'static str,
+    receiver: mpsc::Receiver<HotkeyEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl HotkeyListener {
+    // `wake` runs on the listener thread after each event so the UI can repaint and handle it.
+    pub fn start(bindings: Vec<(HotkeyAction, Binding)>, wake: impl Fn() + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let backend = backend::name();
+        let registered = bindings.clone();
+        let thread = thread::spawn(move || {
+            let send = move |event: HotkeyEvent| {
+                let _ = sender.send(event);
+                wake();
+            };
+            if let Err(e) = backend::listen(&registered, &stopped, &send) {
+                warn!("Global hotkeys unavailable: {}", e);
+                send(HotkeyEvent::Failed(e));
+            }
+        });
+        info!("Listening for {} global hotkey(s) via {}", bindings.len(), backend);
+        Self { bindings, backend, receiver, stop, thread: Some(thread) }
+    }
+    
+    pub fn poll(&self) -> Vec<HotkeyEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+impl Drop for HotkeyListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Every backend checks `stop` at least once per POLL_INTERVAL, and the bindings are only free again once it has seen it.
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod backend {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+    
+    use super::{Binding, HotkeyAction, HotkeyEvent, POLL_INTERVAL};
+    
+    pub fn name() -> &'static str {
+        "RegisterHotKey"
+    }
+    
+    fn virtual_key(binding: &Binding) -> u32 {
+        match binding.function_key() {
+            Some(n) => VK_F1.0 as u32 + n - 1,
+            // Letters and digits share their ASCII code.
+            None => binding.key.as_bytes()[0] as u32,
+        }
+    }
+    
+    // Hotkeys registered without a window are posted to this thread's message queue.
+    pub fn listen(bindings: &[(HotkeyAction, Binding)], stop: &AtomicBool, send: &dyn Fn(HotkeyEvent)) -> Result<(), String> {
+        let mut registered = Vec::new();
+        for (id, (action, binding)) in bindings.iter().enumerate() {
+            let mut modifiers = MOD_NOREPEAT;
+            for (held, flag) in [(binding.ctrl, MOD_CONTROL), (binding.alt, MOD_ALT), (binding.shift, MOD_SHIFT), (binding.super_key, MOD_WIN)] {
+                if held {
+                    modifiers |= flag;
+                }
+            }
+            match unsafe { RegisterHotKey(HWND::default(), id as i32, modifiers, virtual_key(binding)) } {
+                Ok(()) => registered.push(id as i32),
+                Err(e) => send(HotkeyEvent::Failed(format!("{} ({}): {}", binding, action.label(), e))),
+            }
+        }
+        
+        let mut message = MSG::default();
+        while !stop.load(Ordering::Relaxed) {
+            while unsafe { PeekMessageW(&mut message, HWND::default(), WM_HOTKEY, WM_HOTKEY, PM_REMOVE) }.as_bool() {
+                if let Some((action, _)) = bindings.get(message.wParam.0) {
+                    send(HotkeyEvent::Pressed(*action));
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        for id in registered {
+            let _ = unsafe { UnregisterHotKey(HWND::default(), id) };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod backend {
+    use std::sync::atomic::AtomicBool;
+    
+    use log::warn;
+    
+    use super::{Binding, HotkeyAction, HotkeyEvent};
+    
+    // Wayland compositors only hand out global shortcuts through the portal; X11 lets us grab keys directly.
+    pub fn name() -> &'static str {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            "the GlobalShortcuts portal"
+        } else {
+            "X11"
+        }
+    }
+    
+    pub fn listen(bindings: &[(HotkeyAction, Binding)], stop: &AtomicBool, send: &dyn Fn(HotkeyEvent)) -> Result<(), String> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            match portal::listen(bindings, stop, send) {
+                Ok(()) => return Ok(()),
+                Err(e) if std::env::var_os("DISPLAY").is_some() => {
+                    warn!("GlobalShortcuts portal unavailable ({}), falling back to X11", e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        x11::listen(bindings, stop, send)
+    }
+    
+    mod portal {
+        use futures_lite::future::{block_on, poll_once};
+        use futures_lite::StreamExt;
+        use std::collections::HashMap;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        use zbus::blocking::{Connection, Proxy};
+        use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+        use zbus::{Message, SignalStream};
+        
+        use super::super::{Binding, HotkeyAction, HotkeyEvent, POLL_INTERVAL};
+        
+        const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+        const PATH: &str = "/org/freedesktop/portal/desktop";
+        const INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+        const TOKEN: &str = "vr_suite";
+        
+        // The XDG shortcut format, e.g. "CTRL+ALT+F9" or "CTRL+ALT+a": keysym names, so letters are lowercase.
+        fn trigger(binding: &Binding) -> String {
+            let modifiers = [(binding.ctrl, "CTRL+"), (binding.alt, "ALT+"), (binding.shift, "SHIFT+"), (binding.super_key, "LOGO+")];
+            let mut trigger: String = modifiers.iter().filter(|(held, _)| *held).map(|(_, name)| *name).collect();
+            match binding.function_key() {
+                Some(_) => trigger.push_str(&binding.key),
+                None => trigger.push_str(&binding.key.to_lowercase()),
+            }
+            trigger
+        }
+        
+        // Takes whatever signal is already queued without waiting; zbus reads the socket on its own thread.
+        fn try_next(signals: &mut SignalStream<'_>) -> Option<Option<Arc<Message>>> {
+            block_on(poll_once(signals.next()))
+        }
+        
+        // Portal methods answer through a Response signal on a request object, which must be subscribed before the call.
+        // The answer can wait on the user, so this gives up with None once `stop` is set.
+        fn request<'a>(
+            conn: &Connection,
+            proxy: &Proxy<'a>,
+            token: &str,
+            stop: &AtomicBool,
+            call: impl FnOnce(&Proxy<'a>) -> zbus::Result<OwnedObjectPath>,
+        ) -> Result<Option<HashMap<String, OwnedValue>>, String> {
+            let sender = conn.unique_name().map(|name| name.as_str().trim_start_matches(':').replace('.', "_")).unwrap_or_default();
+            let path = format!("{}/request/{}/{}", PATH, sender, token);
+            let request = Proxy::new(conn, DESTINATION, path.as_str(), "org.freedesktop.portal.Request").map_err(|e| e.to_string())?;
+            let mut responses = block_on(request.inner().receive_signal("Response")).map_err(|e| e.to_string())?;
+            call(proxy).map_err(|e| e.to_string())?;
+            let message = loop {
+                if stop.load(Ordering::Relaxed) {
+                    return Ok(None);
+                }
+                match try_next(&mut responses) {
+                    Some(Some(message)) => break message,
+                    Some(None) => return Err(String::from("the portal closed the request")),
+                    None => std::thread::sleep(POLL_INTERVAL),
+                }
+            };
+            let (code, results): (u32, HashMap<String, OwnedValue>) = message.body().map_err(|e| e.to_string())?;
+            match code {
+                0 => Ok(Some(results)),
+                1 => Err(String::from("the shortcuts were declined")),
+                _ => Err(String::from("the portal request failed")),
+            }
+        }
+        
+        pub fn listen(bindings: &[(HotkeyAction, Binding)], stop: &AtomicBool, send: &dyn Fn(HotkeyEvent)) -> Result<(), String> {
+            let conn = Connection::session().map_err(|e| e.to_string())?;
+            let proxy = Proxy::new(&conn, DESTINATION, PATH, INTERFACE).map_err(|e| e.to_string())?;
+            
+            let session_token = format!("{}_session_{}", TOKEN, std::process::id());
+            let create_token = format!("{}_create_{}", TOKEN, std::process::id());
+            let results = request(&conn, &proxy, &create_token, stop, |proxy| {
+                let mut options: HashMap<&str, Value> = HashMap::new();
+                options.insert("handle_token", Value::from(create_token.as_str()));
+                options.insert("session_handle_token", Value::from(session_token.as_str()));
+                proxy.call("CreateSession", &(options,))
+            })?;
+            let results = match results {
+                Some(results) => results,
+                None => return Ok(()),
+            };
+            let session = results
+                .get("session_handle")
+                .and_then(|handle| String::try_from(handle.clone()).ok())
+                .ok_or("the portal returned no session")?;
+            let session = OwnedObjectPath::try_from(session).map_err(|e| e.to_string())?;
+            
+            let shortcuts: Vec<(&str, HashMap<&str, Value>)> = bindings
+                .iter()
+                .map(|(action, binding)| {
+                    let mut options: HashMap<&str, Value> = HashMap::new();
+                    options.insert("description", Value::from(action.label()));
+                    options.insert("preferred_trigger", Value::from(trigger(binding)));
+                    (action.name(), options)
+                })
+                .collect();
+            let bind_token = format!("{}_bind_{}", TOKEN, std::process::id());
+            let mut activated = block_on(proxy.inner().receive_signal("Activated")).map_err(|e| e.to_string())?;
+            let bound = request(&conn, &proxy, &bind_token, stop, |proxy| {
+                let mut options: HashMap<&str, Value> = HashMap::new();
+                options.insert("handle_token", Value::from(bind_token.as_str()));
+                proxy.call("BindShortcuts", &(&session, shortcuts, "", options))
+            })?;
+            
+            while bound.is_some() && !stop.load(Ordering::Relaxed) {
+                while let Some(Some(message)) = try_next(&mut activated) {
+                    if let Ok((_, id, _, _)) = message.body::<(OwnedObjectPath, String, u64, HashMap<String, OwnedValue>)>() {
+                        if let Some((action, _)) = bindings.iter().find(|(action, _)| action.name() == id) {
+                            send(HotkeyEvent::Pressed(*action));
+                        }
+                    }
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            let session = Proxy::new(&conn, DESTINATION, session.as_str(), "org.freedesktop.portal.Session").map_err(|e| e.to_string())?;
+            let _: zbus::Result<()> = session.call("Close", &());
+            Ok(())
+        }
+    }
+    
+    mod x11 {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::{ConnectionExt, GrabMode, ModMask};
+        use x11rb::protocol::Event;
+        
+        use super::super::{Binding, HotkeyAction, HotkeyEvent, POLL_INTERVAL};
+        
+        const XK_F1: u32 = 0xffbe;
+        
+        // Letters use their lowercase keysym, digits and function keys their own.
+        fn keysym(binding: &Binding) -> u32 {
+            match binding.function_key() {
+                Some(n) => XK_F1 + n - 1,
+                None => binding.key.to_ascii_lowercase().as_bytes()[0] as u32,
+            }
+        }
+        
+        fn modifiers(binding: &Binding) -> ModMask {
+            let mut mask = ModMask::from(0u16);
+            for (held, flag) in [(binding.ctrl, ModMask::CONTROL), (binding.alt, ModMask::M1), (binding.shift, ModMask::SHIFT), (binding.super_key, ModMask::M4)] {
+                if held {
+                    mask |= flag;
+                }
+            }
+            mask
+        }
+        
+        pub fn listen(bindings: &[(HotkeyAction, Binding)], stop: &AtomicBool, send: &dyn Fn(HotkeyEvent)) -> Result<(), String> {
+            let (conn, screen) = x11rb::connect(None).map_err(|e| e.to_string())?;
+            let root = conn.setup().roots[screen].root;
+            let (min, max) = (conn.setup().min_keycode, conn.setup().max_keycode);
+            let mapping = conn
+                .get_keyboard_mapping(min, max - min + 1)
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|e| e.to_string())?;
+            let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+            let keycode = |keysym: u32| {
+                mapping.keysyms.chunks(per_keycode).position(|syms| syms.contains(&keysym)).map(|i| min + i as u8)
+            };
+            
+            // Grabs are exact, so repeat each one with Caps Lock and Num Lock held.
+            let locks = [ModMask::from(0u16), ModMask::LOCK, ModMask::M2, ModMask::LOCK | ModMask::M2];
+            let mut grabbed = Vec::new();
+            for (action, binding) in bindings {
+                let code = match keycode(keysym(binding)) {
+                    Some(code) => code,
+                    None => {
+                        send(HotkeyEvent::Failed(format!("{} ({}): key not on this keyboard", binding, action.label())));
+                        continue;
+                    }
+                };
+                let mask = modifiers(binding);
+                let mut held = Vec::new();
+                let mut result = Ok(());
+                for lock in locks {
+                    result = conn.grab_key(false, root, mask | lock, code, GrabMode::ASYNC, GrabMode::ASYNC)
+                        .map_err(|e| e.to_string())
+                        .and_then(|cookie| cookie.check().map_err(|e| e.to_string()));
+                    if result.is_err() {
+                        break;
+                    }
+                    held.push(lock);
+                }
+                match result {
+                    Ok(()) => grabbed.push((*action, code, mask)),
+                    Err(e) => {
+                        // A binding half grabbed would still swallow the key with some lock states.
+                        for lock in held {
+                            let _ = conn.ungrab_key(code, root, mask | lock);
+                        }
+                        send(HotkeyEvent::Failed(format!("{} ({}): {}", binding, action.label(), e)));
+                    }
+                }
+            }
+            
+            let relevant = u16::from(ModMask::CONTROL | ModMask::M1 | ModMask::SHIFT | ModMask::M4);
+            while !stop.load(Ordering::Relaxed) {
+                while let Some(event) = conn.poll_for_event().map_err(|e| e.to_string())? {
+                    if let Event::KeyPress(press) = event {
+                        let state = u16::from(press.state) & relevant;
+                        if let Some((action, _, _)) = grabbed.iter().find(|(_, code, mask)| *code == press.detail && u16::from(*mask) == state) {
+                            send(HotkeyEvent::Pressed(*action));
+                        }
+                    }
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            for (_, code, mask) in grabbed {
+                for lock in locks {
+                    let _ = conn.ungrab_key(code, root, mask | lock);
+                }
+            }
+            let _ = conn.flush();
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod backend {
+    use std::sync::atomic::AtomicBool;
+    
+    use super::{Binding, HotkeyAction, HotkeyEvent};
+    
+    pub fn name() -> &'static str {
+        "no backend"
+    }
+    
+    pub fn listen(_bindings: &[(HotkeyAction, Binding)], _stop: &AtomicBool, _send: &dyn Fn(HotkeyEvent)) -> Result<(), String> {
+        Err(String::from("global hotkeys are not supported on this platform"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn modifiers_and_key_are_parsed() {
+        let binding = Binding::parse("ctrl + Shift+f5").unwrap();
+        assert!(binding.ctrl && binding.shift && !binding.alt && !binding.super_key);
+        assert_eq!(binding.key, "F5");
+        assert_eq!(binding.to_string(), "Ctrl+Shift+F5");
+        assert_eq!(Binding::parse("Win+Alt+q").unwrap().to_string(), "Alt+Super+Q");
+    }
+    
+    #[test]
+    fn modifiers_must_come_before_the_key() {
+        assert!(Binding::parse("F5+Ctrl").is_err());
+        assert!(Binding::parse("Ctrl+A+B").is_err());
+        assert!(Binding::parse("Ctrl+Alt").is_err());
+    }
+    
+    #[test]
+    fn function_keys_stop_at_f24() {
+        assert_eq!(Binding::parse("F24").unwrap().key, "F24");
+        assert!(Binding::parse("F25").is_err());
+        assert!(Binding::parse("F0").is_err());
+    }
+    
+    #[test]
+    fn number_pad_digits_only() {
+        assert_eq!(Binding::parse("Ctrl+Num5").unwrap().key, "5");
+        assert!(Binding::parse("Ctrl+NumA").is_err());
+        assert!(Binding::parse("Ctrl+NumLock").is_err());
+    }
+    
+    #[test]
+    fn plain_letters_need_a_modifier() {
+        assert!(Binding::parse("A").is_err());
+        assert!(Binding::parse("Shift+7").is_err());
+        assert!(Binding::parse("Alt+7").is_ok());
+        assert!(Binding::parse("F1").is_ok());
+    }
+}
@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::info;
+
+#[cfg(target_os = "windows")]
+const STEAMVR_APP_ID: &str = "250820";
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct InstallPaths {
+    pub oculus_runtime: String,
+    pub oculus_dash_dir: String,
+    pub oculus_debug_tool: String,
+    pub steamvr_server: String,
+    pub monado_service: String,
+    pub wivrn_server: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum InstallPath {
+    OculusRuntime,
+    OculusDashDir,
+    OculusDebugTool,
+    SteamVrServer,
+    MonadoService,
+    WivrnServer,
+}
+
+pub const ALL_PATHS: &[InstallPath] = &[
+    InstallPath::OculusRuntime,
+    InstallPath::OculusDashDir,
+    InstallPath::OculusDebugTool,
+    InstallPath::SteamVrServer,
+    InstallPath::MonadoService,
+    InstallPath::WivrnServer,
+];
+
+impl InstallPath {
+    pub fn label(&self) -> &'static str {
+        match self {
+            InstallPath::OculusRuntime => "Oculus runtime (OVRServer)",
+            InstallPath::OculusDashDir => "OculusDash directory",
+            InstallPath::OculusDebugTool => "Oculus Debug Tool",
+            InstallPath::SteamVrServer => "SteamVR vrserver",
+            InstallPath::MonadoService => "Monado service",
+            InstallPath::WivrnServer => "WiVRn server",
+        }
+    }
+}
+
+impl InstallPaths {
+    pub fn get(&self, path: InstallPath) -> &str {
+        match path {
+            InstallPath::OculusRuntime => &self.oculus_runtime,
+            InstallPath::OculusDashDir => &self.oculus_dash_dir,
+            InstallPath::OculusDebugTool => &self.oculus_debug_tool,
+            InstallPath::SteamVrServer => &self.steamvr_server,
+            InstallPath::MonadoService => &self.monado_service,
+            InstallPath::WivrnServer => &self.wivrn_server,
+        }
+    }
+    
+    pub fn get_mut(&mut self, path: InstallPath) -> &mut String {
+        match path {
+            InstallPath::OculusRuntime => &mut self.oculus_runtime,
+            InstallPath::OculusDashDir => &mut self.oculus_dash_dir,
+            InstallPath::OculusDebugTool => &mut self.oculus_debug_tool,
+            InstallPath::SteamVrServer => &mut self.steamvr_server,
+            InstallPath::MonadoService => &mut self.monado_service,
+            InstallPath::WivrnServer => &mut self.wivrn_server,
+        }
+    }
+    
+    // A non-empty override always wins over the discovered value.
+    pub fn resolve(&self, overrides: &InstallPaths, path: InstallPath) -> PathBuf {
+        let value = overrides.get(path).trim();
+        if value.is_empty() {
+            PathBuf::from(self.get(path))
+        } else {
+            PathBuf::from(value)
+        }
+    }
+}
+
+pub fn discover() -> InstallPaths {
+    let mut paths = InstallPaths::default();
+    
+    if let Some(oculus) = oculus_root() {
+        let support = oculus.join("Support");
+        paths.oculus_runtime = existing(support.join("oculus-runtime").join("OVRServer_x64.exe"));
+        paths.oculus_dash_dir = existing(support.join("oculus-dash").join("dash").join("bin"));
+        paths.oculus_debug_tool = existing(support.join("oculus-diagnostics").join("OculusDebugTool.exe"));
+    }
+    
+    if let Some(steamvr) = steamvr_root() {
+        let server = if cfg!(target_os = "windows") {
+            steamvr.join("bin").join("win64").join("vrserver.exe")
+        } else {
+            steamvr.join("bin").join("linux64").join("vrserver")
+        };
+        paths.steamvr_server = existing(server);
+    }
+    
+    paths.monado_service = find_program("monado-service", &["org.monado.Monado"]);
+    paths.wivrn_server = find_program("wivrn-server", &["io.github.wivrn.wivrn"]);
+    
+    for path in ALL_PATHS {
+        let value = paths.get(*path);
+        if !value.is_empty() {
+            info!("Discovered {}: {}", path.label(), value);
+        }
+    }
+    paths
+}
+
+fn existing(path: PathBuf) -> String {
+    if path.exists() {
+        path.to_string_lossy().to_string()
+    } else {
+        String::from("")
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn oculus_root() -> Option<PathBuf> {
+    use winreg::enums::*;
+    use winreg::RegKey;
+    
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    if let Ok(key) = hklm.open_subkey("SOFTWARE\\WOW6432Node\\Oculus VR, LLC\\Oculus") {
+        if let Ok(base) = key.get_value::<String, _>("Base") {
+            return Some(PathBuf::from(base));
+        }
+    }
+    if let Ok(base) = std::env::var("OculusBase") {
+        return Some(PathBuf::from(base));
+    }
+    if let Some(location) = uninstall_location(|name| name.starts_with("Oculus") || name.starts_with("Meta Quest Link")) {
+        return Some(location);
+    }
+    
+    let fallback = PathBuf::from(r"C:\Program Files\Oculus");
+    if fallback.exists() { Some(fallback) } else { None }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn oculus_root() -> Option<PathBuf> {
+    None
+}
+
+fn steamvr_root() -> Option<PathBuf> {
+    for steam in steam_roots() {
+        for library in library_folders(&steam) {
+            let candidate = library.join("steamapps").join("common").join("SteamVR");
+            if candidate.exists() {
+                return Some(candidate);
+            }
+        }
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        let uninstall_name = format!("Steam App {}", STEAMVR_APP_ID);
+        if let Some(location) = uninstall_location(|name| name == "SteamVR" || name == uninstall_name) {
+            return Some(location);
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn steam_roots() -> Vec<PathBuf> {
+    use winreg::enums::*;
+    use winreg::RegKey;
+    
+    let mut roots = Vec::new();
+    if let Ok(key) = RegKey::predef(HKEY_CURRENT_USER).open_subkey("Software\\Valve\\Steam") {
+        if let Ok(path) = key.get_value::<String, _>("SteamPath") {
+            roots.push(PathBuf::from(path));
+        }
+    }
+    if let Ok(key) = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey("SOFTWARE\\WOW6432Node\\Valve\\Steam") {
+        if let Ok(path) = key.get_value::<String, _>("InstallPath") {
+            roots.push(PathBuf::from(path));
+        }
+    }
+    roots.push(PathBuf::from(r"C:\Program Files (x86)\Steam"));
+    roots
+}
+
+#[cfg(not(target_os = "windows"))]
+fn steam_roots() -> Vec<PathBuf> {
+    let home = match std::env::var("HOME") {
+        Ok(home) => PathBuf::from(home),
+        Err(_) => return Vec::new(),
+    };
+    vec![
+        home.join(".steam").join("steam"),
+        home.join(".local").join("share").join("Steam"),
+        home.join(".var").join("app").join("com.valvesoftware.Steam").join(".local").join("share").join("Steam"),
+    ]
+}
+
+// Every Steam install is itself a library; libraryfolders.vdf lists the others.
+fn library_folders(steam: &Path) -> Vec<PathBuf> {
+    let mut folders = vec![steam.to_path_buf()];
+    let vdf = match fs::read_to_string(steam.join("steamapps").join("libraryfolders.vdf")) {
+        Ok(vdf) => vdf,
+        Err(_) => return folders,
+    };
+    for line in vdf.lines() {
+        let tokens: Vec<&str> = line.split('"').map(str::trim).filter(|t| !t.is_empty()).collect();
+        if tokens.len() == 2 && tokens[0] == "path" {
+            let folder = PathBuf::from(tokens[1].replace("\\\\", "\\"));
+            if !folders.contains(&folder) {
+                folders.push(folder);
+            }
+        }
+    }
+    folders
+}
+
+#[cfg(target_os = "windows")]
+fn uninstall_location(matches: impl Fn(&str) -> bool) -> Option<PathBuf> {
+    use winreg::enums::*;
+    use winreg::RegKey;
+    
+    let roots = [
+        (HKEY_LOCAL_MACHINE, "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall"),
+        (HKEY_LOCAL_MACHINE, "SOFTWARE\\WOW6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall"),
+        (HKEY_CURRENT_USER, "Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall"),
+    ];
+    for (hive, path) in roots {
+        let uninstall = match RegKey::predef(hive).open_subkey(path) {
+            Ok(key) => key,
+            Err(_) => continue,
+        };
+        for name in uninstall.enum_keys().flatten() {
+            let entry = match uninstall.open_subkey(&name) {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            let display_name: String = entry.get_value("DisplayName").unwrap_or_default();
+            if !matches(&display_name) {
+                continue;
+            }
+            if let Ok(location) = entry.get_value::<String, _>("InstallLocation") {
+                if !location.is_empty() && Path::new(&location).exists() {
+                    return Some(PathBuf::from(location));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn find_program(_name: &str, _flatpak_ids: &[&str]) -> String {
+    String::from("")
+}
+
+#[cfg(not(target_os = "windows"))]
+fn find_program(name: &str, flatpak_ids: &[&str]) -> String {
+    let mut dirs: Vec<PathBuf> = std::env::var("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    for prefix in ["/usr/bin", "/usr/local/bin", "/opt/monado/bin", "/opt/wivrn/bin"] {
+        dirs.push(PathBuf::from(prefix));
+    }
+    if let Ok(home) = std::env::var("HOME") {
+        dirs.push(PathBuf::from(home).join(".local").join("bin"));
+    }
+    
+    for dir in dirs {
+        let candidate = dir.join(name);
+        if candidate.is_file() {
+            return candidate.to_string_lossy().to_string();
+        }
+    }
+    
+    // Flatpak exports a wrapper that runs the app through `flatpak run`.
+    let mut exports = vec![PathBuf::from("/var/lib/flatpak/exports/bin")];
+    if let Ok(home) = std::env::var("HOME") {
+        exports.push(PathBuf::from(home).join(".local").join("share").join("flatpak").join("exports").join("bin"));
+    }
+    for dir in exports {
+        for id in flatpak_ids {
+            let candidate = dir.join(id);
+            if candidate.is_file() {
+                return candidate.to_string_lossy().to_string();
+            }
+        }
+    }
+    String::from("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn library_folders_are_read_once_each() {
+        let steam = std::env::temp_dir().join(format!("vr_suite_steam_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&steam);
+        fs::create_dir_all(steam.join("steamapps")).unwrap();
+        let vdf = format!(
+            "\"libraryfolders\"\n{{\n\t\"0\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t\t\"label\"\t\t\"\"\n\t}}\n\t\"1\"\n\t{{\n\t\t\"path\"\t\t\"D:\\\\SteamLibrary\"\n\t}}\n\t\"2\"\n\t{{\n\t\t\"path\"\t\t\"D:\\\\SteamLibrary\"\n\t}}\n}}\n",
+            steam.display()
+        );
+        fs::write(steam.join("steamapps").join("libraryfolders.vdf"), vdf).unwrap();
+        
+        assert_eq!(library_folders(&steam), vec![steam.clone(), PathBuf::from("D:\\SteamLibrary")]);
+        let _ = fs::remove_dir_all(&steam);
+    }
+    
+    #[test]
+    fn missing_vdf_leaves_only_the_steam_folder() {
+        let steam = std::env::temp_dir().join(format!("vr_suite_no_vdf_{}", std::process::id()));
+        assert_eq!(library_folders(&steam), vec![steam.clone()]);
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::System;
+
+use log::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const STEAMVR_APP_URL: &str = "steam://rungameid/250820";
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct StartupProgram {
+    pub path: String,
+    pub args: String,
+    pub working_dir: String,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum StepState {
+    Pending,
+    Running,
+    Succeeded(String),
+    Failed(String),
+    Skipped(String),
+}
+
+#[derive(Clone)]
+pub struct LaunchStep {
+    pub name: String,
+    pub state: StepState,
+}
+
+pub enum RuntimeLauncher {
+    Executable(PathBuf),
+    Url(&'static str),
+}
+
+pub struct Runtime {
+    pub name: &'static str,
+    pub process: &'static str,
+    pub launcher: RuntimeLauncher,
+}
+
+pub struct LaunchPlan {
+    pub runtime: Runtime,
+    pub start_steamvr: bool,
+    pub programs: Vec<StartupProgram>,
+    pub timeout: Duration,
+}
+
+pub type LaunchProgress = Arc<Mutex<Vec<LaunchStep>>>;
+
+pub fn steamvr_runtime() -> Runtime {
+    Runtime {
+        name: "SteamVR",
+        process: "vrserver",
+        launcher: RuntimeLauncher::Url(STEAMVR_APP_URL),
+    }
+}
+
+pub fn start(plan: LaunchPlan) -> LaunchProgress {
+    let mut steps = vec![
+        LaunchStep { name: format!("Start {}", plan.runtime.name), state: StepState::Pending },
+        LaunchStep { name: format!("Wait for {}", plan.runtime.name), state: StepState::Pending },
+    ];
+    if plan.start_steamvr {
+        steps.push(LaunchStep { name: String::from("Start SteamVR"), state: StepState::Pending });
+        steps.push(LaunchStep { name: String::from("Wait for SteamVR"), state: StepState::Pending });
+    }
+    for program in &plan.programs {
+        steps.push(LaunchStep { name: format!("Start {}", program.path), state: StepState::Pending });
+    }
+    
+    let progress = Arc::new(Mutex::new(steps));
+    let thread_progress = progress.clone();
+    std::thread::spawn(move || run(plan, thread_progress));
+    progress
+}
+
+fn run(plan: LaunchPlan, progress: LaunchProgress) {
+    let mut sys = System::new();
+    let mut step = 0;
+    
+    if !start_runtime(&plan.runtime, plan.timeout, &mut sys, &progress, &mut step) {
+        skip_remaining(&progress, step, "runtime is not running");
+        return;
+    }
+    
+    if plan.start_steamvr && !start_runtime(&steamvr_runtime(), plan.timeout, &mut sys, &progress, &mut step) {
+        skip_remaining(&progress, step, "SteamVR is not running");
+        return;
+    }
+    
+    for program in &plan.programs {
+        set_state(&progress, step, StepState::Running);
+        let state = match launch_program(program) {
+            Ok(pid) => StepState::Succeeded(format!("started with pid {}", pid)),
+            Err(e) => StepState::Failed(e),
+        };
+        set_state(&progress, step, state);
+        step += 1;
+    }
+}
+
+fn start_runtime(runtime: &Runtime, timeout: Duration, sys: &mut System, progress: &LaunchProgress, step: &mut usize) -> bool {
+    set_state(progress, *step, StepState::Running);
+    if is_running(sys, runtime.process) {
+        set_state(progress, *step, StepState::Skipped(String::from("already running")));
+        set_state(progress, *step + 1, StepState::Succeeded(String::from("running")));
+        *step += 2;
+        return true;
+    }
+    
+    if let Err(e) = spawn_launcher(&runtime.launcher) {
+        warn!("Failed to start {}: {}", runtime.name, e);
+        set_state(progress, *step, StepState::Failed(e.to_string()));
+        *step += 1;
+        return false;
+    }
+    info!("Started {}", runtime.name);
+    set_state(progress, *step, StepState::Succeeded(String::from("launched")));
+    *step += 1;
+    
+    set_state(progress, *step, StepState::Running);
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        if is_running(sys, runtime.process) {
+            set_state(progress, *step, StepState::Succeeded(format!("ready after {:.1} s", started.elapsed().as_secs_f32())));
+            *step += 1;
+            return true;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    
+    warn!("{} did not start within {} s", runtime.name, timeout.as_secs());
+    set_state(progress, *step, StepState::Failed(format!("timed out after {} s", timeout.as_secs())));
+    *step += 1;
+    false
+}
+
+fn launch_program(program: &StartupProgram) -> Result<u32, String> {
+    let mut command = Command::new(&program.path);
+    command.args(split_args(&program.args));
+    if !program.working_dir.trim().is_empty() {
+        command.current_dir(program.working_dir.trim());
+    }
+    match spawn_reaped(&mut command) {
+        Ok(pid) => {
+            info!("Started {} (pid {})", program.path, pid);
+            Ok(pid)
+        }
+        Err(e) => {
+            warn!("Failed to start {}: {}", program.path, e);
+            Err(e.to_string())
+        }
+    }
+}
+
+// Waits for the child in the background so it does not stay behind as a zombie once it exits.
+pub fn spawn_reaped(command: &mut Command) -> std::io::Result<u32> {
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(pid)
+}
+
+pub fn spawn_launcher(launcher: &RuntimeLauncher) -> std::io::Result<()> {
+    match launcher {
+        RuntimeLauncher::Executable(path) => spawn_reaped(&mut Command::new(path)).map(|_| ()),
+        RuntimeLauncher::Url(url) => open_url(url),
+    }
+}
+
+fn is_running(sys: &mut System, process: &str) -> bool {
+    sys.refresh_processes();
+    sys.processes_by_name(process).next().is_some()
+}
+
+#[cfg(target_os = "windows")]
+fn open_url(url: &str) -> std::io::Result<()> {
+    spawn_reaped(Command::new("cmd").args(["/c", "start", "", url])).map(|_| ())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn open_url(url: &str) -> std::io::Result<()> {
+    spawn_reaped(Command::new("xdg-open").arg(url)).map(|_| ())
+}
+
+fn set_state(progress: &LaunchProgress, step: usize, state: StepState) {
+    if let Some(s) = progress.lock().unwrap().get_mut(step) {
+        s.state = state;
+    }
+}
+
+fn skip_remaining(progress: &LaunchProgress, from: usize, reason: &str) {
+    let mut steps = progress.lock().unwrap();
+    for s in steps.iter_mut().skip(from) {
+        s.state = StepState::Skipped(reason.to_string());
+    }
+}
+
+fn split_args(args: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for c in args.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    result.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        result.push(current);
+    }
+    result
+}
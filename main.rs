@@ -1,19 +1,88 @@
 use eframe::egui;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
+use std::collections::HashSet;
+use std::sync::{mpsc, Arc, Mutex};
 use sysinfo::System;
+#[cfg(target_os = "windows")]
 use std::process::Command;
 use std::fs;
-use std::path::Path;
-use std::io::{Read, Write};
-use log::{info, debug, LevelFilter};
+#[cfg(target_os = "windows")]
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use log::{info, debug, warn, LevelFilter};
 use simplelog::{Config, WriteLogger};
 
-#[derive(Serialize, Deserialize, Clone)]
+mod affinity;
+mod audio;
+mod autotune;
+mod cli;
+mod config;
+mod elevation;
+mod file_swap;
+mod hang;
+mod headsets;
+mod history;
+mod hooks;
+mod hotkeys;
+mod install;
+mod launch;
+mod openxr;
+#[cfg(target_os = "linux")]
+mod power;
+mod priority;
+mod profile_io;
+mod profiles;
+mod recommend;
+mod registry;
+mod report;
+mod scripting;
+mod services;
+mod settings_diff;
+mod settings_watch;
+mod support_bundle;
+mod system_state;
+
+#[cfg(target_os = "windows")]
+const VR_PROCESSES: &[&str] = &[
+    "OVRServer_x64.exe",
+    "OculusClient.exe",
+    "vrserver.exe",
+    "vrdashboard.exe",
+    "vrcompositor.exe",
+];
+
+#[cfg(not(target_os = "windows"))]
+const VR_PROCESSES: &[&str] = &[
+    "vrserver",
+    "vrcompositor",
+    "vrdashboard",
+    "monado-service",
+    "wivrn-server",
+];
+
+const RUNTIME_PROCESSES: &[&str] = &["OVRServer", "vrserver", "monado-service", "wivrn-server"];
+
+// Including the current log; the support bundle collects all of them.
+const KEPT_LOG_FILES: usize = 3;
+
+const SERVICE_TIMEOUT: Duration = Duration::from_secs(15);
+
+const SERVICE_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+const DASH_SWAP_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+const HEADSET_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
 struct VRSettings {
     render_scale: f32,
     use_openxr: bool,
     use_steamvr: bool,
+    linux_runtime: LinuxRuntime,
+    headset_model: String,
+    connection_type: ConnectionType,
     encode_bitrate_mbps: u32,
     encode_resolution_width: u32,
     encode_resolution_height: u32,
@@ -23,7 +92,9 @@ struct VRSettings {
     foveated_rendering: bool,
     foveated_level: FoveatedLevel,
     cpu_priority_boost: bool,
+    cpu_priority: CpuPriority,
     gpu_priority: GPUPriority,
+    io_priority: IoPriority,
     pixel_density: f32,
     fov_scale: f32,
     force_composition_layers: bool,
@@ -41,23 +112,53 @@ struct VRSettings {
     frame_throttle_fps: u32,
     shake_reduction: bool,
     audio_switching: bool,
+    audio_output_pattern: String,
+    audio_input_pattern: String,
     super_sampling: f32,
     mirror_window: bool,
     guardian_visibility: bool,
-    cpu_affinity: u32,
+    affinity_cores: Vec<bool>,
     power_plan: PowerPlan,
     oculus_killer_enabled: bool,
+    oculus_killer_replacement: String,
     relinked_mode: bool,
     disable_asw: bool,
     enable_steamvr_autostart: bool,
     enable_runtime_high_priority: bool,
     allow_other_software: bool,
+    #[serde(skip_serializing)]
     custom_startup_program: String,
+    startup_programs: Vec<launch::StartupProgram>,
+    runtime_start_timeout_seconds: u32,
+    install_paths: install::InstallPaths,
+    install_path_overrides: install::InstallPaths,
+    drift_monitor_enabled: bool,
+    drift_monitor_interval_seconds: u32,
+    drift_auto_reapply: bool,
+    autotune_enabled: bool,
+    autotune_frame_log: String,
+    autotune_headroom_percent: u32,
+    autotune_min_render_scale: f32,
+    autotune_max_render_scale: f32,
     custom_fps: u32,
     disable_oled_mura: bool,
     debug_logging: bool,
     disable_telemetry: bool,
     disable_login: bool,
+    active_game_profile: String,
+    hooks: Vec<hooks::Hook>,
+    game_processes: Vec<String>,
+    apply_scripts: Vec<scripting::ApplyScript>,
+    script_allowed_paths: Vec<String>,
+    script_registry_allowed: Vec<String>,
+    hotkeys: Vec<hotkeys::Hotkey>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+enum LinuxRuntime {
+    SteamVR,
+    Monado,
+    WiVRn,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
@@ -77,6 +178,13 @@ enum FoveatedLevel {
     HighTop,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+enum CpuPriority {
+    Normal,
+    High,
+    Realtime,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 enum GPUPriority {
     Normal,
@@ -84,6 +192,15 @@ enum GPUPriority {
     Realtime,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+enum IoPriority {
+    Normal,
+    High,
+    Realtime,
+}
+
+// Variant names are stored in settings.json.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 enum UpscalingType {
     NIS,
@@ -91,6 +208,13 @@ enum UpscalingType {
     CAS,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+enum ConnectionType {
+    LinkCable,
+    AirLink,
+    DisplayPort,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 enum PowerPlan {
     Balanced,
@@ -98,22 +222,62 @@ enum PowerPlan {
     PowerSaver,
 }
 
+#[cfg(target_os = "windows")]
+impl PowerPlan {
+    fn scheme_guid(&self) -> &'static str {
+        match self {
+            PowerPlan::Balanced => "381b4222-f694-41f0-9685-ff5bb260df2e",
+            PowerPlan::HighPerformance => "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c",
+            PowerPlan::PowerSaver => "a1841308-3541-4fab-bc81-f71556f20b4a",
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct RegistryWrite {
+    step: &'static str,
+    field: &'static str,
+    path: &'static str,
+    name: &'static str,
+    value: u32,
+}
+
+impl VRSettings {
+    // Pulls the encode and frame-rate settings back inside what the selected headset supports.
+    fn fit_to_headset(&mut self) {
+        let model = headsets::find_or_default(&self.headset_model);
+        let width = model.encode_width_range();
+        let height = model.encode_height_range();
+        self.encode_resolution_width = self.encode_resolution_width.clamp(*width.start(), *width.end());
+        self.encode_resolution_height = self.encode_resolution_height.clamp(*height.start(), *height.end());
+        self.frame_throttle_fps = self.frame_throttle_fps.min(model.max_refresh_rate());
+        if !model.refresh_rates.contains(&self.custom_fps) {
+            self.custom_fps = model.max_refresh_rate();
+        }
+    }
+}
+
 impl Default for VRSettings {
     fn default() -> Self {
         Self {
             render_scale: 1.2,
             use_openxr: true,
             use_steamvr: false,
+            linux_runtime: LinuxRuntime::SteamVR,
+            headset_model: String::from(headsets::DEFAULT_HEADSET),
+            connection_type: ConnectionType::LinkCable,
             encode_bitrate_mbps: 300,
             encode_resolution_width: 2784,
-            encode_resolution_height: 1472,
+            encode_resolution_height: 1584,
             link_sharpening: 0.5,
             asw_enabled: true,
             asw_mode: ASWMode::Auto,
             foveated_rendering: true,
             foveated_level: FoveatedLevel::High,
             cpu_priority_boost: true,
+            cpu_priority: CpuPriority::High,
             gpu_priority: GPUPriority::High,
+            io_priority: IoPriority::Normal,
             pixel_density: 1.0,
             fov_scale: 1.0,
             force_composition_layers: false,
@@ -131,23 +295,45 @@ impl Default for VRSettings {
             frame_throttle_fps: 90,
             shake_reduction: false,
             audio_switching: true,
+            audio_output_pattern: String::from("Oculus Virtual Audio Device"),
+            audio_input_pattern: String::from("Oculus Virtual Audio Device"),
             super_sampling: 1.0,
             mirror_window: false,
             guardian_visibility: true,
-            cpu_affinity: 0,
+            affinity_cores: Vec::new(),
             power_plan: PowerPlan::HighPerformance,
             oculus_killer_enabled: false,
+            oculus_killer_replacement: String::from(""),
             relinked_mode: false,
             disable_asw: false,
             enable_steamvr_autostart: true,
             enable_runtime_high_priority: true,
             allow_other_software: true,
             custom_startup_program: String::from(""),
+            startup_programs: Vec::new(),
+            runtime_start_timeout_seconds: 30,
+            install_paths: install::InstallPaths::default(),
+            install_path_overrides: install::InstallPaths::default(),
+            drift_monitor_enabled: false,
+            drift_monitor_interval_seconds: 30,
+            drift_auto_reapply: false,
+            autotune_enabled: false,
+            autotune_frame_log: String::from(""),
+            autotune_headroom_percent: 10,
+            autotune_min_render_scale: 0.7,
+            autotune_max_render_scale: 1.5,
             custom_fps: 120,
             disable_oled_mura: false,
             debug_logging: false,
             disable_telemetry: false,
             disable_login: false,
+            active_game_profile: String::from(""),
+            hooks: Vec::new(),
+            game_processes: Vec::new(),
+            apply_scripts: Vec::new(),
+            script_allowed_paths: Vec::new(),
+            script_registry_allowed: Vec::new(),
+            hotkeys: Vec::new(),
         }
     }
 }
@@ -159,6 +345,7 @@ struct ProcessInfo {
     pid: Option<u32>,
     cpu_usage: f32,
     memory_mb: u64,
+    priority: Option<String>,
 }
 
 #[derive(PartialEq, Clone)]
@@ -166,16 +353,78 @@ enum ProcessStatus {
     Running,
     Stopped,
     Frozen,
-    Restarting,
 }
 
 struct VRPerformanceApp {
     settings: VRSettings,
+    // What settings.json holds as far as this instance knows, to tell external edits from unsaved ones.
+    saved_settings: VRSettings,
+    instance_lock: Option<config::InstanceLock>,
+    settings_watcher: Option<settings_watch::SettingsWatcher>,
+    reload_conflict: Option<settings_watch::ReloadConflict>,
+    reload_notice: Option<String>,
+    history: history::SettingsHistory,
     system: Arc<Mutex<System>>,
     processes: Vec<ProcessInfo>,
     current_tab: Tab,
     stats: PerformanceStats,
-    show_advanced: bool,
+    redact_support_bundle: bool,
+    support_bundle_status: Option<String>,
+    support_bundle_job: Option<mpsc::Receiver<std::io::Result<PathBuf>>>,
+    script_status: Option<String>,
+    runtime_running: bool,
+    priority_status: Vec<String>,
+    apply_report: Option<report::ApplyReport>,
+    elevated: bool,
+    audio: audio::AudioSwitcher,
+    audio_status: Option<String>,
+    audio_devices: Option<(Vec<audio::AudioDevice>, Vec<audio::AudioDevice>)>,
+    launch_progress: Option<launch::LaunchProgress>,
+    oculus_killer_status: Option<String>,
+    dash_swap_status: Option<file_swap::SwapStatus>,
+    dash_swap_checked: Option<Instant>,
+    dash_swap_job: Option<mpsc::Receiver<Option<(file_swap::SwapStatus, bool)>>>,
+    // Whether the swapped-in file is the configured replacement, as of the last status check.
+    dash_replacement_current: bool,
+    services: services::ServiceWorker,
+    service_states: services::ServiceStates,
+    service_job: Option<mpsc::Receiver<Option<String>>>,
+    restart_jobs: Vec<mpsc::Receiver<(String, std::io::Result<()>)>>,
+    service_status: Option<String>,
+    oculus_killer_job: Option<mpsc::Receiver<(Option<String>, report::ApplyReport)>>,
+    // The last swap requested while one was running; only the latest one is kept.
+    oculus_killer_queued: Option<(file_swap::FileSwap, bool, Option<PathBuf>)>,
+    system_state: Option<system_state::SystemSnapshot>,
+    drift: Vec<system_state::Drift>,
+    drift_monitor: Option<system_state::DriftMonitor>,
+    drift_notice: Option<String>,
+    hardware: Option<recommend::Hardware>,
+    headset_monitor: headsets::ConnectionMonitor,
+    connected_headsets: Option<Vec<String>>,
+    running_games: Option<Vec<(String, u32)>>,
+    hotkey_listener: Option<hotkeys::HotkeyListener>,
+    hotkey_status: Option<String>,
+    hotkey_capture: Option<usize>,
+    autotune_session: Option<autotune::Session>,
+    autotune_history: autotune::TuneHistory,
+    autotune_status: Option<String>,
+    profile_path: String,
+    share_input: String,
+    import_preview: Option<profile_io::ImportPreview>,
+    profile_status: Option<String>,
+    game_profiles: Vec<profiles::GameProfile>,
+    overrides: profiles::Overlay,
+    layers: profiles::Resolved,
+    layer_edit: Option<(profiles::Layer, String, String)>,
+    layer_field: String,
+    new_profile_name: String,
+    show_default_layer: bool,
+    cpu_count: usize,
+    performance_cores: Option<Vec<usize>>,
+    // Processes whose affinity this app narrowed, so "All cores" can widen them again.
+    restricted_pids: HashSet<u32>,
+    #[cfg(target_os = "linux")]
+    power: power::PowerManager,
 }
 
 #[derive(PartialEq)]
@@ -186,6 +435,7 @@ enum Tab {
     Advanced,
     Stats,
     ReLinked,
+    Profiles,
 }
 
 struct PerformanceStats {
@@ -199,21 +449,63 @@ struct PerformanceStats {
 
 impl Default for VRPerformanceApp {
     fn default() -> Self {
-        let _ = WriteLogger::init(LevelFilter::Debug, Config::default(), fs::File::create("vr_suite.log").unwrap());
-        
-        let mut settings = VRSettings::default();
-        if let Ok(mut file) = fs::File::open("settings.json") {
-            let mut contents = String::new();
-            if file.read_to_string(&mut contents).is_ok() {
-                if let Ok(loaded) = serde_json::from_str(&contents) {
-                    settings = loaded;
-                }
+        // Only the instance holding the lock may rotate and write the shared log files.
+        let instance_lock = config::InstanceLock::acquire();
+        init_logging(matches!(instance_lock, Ok(Some(_))));
+        let instance_lock = match instance_lock {
+            Ok(Some(lock)) => Some(lock),
+            Ok(None) => {
+                warn!("Settings are locked by another instance (pid {}), logging to stderr only", config::lock_owner());
+                None
+            }
+            Err(e) => {
+                warn!("Failed to lock settings: {}", e);
+                None
             }
+        };
+        
+        if let Err(e) = config::migrate_legacy() {
+            warn!("Failed to migrate settings to {}: {}", config::config_dir().display(), e);
+        }
+        info!("Settings stored in {}", config::config_dir().display());
+        let saved_settings: VRSettings = config::read_with_backup(&config::settings_path()).unwrap_or_default();
+        let mut settings = saved_settings.clone();
+        
+        if !settings.custom_startup_program.is_empty() && settings.startup_programs.is_empty() {
+            let path = std::mem::take(&mut settings.custom_startup_program);
+            settings.startup_programs.push(launch::StartupProgram { path, ..Default::default() });
+        }
+        settings.install_paths = install::discover();
+        // Older releases defaulted to an encode height below every headset's range.
+        settings.fit_to_headset();
+        
+        let system = System::new_all();
+        let cpu_count = system.cpus().len();
+        
+        let game_profiles = profiles::load_all();
+        if !settings.active_game_profile.is_empty() && !game_profiles.iter().any(|p| p.name == settings.active_game_profile) {
+            warn!("Game profile {} no longer exists", settings.active_game_profile);
+            settings.active_game_profile.clear();
         }
+        let active = game_profiles.iter().find(|p| p.name == settings.active_game_profile);
+        let layers = profiles::resolve(&settings, active, &profiles::Overlay::new())
+            .unwrap_or_else(|_| profiles::Resolved { settings: settings.clone(), sources: Default::default() });
         
         Self {
+            history: history::SettingsHistory::new(&settings),
             settings,
-            system: Arc::new(Mutex::new(System::new_all())),
+            saved_settings,
+            instance_lock,
+            settings_watcher: match settings_watch::SettingsWatcher::start() {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    warn!("Failed to watch {} for changes: {}", config::config_dir().display(), e);
+                    None
+                }
+            },
+            reload_conflict: None,
+            reload_notice: None,
+            system: Arc::new(Mutex::new(system)),
             processes: Vec::new(),
             current_tab: Tab::Performance,
             stats: PerformanceStats {
@@ -224,44 +516,206 @@ impl Default for VRPerformanceApp {
                 vram_used_gb: 0.0,
                 latency_ms: 0.0,
             },
-            show_advanced: false,
+            redact_support_bundle: true,
+            support_bundle_status: None,
+            support_bundle_job: None,
+            script_status: None,
+            runtime_running: false,
+            priority_status: Vec::new(),
+            apply_report: None,
+            elevated: elevation::is_elevated(),
+            audio: audio::AudioSwitcher::new(),
+            audio_status: None,
+            audio_devices: None,
+            launch_progress: None,
+            oculus_killer_status: None,
+            dash_swap_status: None,
+            dash_swap_checked: None,
+            dash_swap_job: None,
+            dash_replacement_current: false,
+            services: services::ServiceWorker::start(
+                services::ServiceController::new(),
+                services::MANAGED_SERVICES,
+                SERVICE_REFRESH_INTERVAL,
+            ),
+            service_states: Vec::new(),
+            service_job: None,
+            restart_jobs: Vec::new(),
+            service_status: None,
+            oculus_killer_job: None,
+            oculus_killer_queued: None,
+            system_state: Some(system_state::SystemSnapshot::read()),
+            drift: Vec::new(),
+            drift_monitor: None,
+            drift_notice: None,
+            hardware: None,
+            headset_monitor: headsets::ConnectionMonitor::start(HEADSET_POLL_INTERVAL),
+            connected_headsets: None,
+            hotkey_listener: None,
+            hotkey_status: None,
+            hotkey_capture: None,
+            running_games: None,
+            autotune_session: None,
+            autotune_history: autotune::TuneHistory::load(),
+            autotune_status: None,
+            profile_path: String::from("profile.json"),
+            share_input: String::from(""),
+            import_preview: None,
+            profile_status: None,
+            game_profiles,
+            overrides: profiles::Overlay::new(),
+            layers,
+            layer_edit: None,
+            layer_field: String::from(""),
+            new_profile_name: String::from(""),
+            show_default_layer: false,
+            cpu_count,
+            performance_cores: affinity::performance_cores(),
+            restricted_pids: HashSet::new(),
+            #[cfg(target_os = "linux")]
+            power: power::PowerManager::new(power::CpuFreq::new()),
         }
     }
 }
 
 impl VRPerformanceApp {
-    fn save_settings(&self) {
-        if let Ok(json) = serde_json::to_string_pretty(&self.settings) {
-            if let Ok(mut file) = fs::File::create("settings.json") {
-                let _ = file.write_all(json.as_bytes());
+    // Everything under the config directory is written only by the instance holding the lock.
+    fn check_writable(&self) -> std::io::Result<()> {
+        match self.instance_lock {
+            Some(_) => Ok(()),
+            None => Err(config::locked_error()),
+        }
+    }
+    
+    fn save_settings(&mut self) -> std::io::Result<()> {
+        self.check_writable()?;
+        let json = serde_json::to_string_pretty(&self.settings)?;
+        config::write_atomic(&config::settings_path(), json.as_bytes())?;
+        self.saved_settings = self.settings.clone();
+        Ok(())
+    }
+    
+    fn update_settings_watcher(&mut self) {
+        let changes = match self.settings_watcher.as_mut().and_then(|watcher| watcher.poll()) {
+            Some(changes) => changes,
+            None => return,
+        };
+        if changes.profiles {
+            self.reload_game_profiles();
+        }
+        if changes.settings {
+            self.reload_settings();
+        }
+    }
+    
+    fn reload_game_profiles(&mut self) {
+        self.game_profiles = profiles::load_all();
+        debug!("Reloaded {} game profile(s)", self.game_profiles.len());
+        if !self.settings.active_game_profile.is_empty() && self.active_game_profile().is_none() {
+            warn!("Game profile {} was removed outside the app", self.settings.active_game_profile);
+            self.select_game_profile("");
+        }
+        self.resolve_layers();
+    }
+    
+    // Re-validates settings.json the same way as an imported profile; bad values keep what was saved before.
+    fn read_external_settings(&self) -> Result<VRSettings, String> {
+        let contents = fs::read_to_string(config::settings_path()).map_err(|e| e.to_string())?;
+        let fields: settings_diff::Fields = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+        let (machine, shareable): (settings_diff::Fields, settings_diff::Fields) =
+            fields.into_iter().partition(|(key, _)| profile_io::MACHINE_FIELDS.contains(&key.as_str()));
+        let preview = profile_io::prepare_import(&self.saved_settings, shareable, config::SETTINGS_FILE);
+        for issue in &preview.issues {
+            warn!("{} in {}: {}", issue.field, config::SETTINGS_FILE, issue.message);
+        }
+        let mut accepted = preview.accepted;
+        accepted.extend(machine);
+        settings_diff::with_fields(&self.saved_settings, &accepted).map_err(|e| e.to_string())
+    }
+    
+    // Picks up external edits, keeping unsaved in-app edits unless they touch the same fields.
+    fn reload_settings(&mut self) {
+        let external = match self.read_external_settings() {
+            Ok(external) => external,
+            Err(e) => {
+                warn!("{} changed but could not be loaded: {}", config::SETTINGS_FILE, e);
+                self.reload_notice = Some(format!("{} changed but could not be loaded: {}", config::SETTINGS_FILE, e));
+                return;
             }
+        };
+        let saved = settings_diff::to_fields(&self.saved_settings);
+        let changed = settings_diff::changed_fields(&saved, &settings_diff::to_fields(&external));
+        if changed.is_empty() {
+            return;
+        }
+        info!("{} changed on disk: {}", config::SETTINGS_FILE, changed.join(", "));
+        
+        let edited = settings_diff::changed_fields(&saved, &settings_diff::to_fields(&self.settings));
+        let conflicts: Vec<String> = changed.iter().filter(|field| edited.contains(field)).cloned().collect();
+        if conflicts.is_empty() {
+            self.merge_external(external, &changed);
+            self.reload_notice = Some(format!("Reloaded {} setting(s) from {}", changed.len(), config::SETTINGS_FILE));
+        } else {
+            warn!("External change to {} conflicts with unsaved edits: {}", config::SETTINGS_FILE, conflicts.join(", "));
+            self.reload_conflict = Some(settings_watch::ReloadConflict { external, fields: conflicts });
+        }
+    }
+    
+    fn merge_external(&mut self, external: VRSettings, fields: &[String]) {
+        let mut taken = settings_diff::to_fields(&external);
+        taken.retain(|key, _| fields.contains(key));
+        match settings_diff::with_fields(&self.settings, &taken) {
+            Ok(settings) => self.settings = settings,
+            Err(e) => warn!("Failed to merge external settings: {}", e),
         }
+        self.saved_settings = external;
+        self.resolve_layers();
+    }
+    
+    fn resolve_reload_conflict(&mut self, use_file: bool) {
+        let conflict = match self.reload_conflict.take() {
+            Some(conflict) => conflict,
+            None => return,
+        };
+        let saved = settings_diff::to_fields(&self.saved_settings);
+        let mut fields = settings_diff::changed_fields(&saved, &settings_diff::to_fields(&conflict.external));
+        if !use_file {
+            fields.retain(|field| !conflict.fields.contains(field));
+        }
+        info!(
+            "{} conflict resolved: {} for {}",
+            config::SETTINGS_FILE,
+            if use_file { "took the file version" } else { "kept in-app edits" },
+            conflict.fields.join(", ")
+        );
+        self.merge_external(conflict.external, &fields);
+        self.reload_notice = Some(format!("Reloaded {} setting(s) from {}", fields.len(), config::SETTINGS_FILE));
     }
     
     fn update_processes(&mut self) {
         let mut sys = self.system.lock().unwrap();
         sys.refresh_processes();
         
-        let vr_processes = vec![
-            "OVRServer_x64.exe",
-            "OculusClient.exe",
-            "vrserver.exe",
-            "vrdashboard.exe",
-            "vrcompositor.exe",
-        ];
-        
+        let previous: Vec<(String, Option<u32>)> = self.processes.iter()
+            .map(|p| (p.name.clone(), p.pid))
+            .collect();
+        let frozen: Vec<String> = self.processes.iter()
+            .filter(|p| p.status == ProcessStatus::Frozen)
+            .map(|p| p.name.clone())
+            .collect();
         self.processes.clear();
         
-        for proc_name in vr_processes {
+        for proc_name in VR_PROCESSES {
             let proc = sys.processes_by_name(proc_name).next();
             
             if let Some(p) = proc {
                 self.processes.push(ProcessInfo {
                     name: proc_name.to_string(),
-                    status: ProcessStatus::Running,
+                    status: if hang::is_frozen(p.pid().as_u32()) { ProcessStatus::Frozen } else { ProcessStatus::Running },
                     pid: Some(p.pid().as_u32()),
                     cpu_usage: p.cpu_usage(),
                     memory_mb: p.memory() / 1024 / 1024,
+                    priority: priority::describe_priority(p.pid().as_u32()),
                 });
             } else {
                 self.processes.push(ProcessInfo {
@@ -270,262 +724,1285 @@ impl VRPerformanceApp {
                     pid: None,
                     cpu_usage: 0.0,
                     memory_mb: 0,
+                    priority: None,
                 });
             }
         }
+        let games: Vec<(String, u32)> = self.layers.settings.game_processes.iter()
+            .filter(|name| !name.trim().is_empty())
+            .filter_map(|name| sys.processes_by_name(name.trim()).next().map(|p| (name.trim().to_string(), p.pid().as_u32())))
+            .collect();
+        drop(sys);
+        
+        for p in &self.processes {
+            if p.status == ProcessStatus::Frozen && !frozen.contains(&p.name) {
+                warn!("{} is not responding", p.name);
+                let pid = p.pid.map(|pid| pid.to_string()).unwrap_or_default();
+                self.fire_hooks(hooks::HookEvent::ProcessFrozen, &[("process", p.name.clone()), ("pid", pid)]);
+            }
+        }
+        self.update_games(games);
+        
+        if !previous.is_empty() {
+            let started: Vec<(String, u32)> = self.processes.iter()
+                .filter_map(|p| {
+                    let pid = p.pid?;
+                    let was = previous.iter().find(|(name, _)| *name == p.name).and_then(|(_, pid)| *pid);
+                    if was == Some(pid) { None } else { Some((p.name.clone(), pid)) }
+                })
+                .collect();
+            for (name, pid) in started {
+                self.on_process_started(&name, pid);
+            }
+        }
+        
+        let runtime_running = self.processes.iter()
+            .any(|p| p.status == ProcessStatus::Running && is_runtime_process(&p.name));
+        if runtime_running != self.runtime_running {
+            self.runtime_running = runtime_running;
+            if runtime_running {
+                self.on_runtime_started();
+            } else {
+                self.on_runtime_stopped();
+            }
+        }
     }
     
-    fn apply_settings(&mut self) {
-        info!("Applying settings");
-        self.apply_oculus_link_settings();
-        self.apply_openxr_settings();
-        self.apply_process_priorities();
-        self.apply_asw_settings();
-        self.apply_additional_settings();
-        self.toggle_oculus_killer(self.settings.oculus_killer_enabled);
-        self.apply_relinked_settings();
-        self.save_settings();
+    // Hooks only ever come from the base settings; profiles and overrides cannot add commands.
+    fn fire_hooks(&self, event: hooks::HookEvent, details: &[(&str, String)]) {
+        hooks::fire(&self.settings.hooks, event, details);
     }
     
-    fn apply_oculus_link_settings(&self) {
-        #[cfg(target_os = "windows")]
-        {
-            use winreg::enums::*;
-            use winreg::RegKey;
-            
-            let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-            if let Ok(oculus_key) = hkcu.create_subkey("Software\\Oculus\\RemoteHeadset") {
-                let (key, _) = oculus_key;
-                let _ = key.set_value("BitrateMbps", &self.settings.encode_bitrate_mbps);
-                let _ = key.set_value("EncodeResolutionWidth", &self.settings.encode_resolution_width);
-                let _ = key.set_value("EncodeResolutionHeight", &self.settings.encode_resolution_height);
-                let enabled: u32 = if self.settings.link_sharpening > 0.0 { 1 } else { 0 };
-                let _ = key.set_value("LinkSharpeningEnabled", &enabled);
-                let strength: u32 = (self.settings.link_sharpening * 100.0) as u32;
-                let _ = key.set_value("LinkSharpeningStrength", &strength);
+    // The first scan only records what is already running, like process start detection.
+    fn update_games(&mut self, games: Vec<(String, u32)>) {
+        if let Some(previous) = &self.running_games {
+            for (name, pid) in &games {
+                if !previous.contains(&(name.clone(), *pid)) {
+                    info!("Game {} started with pid {}", name, pid);
+                    self.fire_hooks(hooks::HookEvent::GameLaunched, &[("game", name.clone()), ("pid", pid.to_string())]);
+                }
+            }
+            for (name, pid) in previous {
+                if !games.contains(&(name.clone(), *pid)) {
+                    info!("Game {} exited", name);
+                    self.fire_hooks(hooks::HookEvent::GameExited, &[("game", name.clone()), ("pid", pid.to_string())]);
+                }
             }
         }
+        self.running_games = Some(games);
     }
     
-    fn apply_openxr_settings(&self) {
-        #[cfg(target_os = "windows")]
-        {
-            use winreg::enums::*;
-            use winreg::RegKey;
-            
-            let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-            if let Ok(openxr_key) = hklm.create_subkey("SOFTWARE\\Khronos\\OpenXR\\1") {
-                let (key, _) = openxr_key;
-                
-                if self.settings.use_openxr {
-                    let _ = key.set_value("ActiveRuntime", &"oculus");
-                } else if self.settings.use_steamvr {
-                    let _ = key.set_value("ActiveRuntime", &"steamvr");
+    // Re-registers whenever the bindings change, then runs the actions pressed since the last frame.
+    // A binding being typed is only registered once its field loses focus, not at every keystroke.
+    fn update_hotkeys(&mut self, ctx: &egui::Context) {
+        let editing = ctx.memory(|memory| memory.focused())
+            .is_some_and(|id| (0..self.settings.hotkeys.len()).any(|i| id == hotkey_binding_id(i)));
+        let bindings: Vec<(hotkeys::HotkeyAction, hotkeys::Binding)> = self.layers.settings.hotkeys.iter()
+            .filter(|hotkey| hotkey.enabled)
+            .filter_map(|hotkey| hotkeys::Binding::parse(&hotkey.binding).ok().map(|binding| (hotkey.action, binding)))
+            .collect();
+        let current = self.hotkey_listener.as_ref().map(|listener| &listener.bindings);
+        if !editing && current.map_or(!bindings.is_empty(), |current| *current != bindings) {
+            // Drop the old listener first so its bindings are free to register again.
+            self.hotkey_listener = None;
+            self.hotkey_status = None;
+            if !bindings.is_empty() {
+                let ctx = ctx.clone();
+                self.hotkey_listener = Some(hotkeys::HotkeyListener::start(bindings, move || ctx.request_repaint()));
+            }
+        }
+        
+        let events = self.hotkey_listener.as_ref().map(|listener| listener.poll()).unwrap_or_default();
+        for event in events {
+            match event {
+                hotkeys::HotkeyEvent::Pressed(action) => self.run_hotkey_action(action),
+                hotkeys::HotkeyEvent::Failed(message) => {
+                    warn!("Hotkey failed: {}", message);
+                    self.hotkey_status = Some(message);
                 }
             }
         }
     }
     
-    fn apply_process_priorities(&self) {
-        #[cfg(target_os = "windows")]
-        {
-            use windows::Win32::System::Threading::*;
-            use windows::Win32::Foundation::*;
-            
-            if self.settings.cpu_priority_boost {
-                for proc in &self.processes {
-                    if let Some(pid) = proc.pid {
-                        if proc.name.contains("OVRServer") || proc.name.contains("vrserver") {
-                            unsafe {
-                                if let Ok(handle) = OpenProcess(PROCESS_SET_INFORMATION, false, pid) {
-                                    let priority = match self.settings.gpu_priority {
-                                        GPUPriority::Realtime => REALTIME_PRIORITY_CLASS,
-                                        GPUPriority::High => HIGH_PRIORITY_CLASS,
-                                        GPUPriority::Normal => NORMAL_PRIORITY_CLASS,
-                                    };
-                                    let _ = SetPriorityClass(handle, priority);
-                                    let _ = CloseHandle(handle);
-                                }
-                            }
-                        }
-                    }
+    fn run_hotkey_action(&mut self, action: hotkeys::HotkeyAction) {
+        info!("Hotkey: {}", action.label());
+        match action {
+            hotkeys::HotkeyAction::RestartOvrServer => self.restart_process("OVRServer_x64.exe"),
+            hotkeys::HotkeyAction::CycleAswMode => self.cycle_asw_mode(),
+            hotkeys::HotkeyAction::SwitchProfile => {
+                // Steps through no profile, then each game profile in order.
+                let names: Vec<String> = std::iter::once(String::from(""))
+                    .chain(self.game_profiles.iter().map(|p| p.name.clone()))
+                    .collect();
+                let current = names.iter().position(|name| *name == self.settings.active_game_profile).unwrap_or(0);
+                let next = names[(current + 1) % names.len()].clone();
+                self.select_game_profile(&next);
+                self.apply_settings();
+            }
+            hotkeys::HotkeyAction::ToggleRecording => self.toggle_recording(),
+            hotkeys::HotkeyAction::KillOculusClient => self.kill_oculus_client(),
+        }
+    }
+    
+    fn cycle_asw_mode(&mut self) {
+        let next = match self.layers.settings.asw_mode {
+            ASWMode::Auto => ASWMode::Force45FPS,
+            ASWMode::Force45FPS => ASWMode::Force30FPS,
+            ASWMode::Force30FPS => ASWMode::Off,
+            ASWMode::Off => ASWMode::Auto,
+        };
+        // A game profile or override would hide a base change, so those go to the override layer.
+        match self.layers.source("asw_mode") {
+            profiles::Layer::Game | profiles::Layer::Override => {
+                let value = serde_json::to_value(&next).unwrap_or_default();
+                if let Err(e) = self.set_layer_value(profiles::Layer::Override, "asw_mode", Some(value)) {
+                    warn!("Failed to cycle ASW mode: {}", e);
+                    return;
                 }
             }
+            _ => {
+                self.settings.asw_mode = next;
+                self.resolve_layers();
+            }
         }
+        let mut report = report::ApplyReport::new();
+        report.escalate = !self.elevated;
+        self.with_effective_settings(|app| app.apply_asw_settings(&mut report));
+        self.complete_apply(report);
     }
     
-    fn apply_asw_settings(&self) {
-        #[cfg(target_os = "windows")]
-        {
-            use winreg::enums::*;
-            use winreg::RegKey;
-            
-            let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-            if let Ok(debug_key) = hkcu.create_subkey("Software\\Oculus\\Debug") {
-                let (key, _) = debug_key;
-                
-                let asw_value: u32 = match self.settings.asw_mode {
-                    ASWMode::Off => 0,
-                    ASWMode::Auto => 1,
-                    ASWMode::Force45FPS => 2,
-                    ASWMode::Force30FPS => 3,
-                };
-                
-                let _ = key.set_value("ASW", &asw_value);
+    // Captures frame times from the auto-tune log; with auto-tune off the session is only summarized.
+    fn toggle_recording(&mut self) {
+        if let Some(session) = self.autotune_session.take() {
+            info!("Frame recording stopped");
+            if self.layers.settings.autotune_enabled {
+                self.finish_autotune_session(&session);
+                return;
             }
+            self.autotune_status = Some(match session.frame_times() {
+                Ok(samples) => match autotune::FrameStats::from_samples(samples) {
+                    Some(stats) => format!(
+                        "Recorded {} frames: p50 {:.1} / p95 {:.1} / p99 {:.1} ms",
+                        stats.samples, stats.p50_ms, stats.p95_ms, stats.p99_ms
+                    ),
+                    None => String::from("Recorded no frames"),
+                },
+                Err(e) => format!("Failed to read the frame log: {}", e),
+            });
+        } else if self.settings.autotune_frame_log.is_empty() {
+            warn!("Cannot record without a frame time log");
+            self.hotkey_status = Some(String::from("Set a frame time log under Stats > Auto-Tune to record"));
+        } else {
+            info!("Frame recording started");
+            self.autotune_session = Some(autotune::Session::begin(Path::new(&self.settings.autotune_frame_log)));
         }
     }
     
-    fn apply_additional_settings(&self) {
-        #[cfg(target_os = "windows")]
-        {
-            use winreg::enums::*;
-            use winreg::RegKey;
-            
-            let power_guid = match self.settings.power_plan {
-                PowerPlan::Balanced => "381b4222-f694-41f0-9685-ff5bb260df2e",
-                PowerPlan::HighPerformance => "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c",
-                PowerPlan::PowerSaver => "a1841308-3541-4fab-bc81-f71556f20b4a",
-            };
-            let _ = Command::new("powercfg").args(["/s", power_guid]).output();
-            
-            let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-            if let Ok(oculus_key) = hkcu.create_subkey("Software\\Oculus\\RemoteHeadset") {
-                let (key, _) = oculus_key;
-                let mirror_val: u32 = if self.settings.mirror_window { 1 } else { 0 };
-                let guardian_val: u32 = if self.settings.guardian_visibility { 1 } else { 0 };
-                let _ = key.set_value("MirrorWindow", &mirror_val);
-                let _ = key.set_value("GuardianVisibility", &guardian_val);
+    fn update_headsets(&mut self) {
+        let headsets = match self.headset_monitor.poll() {
+            Some(headsets) => headsets,
+            None => return,
+        };
+        if let Some(previous) = &self.connected_headsets {
+            for name in headsets.iter().filter(|name| !previous.contains(name)) {
+                info!("Headset connected: {}", name);
+                self.fire_hooks(hooks::HookEvent::HeadsetConnected, &[("headset", name.clone())]);
             }
-            
-            if let Ok(mut file) = fs::File::create("openxr_toolkit.ini") {
-                let _ = write!(file, "upscaling_enabled = {}", self.settings.upscaling_enabled);
+            for name in previous.iter().filter(|name| !headsets.contains(name)) {
+                info!("Headset disconnected: {}", name);
+                self.fire_hooks(hooks::HookEvent::HeadsetDisconnected, &[("headset", name.clone())]);
             }
         }
+        self.connected_headsets = Some(headsets);
     }
     
-    fn toggle_oculus_killer(&self, enable: bool) {
-        #[cfg(target_os = "windows")]
-        {
-            let _ = Command::new("sc").args(["stop", "OVRService"]).output();
-            std::thread::sleep(std::time::Duration::from_secs(2));
-            
-            let path = r"C:\Program Files\Oculus\Support\oculus-dash\dash\bin";
-            let dash_path = format!("{}\\OculusDash.exe", path);
-            let bak_path = format!("{}\\OculusDash.exe.bak", path);
-            
-            if enable {
-                if !Path::new(&bak_path).exists() {
-                    let _ = fs::rename(&dash_path, &bak_path);
+    fn on_process_started(&mut self, name: &str, pid: u32) {
+        info!("{} started with pid {}", name, pid);
+        
+        let mut report = report::ApplyReport::new();
+        self.with_effective_settings(|app| {
+            app.apply_process_affinity(name, pid, &mut report);
+            if app.settings.cpu_priority_boost && is_runtime_process(name) {
+                app.apply_process_priority(name, pid, &mut report);
+            }
+        });
+        
+        let prefix = format!("{} (", name);
+        self.priority_status.retain(|line| !line.starts_with(&prefix));
+        self.priority_status.extend(priority_status_lines(&report));
+        // Failures land in the Apply report so they are seen even though no Apply was clicked.
+        if report.failure_count() > 0 {
+            self.merge_apply_report(report);
+        }
+    }
+    
+    fn on_runtime_started(&mut self) {
+        info!("VR runtime started");
+        let runtime = self.processes.iter()
+            .find(|p| p.status == ProcessStatus::Running && is_runtime_process(&p.name))
+            .map(|p| p.name.clone())
+            .unwrap_or_default();
+        self.fire_hooks(hooks::HookEvent::RuntimeStarted, &[("runtime", runtime)]);
+        
+        if self.settings.audio_switching {
+            self.audio_status = Some(match self.audio.switch_to_headset(&self.settings.audio_output_pattern, &self.settings.audio_input_pattern) {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("Failed to switch audio devices: {}", e);
+                    format!("Failed to switch audio devices: {}", e)
                 }
-            } else {
-                if Path::new(&bak_path).exists() {
-                    let _ = fs::remove_file(&dash_path);
-                    let _ = fs::rename(&bak_path, &dash_path);
+            });
+        }
+        
+        if self.layers.settings.autotune_enabled && !self.settings.autotune_frame_log.is_empty() {
+            self.autotune_session = Some(autotune::Session::begin(Path::new(&self.settings.autotune_frame_log)));
+        }
+    }
+    
+    fn on_runtime_stopped(&mut self) {
+        info!("VR runtime stopped");
+        self.fire_hooks(hooks::HookEvent::RuntimeStopped, &[]);
+        
+        match self.audio.restore() {
+            Ok(Some(message)) => self.audio_status = Some(message),
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Failed to restore audio devices: {}", e);
+                self.audio_status = Some(format!("Failed to restore audio devices: {}", e));
+            }
+        }
+        
+        #[cfg(target_os = "linux")]
+        self.restore_power_state();
+        
+        if let Some(session) = self.autotune_session.take() {
+            self.finish_autotune_session(&session);
+        }
+    }
+    
+    #[cfg(target_os = "linux")]
+    fn restore_power_state(&mut self) {
+        let error = match self.power.restore() {
+            Ok(()) => return,
+            Err(e) => e,
+        };
+        warn!("Failed to restore power state: {}", error);
+        if error.kind() != std::io::ErrorKind::PermissionDenied || self.elevated {
+            return;
+        }
+        // The plan was set through the elevated helper, so it has to be undone the same way.
+        if let Some(state) = self.power.take_saved() {
+            let mut report = report::ApplyReport::new();
+            report.privileged.push(elevation::HelperCommand::RestorePowerState { state });
+            self.complete_apply(report);
+        }
+    }
+    
+    // The session ran with the effective settings, so those are what gets tuned.
+    fn finish_autotune_session(&mut self, session: &autotune::Session) {
+        self.resolve_layers();
+        let effective = self.layers.settings.clone();
+        let bounds = autotune::Bounds {
+            min_render_scale: effective.autotune_min_render_scale,
+            max_render_scale: effective.autotune_max_render_scale,
+        };
+        let recorded = self.autotune_history.records.len();
+        let result = autotune::finish_session(session, &effective, &bounds, &mut self.autotune_history);
+        if self.autotune_history.records.len() != recorded {
+            self.save_autotune_history();
+        }
+        self.autotune_status = match result {
+            Ok(Some(tuned)) => match self.store_effective_settings(&tuned) {
+                Ok(()) => Some(String::from("Settings tuned for the next session")),
+                Err(e) => {
+                    warn!("Failed to save tuned settings: {}", e);
+                    Some(format!("Failed to save tuned settings: {}", e))
                 }
+            },
+            Ok(None) => self.autotune_history.records.last().map(|record| record.note.clone()),
+            Err(e) => {
+                warn!("Auto-tune could not read the frame time log: {}", e);
+                Some(format!("Could not read frame time log: {}", e))
             }
-            
-            let _ = Command::new("sc").args(["start", "OVRService"]).output();
-            
-            if enable {
-                use winreg::enums::*;
-                use winreg::RegKey;
-                let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-                if let Ok(key) = hklm.open_subkey_with_flags("SOFTWARE\\WOW6432Node\\Oculus VR, LLC\\Oculus\\Config", KEY_WRITE) {
-                    let _ = key.set_value("CoreChannel", &"NO_UPDATES");
+        };
+    }
+    
+    fn save_autotune_history(&self) {
+        if let Err(e) = self.check_writable().and_then(|_| self.autotune_history.save()) {
+            warn!("Failed to save auto-tune history: {}", e);
+        }
+    }
+    
+    fn revert_autotune(&mut self, index: usize) {
+        let record = match self.autotune_history.records.get(index) {
+            Some(record) => record.clone(),
+            None => return,
+        };
+        self.resolve_layers();
+        let result = autotune::revert(&self.layers.settings, &record)
+            .map_err(|e| e.to_string())
+            .and_then(|settings| self.store_effective_settings(&settings));
+        match result {
+            Ok(()) => {
+                info!("Reverted auto-tune change from {}", record.timestamp);
+                self.autotune_history.records[index].reverted = true;
+                self.save_autotune_history();
+            }
+            Err(e) => warn!("Failed to revert auto-tune change: {}", e),
+        }
+    }
+    
+    // Writes each field that differs from the effective settings to the layer it comes from, so a
+    // game profile or override does not hide the change.
+    fn store_effective_settings(&mut self, settings: &VRSettings) -> Result<(), String> {
+        let changes = settings_diff::changes(&settings_diff::to_fields(&self.layers.settings), &settings_diff::to_fields(settings));
+        let mut base = settings_diff::Fields::new();
+        for change in changes {
+            match self.layers.source(&change.field) {
+                layer @ (profiles::Layer::Game | profiles::Layer::Override) => {
+                    self.set_layer_value(layer, &change.field, Some(change.to))?
+                }
+                _ => {
+                    base.insert(change.field, change.to);
                 }
             }
         }
+        if !base.is_empty() {
+            self.settings = settings_diff::with_fields(&self.settings, &base).map_err(|e| e.to_string())?;
+            self.resolve_layers();
+            self.save_settings().map_err(|e| e.to_string())?;
+        }
+        Ok(())
     }
     
-    fn apply_relinked_settings(&mut self) {
-        if self.settings.debug_logging {
-            debug!("Applying ReLinked settings");
+    fn update_support_bundle(&mut self) {
+        let result = match self.support_bundle_job.as_ref().map(|job| job.try_recv()) {
+            Some(Ok(result)) => result,
+            Some(Err(mpsc::TryRecvError::Empty)) | None => return,
+            Some(Err(mpsc::TryRecvError::Disconnected)) => Err(std::io::Error::other("the bundle worker stopped")),
+        };
+        self.support_bundle_job = None;
+        self.support_bundle_status = Some(match result {
+            Ok(path) => {
+                info!("Support bundle written to {}", path.display());
+                format!("Support bundle written to {}", path.display())
+            }
+            Err(e) => format!("Failed to create support bundle: {}", e),
+        });
+    }
+    
+    fn apply_settings(&mut self) {
+        info!("Applying settings");
+        let mut report = report::ApplyReport::new();
+        report.escalate = !self.elevated;
+        self.with_effective_settings(|app| {
+            app.apply_oculus_link_settings(&mut report);
+            app.apply_openxr_settings(&mut report);
+            app.apply_process_priorities(&mut report);
+            app.apply_cpu_affinity(&mut report);
+            app.apply_asw_settings(&mut report);
+            app.apply_additional_settings(&mut report);
+            app.apply_relinked_settings(&mut report);
+            app.toggle_oculus_killer(app.settings.oculus_killer_enabled, &mut report);
+        });
+        self.run_apply_scripts(&mut report);
+        report.record("Save settings", config::settings_path().display().to_string(), self.save_settings());
+        self.history.mark_applied(&self.settings);
+        self.complete_apply(report);
+        let failures = self.apply_report.as_ref().map_or(0, |report| report.failure_count());
+        self.fire_hooks(
+            hooks::HookEvent::ProfileApplied,
+            &[
+                ("game_profile", self.settings.active_game_profile.clone()),
+                ("overrides", self.overrides.len().to_string()),
+                ("failures", failures.to_string()),
+            ],
+        );
+    }
+    
+    // User scripts run after the built-in steps and see what those wrote directly. Steps that need
+    // elevation are only queued by then and run in complete_apply, after the scripts.
+    fn run_apply_scripts(&self, report: &mut report::ApplyReport) {
+        let sys = self.system.lock().unwrap();
+        for script in self.settings.apply_scripts.iter().filter(|s| s.enabled && !s.path.trim().is_empty()) {
+            let context = self.script_context(&sys, false);
+            scripting::run_file(Path::new(script.path.trim()), context).record_into(&format!("Script {}", script.name()), report);
+        }
+    }
+    
+    // Scripts and their allowlists come from the base settings; the values they read are the effective ones.
+    fn script_context(&self, sys: &System, dry_run: bool) -> scripting::ScriptContext {
+        let mut context = scripting::ScriptContext::new(&self.settings, sys, dry_run);
+        context.settings = settings_diff::to_fields(&self.layers.settings);
+        context
+    }
+    
+    fn active_game_profile(&self) -> Option<&profiles::GameProfile> {
+        self.game_profiles.iter().find(|p| p.name == self.settings.active_game_profile)
+    }
+    
+    fn resolve_layers(&mut self) {
+        match profiles::resolve(&self.settings, self.active_game_profile(), &self.overrides) {
+            Ok(layers) => self.layers = layers,
+            Err(e) => debug!("Failed to resolve profile layers: {}", e),
         }
+    }
+    
+    // What the last Apply wrote, seen through the current game profile and overrides. Drift is
+    // measured against this rather than against edits that were never applied.
+    fn applied_settings(&self) -> VRSettings {
+        profiles::resolve(self.history.applied(), self.active_game_profile(), &self.overrides)
+            .map(|layers| layers.settings)
+            .unwrap_or_else(|_| self.layers.settings.clone())
+    }
+    
+    fn with_applied_settings(&mut self, apply: impl FnOnce(&mut Self)) {
+        let applied = self.applied_settings();
+        let base = std::mem::replace(&mut self.settings, applied);
+        apply(self);
+        self.settings = base;
+    }
+    
+    // The apply steps read `self.settings`, so they run against the resolved layers swapped in.
+    fn with_effective_settings(&mut self, apply: impl FnOnce(&mut Self)) {
+        self.resolve_layers();
+        let effective = self.layers.settings.clone();
+        let base = std::mem::replace(&mut self.settings, effective.clone());
+        apply(self);
+        let applied = std::mem::replace(&mut self.settings, base);
         
-        #[cfg(target_os = "windows")]
-        {
-            use winreg::enums::*;
-            use winreg::RegKey;
-            
-            if self.settings.relinked_mode {
-                self.settings.disable_telemetry = true;
-                self.settings.disable_login = true;
-                self.settings.oculus_killer_enabled = true;
-                self.toggle_oculus_killer(true);
-                
-                if self.settings.disable_telemetry {
-                    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-                    if let Ok(key) = hkcu.create_subkey("Software\\Oculus\\Telemetry") {
-                        let (key, _) = key;
-                        let _ = key.set_value("Enabled", &0u32);
-                    }
-                }
-                
-                info!("ReLinked mode enabled - manual runtime modifications may be needed");
-                info!("Setting custom FPS to {}", self.settings.custom_fps);
-                
-                self.settings.enable_runtime_high_priority = true;
-                self.apply_process_priorities();
-                
-                self.settings.allow_other_software = true;
-                self.apply_openxr_settings();
+        // Values a step forces (ReLinked mode) still stick to the base, as before layering.
+        let forced = settings_diff::changes(&settings_diff::to_fields(&effective), &settings_diff::to_fields(&applied));
+        if !forced.is_empty() {
+            let fields: settings_diff::Fields = forced.into_iter().map(|c| (c.field, c.to)).collect();
+            if let Ok(settings) = settings_diff::with_fields(&self.settings, &fields) {
+                self.settings = settings;
             }
         }
+        self.resolve_layers();
     }
     
-    fn launch_runtime(&self) {
-        #[cfg(target_os = "windows")]
-        {
-            let oculus_path = r"C:\Program Files\Oculus\Support\oculus-runtime\OVRServer_x64.exe";
-            let _ = Command::new(oculus_path).spawn();
-            info!("Launched Oculus Runtime");
+    // Runs any deferred privileged steps, then shows the report.
+    fn complete_apply(&mut self, mut report: report::ApplyReport) {
+        if !report.privileged.is_empty() {
+            let commands = std::mem::take(&mut report.privileged);
+            match elevation::run_elevated(&commands) {
+                Ok(helper_report) => report.entries.extend(helper_report.entries),
+                Err(e) => report.record("Privileged helper", format!("{} operation(s)", commands.len()), Err(e)),
+            }
+        }
+        info!("Apply finished with {} failed step(s)", report.failure_count());
+        let priority_status = priority_status_lines(&report);
+        if !priority_status.is_empty() {
+            self.priority_status = priority_status;
+        }
+        self.apply_report = Some(report);
+        self.refresh_system_state();
+    }
+    
+    // Steps that finish after Apply returned are added to the report already shown.
+    fn merge_apply_report(&mut self, report: report::ApplyReport) {
+        match &mut self.apply_report {
+            Some(existing) => existing.entries.extend(report.entries),
+            None => self.apply_report = Some(report),
         }
     }
     
-    fn restart_process(&self, process_name: &str) {
+    #[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
+    fn apply_oculus_link_settings(&self, report: &mut report::ApplyReport) {
         #[cfg(target_os = "windows")]
-        {
-            let _ = Command::new("taskkill")
-                .args(&["/F", "/IM", process_name])
-                .output();
-            
-            std::thread::sleep(std::time::Duration::from_millis(500));
-            
-            if process_name.contains("OVRServer") {
-                let oculus_path = r"C:\Program Files\Oculus\Support\oculus-runtime\OVRServer_x64.exe";
-                let _ = Command::new(oculus_path).spawn();
-            } else if process_name.contains("vrserver") {
-                let steam_path = r"C:\Program Files (x86)\Steam\steamapps\common\SteamVR\bin\win64\vrserver.exe";
-                let _ = Command::new(steam_path).spawn();
-            }
+        self.write_registry_values(|write| write.step == "Oculus Link", report);
+    }
+    
+    // Every per-user registry value Apply writes, tagged with the setting it comes from.
+    #[cfg(target_os = "windows")]
+    fn registry_writes(&self) -> Vec<RegistryWrite> {
+        let link = "Software\\Oculus\\RemoteHeadset";
+        let sharpening: u32 = if self.settings.link_sharpening > 0.0 { 1 } else { 0 };
+        let strength: u32 = (self.settings.link_sharpening * 100.0) as u32;
+        let asw: u32 = match self.settings.asw_mode {
+            ASWMode::Off => 0,
+            ASWMode::Auto => 1,
+            ASWMode::Force45FPS => 2,
+            ASWMode::Force30FPS => 3,
+        };
+        let mirror: u32 = if self.settings.mirror_window { 1 } else { 0 };
+        let guardian: u32 = if self.settings.guardian_visibility { 1 } else { 0 };
+        
+        vec![
+            RegistryWrite { step: "Oculus Link", field: "encode_bitrate_mbps", path: link, name: "BitrateMbps", value: self.settings.encode_bitrate_mbps },
+            RegistryWrite { step: "Oculus Link", field: "encode_resolution_width", path: link, name: "EncodeResolutionWidth", value: self.settings.encode_resolution_width },
+            RegistryWrite { step: "Oculus Link", field: "encode_resolution_height", path: link, name: "EncodeResolutionHeight", value: self.settings.encode_resolution_height },
+            RegistryWrite { step: "Oculus Link", field: "link_sharpening", path: link, name: "LinkSharpeningEnabled", value: sharpening },
+            RegistryWrite { step: "Oculus Link", field: "link_sharpening", path: link, name: "LinkSharpeningStrength", value: strength },
+            RegistryWrite { step: "ASW", field: "asw_mode", path: "Software\\Oculus\\Debug", name: "ASW", value: asw },
+            RegistryWrite { step: "Display", field: "mirror_window", path: link, name: "MirrorWindow", value: mirror },
+            RegistryWrite { step: "Display", field: "guardian_visibility", path: link, name: "GuardianVisibility", value: guardian },
+        ]
+    }
+    
+    #[cfg(target_os = "windows")]
+    fn write_registry_values(&self, include: impl Fn(&RegistryWrite) -> bool, report: &mut report::ApplyReport) {
+        for write in self.registry_writes().into_iter().filter(|write| include(write)) {
+            report.record(
+                write.step,
+                registry::display_path(registry::Hive::CurrentUser, write.path, write.name),
+                registry::write_value(registry::Hive::CurrentUser, write.path, write.name, &write.value),
+            );
         }
     }
     
-    fn kill_oculus_client(&self) {
+    #[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
+    fn apply_openxr_settings(&self, report: &mut report::ApplyReport) {
         #[cfg(target_os = "windows")]
         {
-            let _ = Command::new("taskkill")
-                .args(&["/F", "/IM", "OculusClient.exe"])
-                .output();
+            let runtime = if self.settings.use_openxr {
+                Some("oculus")
+            } else if self.settings.use_steamvr {
+                Some("steamvr")
+            } else {
+                None
+            };
+            if let Some(runtime) = runtime {
+                let path = "SOFTWARE\\Khronos\\OpenXR\\1";
+                report.record_or_escalate(
+                    "OpenXR runtime",
+                    registry::display_path(registry::Hive::LocalMachine, path, "ActiveRuntime"),
+                    registry::write_value(registry::Hive::LocalMachine, path, "ActiveRuntime", &runtime),
+                    elevation::HelperCommand::SetOpenXrRuntime { runtime: runtime.to_string() },
+                );
+            }
         }
     }
-}
-
-impl eframe::App for VRPerformanceApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.update_processes();
+    
+    fn apply_process_priorities(&self, report: &mut report::ApplyReport) {
+        if !self.settings.cpu_priority_boost {
+            return;
+        }
         
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-            ui.horizontal(|ui| {
-                ui.heading("VR Performance Suite");
-                ui.separator();
-                
-                if ui.selectable_label(self.current_tab == Tab::Performance, "Performance").clicked() {
-                    self.current_tab = Tab::Performance;
-                }
-                if ui.selectable_label(self.current_tab == Tab::Visual, "Visual").clicked() {
-                    self.current_tab = Tab::Visual;
+        for proc in &self.processes {
+            if let Some(pid) = proc.pid {
+                if is_runtime_process(&proc.name) {
+                    self.apply_process_priority(&proc.name, pid, report);
+                }
+            }
+        }
+    }
+    
+    fn apply_process_priority(&self, name: &str, pid: u32, report: &mut report::ApplyReport) {
+        let target = format!("{} ({})", name, pid);
+        let result = priority::set_process_priority(pid, &self.settings.cpu_priority, &self.settings.io_priority);
+        #[cfg(target_os = "linux")]
+        report.record_or_escalate("CPU priority", target.clone(), result, elevation::HelperCommand::SetProcessPriority {
+            pid,
+            cpu: self.settings.cpu_priority.clone(),
+            io: self.settings.io_priority.clone(),
+        });
+        #[cfg(not(target_os = "linux"))]
+        report.record("CPU priority", target.clone(), result);
+        
+        // The GPU radios are disabled where unsupported, so the stored value there is not the user's choice.
+        if priority::gpu_priority_supported() {
+            report.record("GPU priority", target, priority::set_gpu_priority(pid, &self.settings.gpu_priority));
+        }
+    }
+    
+    fn apply_cpu_affinity(&mut self, report: &mut report::ApplyReport) {
+        let running: Vec<(String, u32)> = self.processes.iter().filter_map(|p| p.pid.map(|pid| (p.name.clone(), pid))).collect();
+        for (name, pid) in running {
+            self.apply_process_affinity(&name, pid, report);
+        }
+    }
+    
+    // "All cores" only touches processes this app restricted earlier, to widen them again; OVRServer runs as
+    // SYSTEM and refuses an unelevated change, so it is otherwise left alone.
+    fn apply_process_affinity(&mut self, name: &str, pid: u32, report: &mut report::ApplyReport) {
+        let restrict = affinity::is_restricted(&self.settings.affinity_cores);
+        if !restrict && !self.restricted_pids.contains(&pid) {
+            return;
+        }
+        let result = affinity::set_process_affinity(pid, &affinity::selected_cores(&self.settings.affinity_cores, self.cpu_count));
+        if result.is_ok() {
+            if restrict {
+                self.restricted_pids.insert(pid);
+            } else {
+                self.restricted_pids.remove(&pid);
+            }
+        }
+        report.record("CPU affinity", format!("{} ({})", name, pid), result);
+    }
+    
+    #[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
+    fn apply_asw_settings(&self, report: &mut report::ApplyReport) {
+        #[cfg(target_os = "windows")]
+        self.write_registry_values(|write| write.step == "ASW", report);
+    }
+    
+    fn apply_additional_settings(&mut self, report: &mut report::ApplyReport) {
+        self.apply_power_plan(report);
+        
+        #[cfg(target_os = "windows")]
+        {
+            self.write_registry_values(|write| write.step == "Display", report);
+            
+            let result = fs::File::create("openxr_toolkit.ini")
+                .and_then(|mut file| write!(file, "upscaling_enabled = {}", self.settings.upscaling_enabled));
+            report.record("OpenXR Toolkit", "openxr_toolkit.ini", result);
+        }
+    }
+    
+    fn apply_power_plan(&mut self, report: &mut report::ApplyReport) {
+        #[cfg(target_os = "linux")]
+        report.record_or_escalate(
+            "Power plan",
+            "cpufreq / power-profiles-daemon",
+            self.power.apply(&self.settings.power_plan),
+            elevation::HelperCommand::SetPowerPlan { plan: self.settings.power_plan.clone() },
+        );
+        
+        #[cfg(target_os = "windows")]
+        {
+            let power_guid = self.settings.power_plan.scheme_guid();
+            report.record(
+                "Power plan",
+                format!("powercfg /s {}", power_guid),
+                report::check_output(Command::new("powercfg").args(["/s", power_guid]).output()),
+            );
+        }
+    }
+    
+    fn toggle_oculus_killer(&mut self, enable: bool, report: &mut report::ApplyReport) {
+        let dir = self.install_path(install::InstallPath::OculusDashDir);
+        if dir.as_os_str().is_empty() {
+            if enable {
+                report.record(
+                    "OculusKiller",
+                    "OculusDash directory",
+                    Err(std::io::Error::new(std::io::ErrorKind::NotFound, "OculusDash directory not found")),
+                );
+                self.oculus_killer_status = Some(String::from("OculusDash directory not found"));
+            }
+            return;
+        }
+        
+        let swap = file_swap::FileSwap::new(dir, "OculusDash.exe");
+        let replacement = self.settings.oculus_killer_replacement.trim();
+        let replacement = if replacement.is_empty() { None } else { Some(PathBuf::from(replacement)) };
+        
+        #[cfg(target_os = "windows")]
+        if !self.elevated && report.escalate {
+            // The helper checks the file again; the last status only spares a prompt when nothing would change.
+            let done = match self.dash_swap_status {
+                Some(file_swap::SwapStatus::Swapped) => enable && self.dash_replacement_current,
+                Some(file_swap::SwapStatus::Original) => !enable,
+                _ => false,
+            };
+            report.privileged.retain(|command| !matches!(command, elevation::HelperCommand::OculusKiller { .. }));
+            if !done {
+                report.privileged.push(elevation::HelperCommand::OculusKiller {
+                    enable,
+                    dash_dir: swap.target().parent().map(Path::to_path_buf).unwrap_or_default(),
+                    replacement,
+                });
+            }
+            self.oculus_killer_status = None;
+            return;
+        }
+        
+        self.start_oculus_killer(swap, enable, replacement);
+    }
+    
+    // Stopping the Oculus service can take a while, so the swap finishes in the background.
+    // A request made while one runs waits for it, and only the latest one is kept.
+    fn start_oculus_killer(&mut self, swap: file_swap::FileSwap, enable: bool, replacement: Option<PathBuf>) {
+        if self.oculus_killer_job.is_some() {
+            self.oculus_killer_queued = Some((swap, enable, replacement));
+            return;
+        }
+        self.oculus_killer_status = Some(format!("{} OculusDash.exe...", if enable { "Replacing" } else { "Restoring" }));
+        self.oculus_killer_job = Some(self.services.run(move |services| {
+            let mut report = report::ApplyReport::new();
+            let status = run_oculus_killer(&swap, enable, replacement.as_deref(), services, &mut report);
+            (status, report)
+        }));
+    }
+    
+    #[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
+    fn apply_relinked_settings(&mut self, report: &mut report::ApplyReport) {
+        if self.settings.debug_logging {
+            debug!("Applying ReLinked settings");
+        }
+        
+        #[cfg(target_os = "windows")]
+        {
+            if self.settings.relinked_mode {
+                self.settings.disable_telemetry = true;
+                self.settings.disable_login = true;
+                self.settings.oculus_killer_enabled = true;
+                
+                if self.settings.disable_telemetry {
+                    let path = "Software\\Oculus\\Telemetry";
+                    report.record(
+                        "Telemetry",
+                        registry::display_path(registry::Hive::CurrentUser, path, "Enabled"),
+                        registry::write_value(registry::Hive::CurrentUser, path, "Enabled", &0u32),
+                    );
+                }
+                
+                info!("ReLinked mode enabled - manual runtime modifications may be needed");
+                info!("Setting custom FPS to {}", self.settings.custom_fps);
+                
+                self.settings.enable_runtime_high_priority = true;
+                self.apply_process_priorities(report);
+                
+                self.settings.allow_other_software = true;
+                self.apply_openxr_settings(report);
+            }
+        }
+    }
+    
+    fn launch_runtime(&mut self) {
+        #[cfg(target_os = "windows")]
+        let (runtime, start_steamvr) = if self.settings.use_openxr {
+            let runtime = launch::Runtime {
+                name: "Oculus Runtime",
+                process: "OVRServer_x64",
+                launcher: launch::RuntimeLauncher::Executable(self.install_path(install::InstallPath::OculusRuntime)),
+            };
+            (runtime, self.settings.enable_steamvr_autostart)
+        } else {
+            (launch::steamvr_runtime(), false)
+        };
+        #[cfg(not(target_os = "windows"))]
+        let (runtime, start_steamvr) = match self.settings.linux_runtime {
+            LinuxRuntime::SteamVR => (launch::steamvr_runtime(), false),
+            LinuxRuntime::Monado => {
+                let runtime = launch::Runtime {
+                    name: "Monado",
+                    process: "monado-service",
+                    launcher: launch::RuntimeLauncher::Executable(self.install_path(install::InstallPath::MonadoService)),
+                };
+                (runtime, self.settings.enable_steamvr_autostart)
+            }
+            LinuxRuntime::WiVRn => {
+                let runtime = launch::Runtime {
+                    name: "WiVRn",
+                    process: "wivrn-server",
+                    launcher: launch::RuntimeLauncher::Executable(self.install_path(install::InstallPath::WivrnServer)),
+                };
+                (runtime, self.settings.enable_steamvr_autostart)
+            }
+        };
+        
+        let plan = launch::LaunchPlan {
+            start_steamvr,
+            runtime,
+            programs: self.settings.startup_programs.iter()
+                .filter(|p| !p.path.trim().is_empty())
+                .cloned()
+                .collect(),
+            timeout: std::time::Duration::from_secs(self.settings.runtime_start_timeout_seconds as u64),
+        };
+        
+        info!("Starting launch sequence");
+        self.launch_progress = Some(launch::start(plan));
+    }
+    
+    fn headset(&self) -> &'static headsets::HeadsetModel {
+        headsets::find_or_default(&self.settings.headset_model)
+    }
+    
+    fn select_headset(&mut self, id: &str) {
+        let model = headsets::find_or_default(id);
+        info!("Headset model set to {}", model.name);
+        self.settings.headset_model = model.id.to_string();
+        self.settings.fit_to_headset();
+    }
+    
+    fn install_path(&self, path: install::InstallPath) -> PathBuf {
+        self.settings.install_paths.resolve(&self.settings.install_path_overrides, path)
+    }
+    
+    // Hashing OculusDash.exe is too slow for the UI thread, so the check runs on the services worker.
+    fn refresh_dash_swap_status(&mut self) {
+        if self.dash_swap_job.is_some() || self.dash_swap_checked.is_some_and(|t| t.elapsed() < DASH_SWAP_REFRESH_INTERVAL) {
+            return;
+        }
+        self.dash_swap_checked = Some(Instant::now());
+        let dir = self.install_path(install::InstallPath::OculusDashDir);
+        if dir.as_os_str().is_empty() {
+            self.dash_swap_status = None;
+            return;
+        }
+        let replacement = self.settings.oculus_killer_replacement.trim();
+        let replacement = if replacement.is_empty() { None } else { Some(PathBuf::from(replacement)) };
+        self.dash_swap_job = Some(self.services.run(move |_| {
+            let swap = file_swap::FileSwap::new(dir, "OculusDash.exe");
+            let status = swap.status().ok()?;
+            let current = status == file_swap::SwapStatus::Swapped
+                && swap.installed_matches(replacement.as_deref()).unwrap_or(false);
+            Some((status, current))
+        }));
+    }
+    
+    fn update_services(&mut self) {
+        if let Some(states) = self.services.poll_states() {
+            self.service_states = states;
+        }
+        if let Some(job) = &self.service_job {
+            match job.try_recv() {
+                Ok(status) => {
+                    self.service_status = status;
+                    self.service_job = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.service_job = None,
+            }
+        }
+        let mut finished = Vec::new();
+        self.restart_jobs.retain(|job| match job.try_recv() {
+            Ok(result) => {
+                finished.push(result);
+                false
+            }
+            Err(mpsc::TryRecvError::Empty) => true,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        });
+        for (name, result) in finished {
+            match &result {
+                Ok(()) => {
+                    info!("Restarted {}", name);
+                    self.fire_hooks(hooks::HookEvent::ProcessRestarted, &[("process", name.clone())]);
+                }
+                Err(e) => warn!("Failed to restart {}: {}", name, e),
+            }
+            let mut report = report::ApplyReport::new();
+            report.record("Restart", name, result);
+            if report.failure_count() > 0 {
+                self.merge_apply_report(report);
+            }
+        }
+        if let Some(job) = &self.oculus_killer_job {
+            match job.try_recv() {
+                Ok((status, report)) => {
+                    self.oculus_killer_status = status;
+                    self.oculus_killer_job = None;
+                    self.merge_apply_report(report);
+                    self.dash_swap_checked = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.oculus_killer_job = None,
+            }
+            if self.oculus_killer_job.is_none() {
+                if let Some((swap, enable, replacement)) = self.oculus_killer_queued.take() {
+                    self.start_oculus_killer(swap, enable, replacement);
+                }
+            }
+        }
+        if let Some(job) = &self.dash_swap_job {
+            match job.try_recv() {
+                Ok(checked) => {
+                    self.dash_swap_status = checked.map(|(status, _)| status);
+                    self.dash_replacement_current = checked.is_some_and(|(_, current)| current);
+                    self.dash_swap_job = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.dash_swap_job = None,
+            }
+        }
+    }
+    
+    fn refresh_system_state(&mut self) {
+        self.dash_swap_checked = None;
+        let snapshot = system_state::SystemSnapshot::read();
+        let drift = snapshot.drift(&self.applied_settings());
+        if !drift.is_empty() {
+            info!("{} setting(s) differ from the system", drift.len());
+        }
+        for item in &drift {
+            debug!(
+                "Drift in {}: saved {}, system {} ({})",
+                item.field,
+                settings_diff::display_value(&item.saved),
+                settings_diff::display_value(&item.live),
+                item.source
+            );
+        }
+        self.system_state = Some(snapshot);
+    }
+    
+    // Applies only the given settings fields, as found by drift detection.
+    fn reapply_fields(&mut self, fields: &[&str], report: &mut report::ApplyReport) {
+        #[cfg(target_os = "windows")]
+        self.write_registry_values(|write| fields.contains(&write.field), report);
+        if fields.contains(&"power_plan") {
+            self.apply_power_plan(report);
+        }
+        if fields.contains(&"use_openxr") || fields.contains(&"use_steamvr") {
+            self.apply_openxr_settings(report);
+        }
+    }
+    
+    fn reapply_drift(&mut self) {
+        let fields: Vec<&'static str> = self.drift.iter().map(|item| item.field).collect();
+        info!("Re-applying {} drifted setting(s)", fields.len());
+        let mut report = report::ApplyReport::new();
+        report.escalate = !self.elevated;
+        self.with_applied_settings(|app| app.reapply_fields(&fields, &mut report));
+        self.complete_apply(report);
+    }
+    
+    fn update_drift_monitor(&mut self) {
+        if !self.settings.drift_monitor_enabled {
+            if self.drift_monitor.take().is_some() {
+                info!("Drift monitor stopped");
+            }
+            return;
+        }
+        let interval = Duration::from_secs(self.settings.drift_monitor_interval_seconds.max(5) as u64);
+        if self.drift_monitor.as_ref().map(|monitor| monitor.interval) != Some(interval) {
+            info!("Drift monitor checking every {} s", interval.as_secs());
+            self.drift_monitor = Some(system_state::DriftMonitor::start(interval));
+        }
+        
+        let snapshot = match self.drift_monitor.as_ref().and_then(|monitor| monitor.poll()) {
+            Some(snapshot) => snapshot,
+            None => return,
+        };
+        let applied = self.applied_settings();
+        let previous = self.system_state.as_ref().map(|s| s.drift(&applied)).unwrap_or_default();
+        let drift = snapshot.drift(&applied);
+        self.system_state = Some(snapshot);
+        
+        // Only act on values that changed since the last check, so a value that cannot be re-applied is reported once.
+        let new: Vec<&system_state::Drift> = drift
+            .iter()
+            .filter(|item| !previous.iter().any(|p| p.field == item.field && p.live == item.live))
+            .collect();
+        if new.is_empty() {
+            return;
+        }
+        for item in &new {
+            warn!(
+                "Drift detected in {}: expected {}, system has {} ({})",
+                item.field,
+                settings_diff::display_value(&item.saved),
+                settings_diff::display_value(&item.live),
+                item.source
+            );
+        }
+        let labels: Vec<&str> = new.iter().map(|item| item.label).collect();
+        
+        if !self.settings.drift_auto_reapply {
+            self.drift_notice = Some(format!("Drift detected: {}", labels.join(", ")));
+            return;
+        }
+        let fields: Vec<&'static str> = new.iter().map(|item| item.field).collect();
+        let mut report = report::ApplyReport::new();
+        self.with_applied_settings(|app| app.reapply_fields(&fields, &mut report));
+        let failures = report.failure_count();
+        info!("Re-applied {} drifted setting(s), {} failed", fields.len(), failures);
+        self.drift_notice = Some(if failures == 0 {
+            format!("Re-applied drifted: {}", labels.join(", "))
+        } else {
+            format!("Re-applying {} failed for {} step(s), see log", labels.join(", "), failures)
+        });
+        self.refresh_system_state();
+    }
+    
+    // Copies live system values into the settings, either all of them or a single field.
+    fn import_from_system(&mut self, field: Option<&str>) {
+        let snapshot = match &self.system_state {
+            Some(snapshot) => snapshot,
+            None => return,
+        };
+        let mut fields = snapshot.fields();
+        if let Some(field) = field {
+            fields.retain(|key, _| key == field);
+        }
+        match settings_diff::with_fields(&self.settings, &fields) {
+            Ok(settings) => {
+                self.settings = settings;
+                info!("Imported {} value(s) from the system", fields.len());
+                if let Err(e) = self.save_settings() {
+                    warn!("Failed to save settings: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to import system values: {}", e),
+        }
+    }
+    
+    fn export_profile(&mut self) {
+        let path = PathBuf::from(&self.profile_path);
+        self.profile_status = Some(match profile_io::export_file(&self.settings, &path) {
+            Ok(()) => {
+                info!("Exported profile to {}", path.display());
+                format!("Exported to {}", path.display())
+            }
+            Err(e) => {
+                warn!("Failed to export profile to {}: {}", path.display(), e);
+                format!("Export failed: {}", e)
+            }
+        });
+    }
+    
+    fn preview_import(&mut self, fields: std::io::Result<settings_diff::Fields>, source: String) {
+        match fields {
+            Ok(fields) => {
+                let preview = profile_io::prepare_import(&self.settings, fields, source);
+                self.profile_status = None;
+                self.import_preview = Some(preview);
+            }
+            Err(e) => {
+                warn!("Failed to read profile from {}: {}", source, e);
+                self.profile_status = Some(format!("Could not read {}: {}", source, e));
+                self.import_preview = None;
+            }
+        }
+    }
+    
+    // Only touches the editor; the imported values take effect on the next Apply All like any other edit.
+    fn accept_import(&mut self) {
+        let preview = match self.import_preview.take() {
+            Some(preview) => preview,
+            None => return,
+        };
+        match settings_diff::with_fields(&self.settings, &preview.accepted) {
+            Ok(settings) => {
+                info!("Imported {} change(s) from {}", preview.changes.len(), preview.source);
+                self.settings = settings;
+                self.profile_status = Some(format!("Imported {} change(s) from {}; Apply All to use them", preview.changes.len(), preview.source));
+            }
+            Err(e) => {
+                warn!("Failed to import profile: {}", e);
+                self.profile_status = Some(format!("Import failed: {}", e));
+            }
+        }
+    }
+    
+    fn select_game_profile(&mut self, name: &str) {
+        if name.is_empty() {
+            info!("Game profile cleared");
+        } else {
+            info!("Game profile {} selected", name);
+        }
+        self.settings.active_game_profile = name.to_string();
+        self.layer_edit = None;
+        self.resolve_layers();
+    }
+    
+    fn create_game_profile(&mut self) {
+        let name = self.new_profile_name.trim().to_string();
+        if !profiles::is_valid_name(&name) {
+            self.profile_status = Some(String::from("Profile names may only contain letters, digits, spaces, - and _"));
+            return;
+        }
+        if self.game_profiles.iter().any(|p| p.name.eq_ignore_ascii_case(&name)) {
+            self.profile_status = Some(format!("A game profile named {} already exists", name));
+            return;
+        }
+        let profile = profiles::GameProfile::new(&name);
+        if let Err(e) = self.check_writable().and_then(|_| profile.save()) {
+            warn!("Failed to create game profile {}: {}", name, e);
+            self.profile_status = Some(format!("Could not create {}: {}", name, e));
+            return;
+        }
+        info!("Created game profile {}", name);
+        self.game_profiles.push(profile);
+        self.game_profiles.sort_by_key(|profile| profile.name.to_lowercase());
+        self.new_profile_name.clear();
+        self.select_game_profile(&name);
+    }
+    
+    fn delete_game_profile(&mut self) {
+        let index = match self.game_profiles.iter().position(|p| p.name == self.settings.active_game_profile) {
+            Some(index) => index,
+            None => return,
+        };
+        if let Err(e) = self.check_writable() {
+            self.profile_status = Some(format!("Could not delete {}: {}", self.settings.active_game_profile, e));
+            return;
+        }
+        let profile = self.game_profiles.remove(index);
+        match profile.delete() {
+            Ok(()) => info!("Deleted game profile {}", profile.name),
+            Err(e) => warn!("Failed to delete game profile {}: {}", profile.name, e),
+        }
+        self.select_game_profile("");
+    }
+    
+    // Sets a field on the game or override layer, or clears it there when `value` is None.
+    fn set_layer_value(&mut self, layer: profiles::Layer, field: &str, value: Option<serde_json::Value>) -> Result<(), String> {
+        if layer == profiles::Layer::Game {
+            self.check_writable().map_err(|e| e.to_string())?;
+        }
+        if let Some(value) = &value {
+            profiles::check_field(field, value)?;
+            let single: settings_diff::Fields = std::iter::once((field.to_string(), value.clone())).collect();
+            let preview = profile_io::prepare_import(&self.layers.settings, single, layer.label());
+            if let Some(issue) = preview.issues.first() {
+                return Err(issue.message.clone());
+            }
+        }
+        
+        let overlay = match layer {
+            profiles::Layer::Override => &mut self.overrides,
+            profiles::Layer::Game => {
+                let name = self.settings.active_game_profile.clone();
+                match self.game_profiles.iter_mut().find(|p| p.name == name) {
+                    Some(profile) => &mut profile.fields,
+                    None => return Err(String::from("no game profile selected")),
+                }
+            }
+            _ => return Err(format!("the {} layer is edited in the other tabs", layer.label())),
+        };
+        match value {
+            Some(value) => {
+                info!("Set {} to {} in the {} layer", field, settings_diff::display_value(&value), layer.label());
+                overlay.insert(field.to_string(), value);
+            }
+            None => {
+                info!("Cleared {} from the {} layer", field, layer.label());
+                overlay.remove(field);
+            }
+        }
+        
+        if layer == profiles::Layer::Game {
+            if let Some(profile) = self.active_game_profile() {
+                profile.save().map_err(|e| format!("could not save {}: {}", profile.path().display(), e))?;
+            }
+        }
+        self.resolve_layers();
+        Ok(())
+    }
+    
+    fn control_service(&mut self, service: &'static services::Service, start: bool) {
+        self.service_job = Some(self.services.run(move |services| {
+            let result = if start {
+                services.start(service)
+            } else {
+                services.stop(service)
+            };
+            match result {
+                Ok(()) => {
+                    info!("Requested {} of {}", if start { "start" } else { "stop" }, service.name);
+                    None
+                }
+                Err(e) => {
+                    warn!("Failed to control {}: {}", service.name, e);
+                    Some(format!("{}: {}", service.label, e))
+                }
+            }
+        }));
+    }
+    
+    fn restart_service(&mut self, service: &'static services::Service) {
+        self.service_job = Some(self.services.run(move |services| {
+            let result = services.stop_and_wait(service, SERVICE_TIMEOUT)
+                .and_then(|_| services.start_and_wait(service, SERVICE_TIMEOUT));
+            match result {
+                Ok(()) => None,
+                Err(e) => {
+                    warn!("Failed to restart {}: {}", service.name, e);
+                    Some(format!("{}: {}", service.label, e))
+                }
+            }
+        }));
+    }
+    
+    // Kills and respawns on the services worker, so the wait in between does not stall the UI.
+    fn restart_process(&mut self, process_name: &str) {
+        let name = process_name.to_string();
+        let launcher = self.restart_launcher(process_name);
+        let system = self.system.clone();
+        self.restart_jobs.push(self.services.run(move |_| {
+            let result = restart_process(&system, &name, launcher);
+            (name, result)
+        }));
+    }
+    
+    // How a process comes back after a restart. The rest come back with their runtime, so they are not restarted alone.
+    fn restart_launcher(&self, process_name: &str) -> Option<launch::RuntimeLauncher> {
+        #[cfg(target_os = "windows")]
+        let path = match process_name {
+            "OVRServer_x64.exe" => install::InstallPath::OculusRuntime,
+            "vrserver.exe" => install::InstallPath::SteamVrServer,
+            _ => return None,
+        };
+        #[cfg(not(target_os = "windows"))]
+        let path = match process_name {
+            "monado-service" => install::InstallPath::MonadoService,
+            "wivrn-server" => install::InstallPath::WivrnServer,
+            "vrserver" => return Some(launch::steamvr_runtime().launcher),
+            _ => return None,
+        };
+        Some(launch::RuntimeLauncher::Executable(self.install_path(path)))
+    }
+    
+    fn handle_history_shortcuts(&mut self, ctx: &egui::Context) {
+        // Text fields keep their own undo while they have focus.
+        if ctx.memory(|memory| memory.focused().is_some()) {
+            return;
+        }
+        let redo = ctx.input_mut(|input| {
+            input.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z)
+                || input.consume_key(egui::Modifiers::COMMAND, egui::Key::Y)
+        });
+        if redo {
+            self.history.redo(&mut self.settings);
+        } else if ctx.input_mut(|input| input.consume_key(egui::Modifiers::COMMAND, egui::Key::Z)) {
+            self.history.undo(&mut self.settings);
+        }
+    }
+    
+    fn revert_to_applied(&mut self) {
+        info!("Reverting {} unapplied change(s)", self.history.unapplied().len());
+        self.settings = self.history.applied().clone();
+    }
+    
+    fn kill_oculus_client(&self) {
+        #[cfg(target_os = "windows")]
+        {
+            let _ = Command::new("taskkill")
+                .args(&["/F", "/IM", "OculusClient.exe"])
+                .output();
+        }
+    }
+}
+
+impl eframe::App for VRPerformanceApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.update_processes();
+        self.update_headsets();
+        self.update_support_bundle();
+        self.update_services();
+        self.update_settings_watcher();
+        self.resolve_layers();
+        self.update_hotkeys(ctx);
+        self.update_drift_monitor();
+        self.handle_history_shortcuts(ctx);
+        self.drift = match &self.system_state {
+            Some(snapshot) => snapshot.drift(&self.applied_settings()),
+            None => Vec::new(),
+        };
+        
+        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("VR Performance Suite");
+                if self.elevated {
+                    ui.label(format!("({})", elevation::privilege_name()));
+                }
+                if self.instance_lock.is_none() {
+                    ui.colored_label(egui::Color32::RED, "Read-only")
+                        .on_hover_text("Another instance holds the settings lock, so changes here are not saved");
+                }
+                ui.separator();
+                
+                if ui.selectable_label(self.current_tab == Tab::Performance, "Performance").clicked() {
+                    self.current_tab = Tab::Performance;
+                }
+                if ui.selectable_label(self.current_tab == Tab::Visual, "Visual").clicked() {
+                    self.current_tab = Tab::Visual;
                 }
                 if ui.selectable_label(self.current_tab == Tab::Processes, "Processes").clicked() {
                     self.current_tab = Tab::Processes;
@@ -539,15 +2016,53 @@ impl eframe::App for VRPerformanceApp {
                 if ui.selectable_label(self.current_tab == Tab::ReLinked, "ReLinked").clicked() {
                     self.current_tab = Tab::ReLinked;
                 }
+                if ui.selectable_label(self.current_tab == Tab::Profiles, "Profiles").clicked() {
+                    self.current_tab = Tab::Profiles;
+                }
                 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("Apply All").clicked() {
                         self.apply_settings();
                     }
+                    let unapplied = self.history.unapplied();
+                    if !unapplied.is_empty() {
+                        ui.colored_label(egui::Color32::LIGHT_BLUE, format!("{} unapplied", unapplied.len()))
+                            .on_hover_text(unapplied.join("\n"));
+                        if ui.button("Revert to Applied").clicked() {
+                            self.revert_to_applied();
+                        }
+                    }
+                    if ui.add_enabled(self.history.can_redo(), egui::Button::new("Redo")).clicked() {
+                        self.history.redo(&mut self.settings);
+                    }
+                    if ui.add_enabled(self.history.can_undo(), egui::Button::new("Undo")).clicked() {
+                        self.history.undo(&mut self.settings);
+                    }
+                    if !self.drift.is_empty() {
+                        let text = egui::RichText::new(format!("{} differ from system", self.drift.len())).color(egui::Color32::YELLOW);
+                        if ui.button(text).clicked() {
+                            self.current_tab = Tab::Advanced;
+                        }
+                    }
+                    if let Some(notice) = &self.drift_notice {
+                        ui.colored_label(egui::Color32::YELLOW, notice);
+                    }
+                    if let Some(notice) = &self.reload_notice {
+                        ui.colored_label(egui::Color32::LIGHT_BLUE, notice);
+                    }
+                    if !self.overrides.is_empty() {
+                        ui.colored_label(egui::Color32::YELLOW, format!("{} override(s)", self.overrides.len()));
+                    }
+                    if !self.settings.active_game_profile.is_empty() {
+                        ui.colored_label(egui::Color32::LIGHT_BLUE, format!("Game: {}", self.settings.active_game_profile));
+                    }
                 });
             });
         });
         
+        self.show_apply_report(ctx);
+        self.show_reload_conflict(ctx);
+        
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 match self.current_tab {
@@ -557,33 +2072,175 @@ impl eframe::App for VRPerformanceApp {
                     Tab::Advanced => self.show_advanced_tab(ui),
                     Tab::Stats => self.show_stats_tab(ui),
                     Tab::ReLinked => self.show_relinked_tab(ui),
+                    Tab::Profiles => self.show_profiles_tab(ui),
                 }
             });
         });
         
+        self.history.observe(&self.settings);
+        
         ctx.request_repaint_after(std::time::Duration::from_millis(500));
     }
 }
 
 impl VRPerformanceApp {
+    fn show_reload_conflict(&mut self, ctx: &egui::Context) {
+        let conflict = match &self.reload_conflict {
+            Some(conflict) => conflict,
+            None => return,
+        };
+        
+        let mut decision = None;
+        egui::Window::new("Settings Changed on Disk").collapsible(false).default_width(500.0).show(ctx, |ui| {
+            ui.label(format!(
+                "{} was changed outside the app, and these settings also have unsaved edits here:",
+                config::settings_path().display()
+            ));
+            let current = settings_diff::to_fields(&self.settings);
+            let external = settings_diff::to_fields(&conflict.external);
+            egui::Grid::new("reload_conflict_grid").striped(true).show(ui, |ui| {
+                ui.label("Setting");
+                ui.label("In app");
+                ui.label("On disk");
+                ui.end_row();
+                
+                for field in &conflict.fields {
+                    ui.label(field);
+                    ui.label(current.get(field).map(settings_diff::display_value).unwrap_or_default());
+                    ui.colored_label(egui::Color32::YELLOW, external.get(field).map(settings_diff::display_value).unwrap_or_default());
+                    ui.end_row();
+                }
+            });
+            ui.label("Other changes from the file are merged either way.");
+            ui.horizontal(|ui| {
+                if ui.button("Use File Version").clicked() {
+                    decision = Some(true);
+                }
+                if ui.button("Keep My Edits").clicked() {
+                    decision = Some(false);
+                }
+            });
+        });
+        if let Some(use_file) = decision {
+            self.resolve_reload_conflict(use_file);
+        }
+    }
+    
+    fn show_apply_report(&mut self, ctx: &egui::Context) {
+        let report = match &self.apply_report {
+            Some(report) => report,
+            None => return,
+        };
+        
+        let mut open = true;
+        egui::Window::new("Apply Report")
+            .open(&mut open)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                let failures = report.failure_count();
+                if failures == 0 {
+                    ui.colored_label(egui::Color32::GREEN, format!("All {} steps applied", report.entries.len()));
+                } else {
+                    ui.colored_label(egui::Color32::RED, format!("{} of {} steps failed", failures, report.entries.len()));
+                }
+                ui.separator();
+                
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("apply_report_grid").striped(true).show(ui, |ui| {
+                        ui.label("Step");
+                        ui.label("Target");
+                        ui.label("Result");
+                        ui.label("Suggestion");
+                        ui.end_row();
+                        
+                        for entry in &report.entries {
+                            ui.label(&entry.step);
+                            ui.label(&entry.target);
+                            match &entry.result {
+                                Ok(()) => {
+                                    ui.colored_label(egui::Color32::GREEN, "OK");
+                                    ui.label("");
+                                }
+                                Err(e) => {
+                                    let message = match e.os_error {
+                                        Some(code) if !e.message.contains("os error") => format!("{} (os error {})", e.message, code),
+                                        _ => e.message.clone(),
+                                    };
+                                    ui.colored_label(egui::Color32::RED, message);
+                                    ui.label(e.suggestion().unwrap_or(""));
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        if !open {
+            self.apply_report = None;
+        }
+    }
+    
+    fn elevation_hint(&self, ui: &mut egui::Ui) {
+        if !self.elevated {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("Requires {} - you will be prompted on apply", elevation::privilege_name()),
+            );
+        }
+    }
+    
+    fn drift_marker(&self, ui: &mut egui::Ui, fields: &[&str]) {
+        for item in self.drift.iter().filter(|item| fields.contains(&item.field)) {
+            let live = settings_diff::display_value(&item.live);
+            let text = if fields.len() == 1 {
+                format!("system: {}", live)
+            } else {
+                format!("system {}: {}", item.label, live)
+            };
+            ui.colored_label(egui::Color32::YELLOW, text).on_hover_text(&item.source);
+        }
+    }
+    
     fn show_performance_tab(&mut self, ui: &mut egui::Ui) {
+        let unapplied = Unapplied::new(&self.history);
         ui.heading("Performance Settings");
         ui.separator();
         
+        self.show_recommendations(ui);
+        
+        ui.add_space(10.0);
+        
         ui.group(|ui| {
             ui.label("Runtime Selection");
-            ui.radio_value(&mut self.settings.use_openxr, true, "Use Oculus OpenXR (Recommended for Quest Link)");
-            ui.radio_value(&mut self.settings.use_steamvr, true, "Use SteamVR OpenXR");
+            #[cfg(target_os = "windows")]
+            {
+                unapplied.mark(ui.vertical(|ui| {
+                    ui.radio_value(&mut self.settings.use_openxr, true, "Use Oculus OpenXR (Recommended for Quest Link)");
+                    ui.radio_value(&mut self.settings.use_steamvr, true, "Use SteamVR OpenXR");
+                }).response, &["use_openxr", "use_steamvr"]);
+                self.drift_marker(ui, &["use_openxr", "use_steamvr"]);
+                self.elevation_hint(ui);
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                unapplied.mark(ui.vertical(|ui| {
+                    ui.radio_value(&mut self.settings.linux_runtime, LinuxRuntime::SteamVR, "SteamVR");
+                    ui.radio_value(&mut self.settings.linux_runtime, LinuxRuntime::Monado, "Monado");
+                    ui.radio_value(&mut self.settings.linux_runtime, LinuxRuntime::WiVRn, "WiVRn (Quest streaming)");
+                }).response, &["linux_runtime"]);
+            }
         });
         
         ui.add_space(10.0);
         
         ui.group(|ui| {
             ui.label("Render Scale");
-            ui.add(egui::Slider::new(&mut self.settings.render_scale, 0.5..=2.0).text("Scale"));
-            ui.label(format!("Resolution: {}x{}", 
-                (2064.0 * self.settings.render_scale) as u32,
-                (2208.0 * self.settings.render_scale) as u32
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.render_scale, 0.5..=2.0).text("Scale")), &["render_scale"]);
+            let headset = self.headset();
+            ui.label(format!("Resolution: {}x{} per eye ({})", 
+                (headset.panel_width as f32 * self.settings.render_scale) as u32,
+                (headset.panel_height as f32 * self.settings.render_scale) as u32,
+                headset.name
             ));
         });
         
@@ -591,16 +2248,17 @@ impl VRPerformanceApp {
         
         ui.group(|ui| {
             ui.label("ASW (Asynchronous Spacewarp)");
-            ui.checkbox(&mut self.settings.asw_enabled, "Enable ASW");
+            unapplied.mark(ui.checkbox(&mut self.settings.asw_enabled, "Enable ASW"), &["asw_enabled"]);
             
             if self.settings.asw_enabled {
-                ui.horizontal(|ui| {
+                unapplied.mark(ui.horizontal(|ui| {
                     ui.label("Mode:");
                     ui.radio_value(&mut self.settings.asw_mode, ASWMode::Auto, "Auto");
                     ui.radio_value(&mut self.settings.asw_mode, ASWMode::Force45FPS, "45 FPS");
                     ui.radio_value(&mut self.settings.asw_mode, ASWMode::Force30FPS, "30 FPS");
                     ui.radio_value(&mut self.settings.asw_mode, ASWMode::Off, "Off");
-                });
+                    self.drift_marker(ui, &["asw_mode"]);
+                }).response, &["asw_mode"]);
             }
         });
         
@@ -608,16 +2266,16 @@ impl VRPerformanceApp {
         
         ui.group(|ui| {
             ui.label("Foveated Rendering");
-            ui.checkbox(&mut self.settings.foveated_rendering, "Enable Foveated Rendering");
+            unapplied.mark(ui.checkbox(&mut self.settings.foveated_rendering, "Enable Foveated Rendering"), &["foveated_rendering"]);
             
             if self.settings.foveated_rendering {
-                ui.horizontal(|ui| {
+                unapplied.mark(ui.horizontal(|ui| {
                     ui.label("Level:");
                     ui.radio_value(&mut self.settings.foveated_level, FoveatedLevel::Low, "Low");
                     ui.radio_value(&mut self.settings.foveated_level, FoveatedLevel::Medium, "Medium");
                     ui.radio_value(&mut self.settings.foveated_level, FoveatedLevel::High, "High");
                     ui.radio_value(&mut self.settings.foveated_level, FoveatedLevel::HighTop, "High+Top");
-                });
+                }).response, &["foveated_level"]);
             }
         });
         
@@ -625,15 +2283,42 @@ impl VRPerformanceApp {
         
         ui.group(|ui| {
             ui.label("Process Priority");
-            ui.checkbox(&mut self.settings.cpu_priority_boost, "Boost VR Process Priority");
+            unapplied.mark(ui.checkbox(&mut self.settings.cpu_priority_boost, "Boost VR Process Priority"), &["cpu_priority_boost"]);
             
             if self.settings.cpu_priority_boost {
+                unapplied.mark(ui.horizontal(|ui| {
+                    ui.label("CPU Priority:");
+                    ui.radio_value(&mut self.settings.cpu_priority, CpuPriority::Normal, "Normal");
+                    ui.radio_value(&mut self.settings.cpu_priority, CpuPriority::High, "High");
+                    ui.radio_value(&mut self.settings.cpu_priority, CpuPriority::Realtime, "Realtime");
+                    #[cfg(target_os = "linux")]
+                    self.elevation_hint(ui);
+                }).response, &["cpu_priority"]);
+                
                 ui.horizontal(|ui| {
                     ui.label("GPU Priority:");
-                    ui.radio_value(&mut self.settings.gpu_priority, GPUPriority::Normal, "Normal");
-                    ui.radio_value(&mut self.settings.gpu_priority, GPUPriority::High, "High");
-                    ui.radio_value(&mut self.settings.gpu_priority, GPUPriority::Realtime, "Realtime");
+                    unapplied.mark(ui.add_enabled_ui(priority::gpu_priority_supported(), |ui| {
+                        ui.radio_value(&mut self.settings.gpu_priority, GPUPriority::Normal, "Normal");
+                        ui.radio_value(&mut self.settings.gpu_priority, GPUPriority::High, "High");
+                        ui.radio_value(&mut self.settings.gpu_priority, GPUPriority::Realtime, "Realtime");
+                    }).response, &["gpu_priority"]);
+                    if !priority::gpu_priority_supported() {
+                        ui.label("(not supported on this platform)");
+                    }
                 });
+                
+                #[cfg(target_os = "linux")]
+                unapplied.mark(ui.horizontal(|ui| {
+                    ui.label("I/O Priority:");
+                    ui.radio_value(&mut self.settings.io_priority, IoPriority::Normal, "Normal");
+                    ui.radio_value(&mut self.settings.io_priority, IoPriority::High, "High");
+                    ui.radio_value(&mut self.settings.io_priority, IoPriority::Realtime, "Realtime");
+                    self.elevation_hint(ui);
+                }).response, &["io_priority"]);
+            }
+            
+            for line in &self.priority_status {
+                ui.label(line);
             }
         });
         
@@ -641,49 +2326,166 @@ impl VRPerformanceApp {
         
         ui.group(|ui| {
             ui.label("Frame Throttle");
-            ui.add(egui::Slider::new(&mut self.settings.frame_throttle_fps, 30..=120).text("FPS Limit"));
+            let max_fps = self.headset().max_refresh_rate();
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.frame_throttle_fps, 30..=max_fps).text("FPS Limit")), &["frame_throttle_fps"]);
         });
         
-        ui.checkbox(&mut self.settings.shake_reduction, "Enable Shake Reduction");
+        unapplied.mark(ui.checkbox(&mut self.settings.shake_reduction, "Enable Shake Reduction"), &["shake_reduction"]);
         
-        ui.horizontal(|ui| {
+        unapplied.mark(ui.horizontal(|ui| {
             ui.label("Power Plan:");
             ui.radio_value(&mut self.settings.power_plan, PowerPlan::Balanced, "Balanced");
             ui.radio_value(&mut self.settings.power_plan, PowerPlan::HighPerformance, "High Performance");
             ui.radio_value(&mut self.settings.power_plan, PowerPlan::PowerSaver, "Power Saver");
+            self.drift_marker(ui, &["power_plan"]);
+            #[cfg(target_os = "linux")]
+            self.elevation_hint(ui);
+        }).response, &["power_plan"]);
+    }
+    
+    fn show_recommendations(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.label("Recommended Settings");
+            
+            let hardware = match &self.hardware {
+                Some(hardware) => hardware,
+                None => {
+                    ui.label("Suggests settings for this PC, the selected headset and its connection.");
+                    if ui.button("Detect Hardware").clicked() {
+                        let system = self.system.lock().unwrap();
+                        self.hardware = Some(recommend::detect(&system));
+                    }
+                    return;
+                }
+            };
+            ui.label(hardware.summary());
+            
+            let recommendations = recommend::recommend(hardware, self.headset(), &self.settings.connection_type);
+            let pending = recommend::pending(&recommendations, &self.settings);
+            if pending.is_empty() {
+                ui.colored_label(egui::Color32::GREEN, "Current settings match the recommendations");
+                return;
+            }
+            
+            let current = settings_diff::to_fields(&self.settings);
+            let mut accepted: Vec<&recommend::Recommendation> = Vec::new();
+            egui::Grid::new("recommendations_grid").striped(true).show(ui, |ui| {
+                ui.label("Setting");
+                ui.label("Current");
+                ui.label("Recommended");
+                ui.label("Why");
+                ui.end_row();
+                
+                for recommendation in &pending {
+                    ui.label(recommendation.label);
+                    ui.label(current.get(recommendation.field).map(settings_diff::display_value).unwrap_or_default());
+                    ui.colored_label(egui::Color32::LIGHT_BLUE, settings_diff::display_value(&recommendation.value));
+                    ui.label(&recommendation.rationale);
+                    if ui.button("Accept").clicked() {
+                        accepted.push(recommendation);
+                    }
+                    ui.end_row();
+                }
+            });
+            if ui.button("Accept All").clicked() {
+                accepted = pending.clone();
+            }
+            
+            if !accepted.is_empty() {
+                let fields: settings_diff::Fields = accepted
+                    .iter()
+                    .map(|r| (r.field.to_string(), r.value.clone()))
+                    .collect();
+                match settings_diff::with_fields(&self.settings, &fields) {
+                    Ok(settings) => {
+                        info!("Accepted {} recommendation(s)", fields.len());
+                        self.settings = settings;
+                    }
+                    Err(e) => warn!("Failed to accept recommendations: {}", e),
+                }
+            }
         });
     }
     
     fn show_visual_tab(&mut self, ui: &mut egui::Ui) {
+        let unapplied = Unapplied::new(&self.history);
         ui.heading("Visual & Encoding Settings");
         ui.separator();
         
+        let headset = self.headset();
+        ui.group(|ui| {
+            ui.label("Headset");
+            let mut selected = headset.id;
+            egui::ComboBox::from_id_source("headset_model")
+                .selected_text(headset.name)
+                .show_ui(ui, |ui| {
+                    for model in headsets::HEADSETS {
+                        ui.selectable_value(&mut selected, model.id, model.name);
+                    }
+                });
+            if selected != headset.id {
+                self.select_headset(selected);
+            }
+            let rates: Vec<String> = headset.refresh_rates.iter().map(|rate| rate.to_string()).collect();
+            ui.label(format!("Panel: {}x{} per eye, {} Hz", headset.panel_width, headset.panel_height, rates.join(" / ")));
+            unapplied.mark(ui.horizontal(|ui| {
+                ui.label("Connection:");
+                ui.radio_value(&mut self.settings.connection_type, ConnectionType::LinkCable, "Link cable");
+                ui.radio_value(&mut self.settings.connection_type, ConnectionType::AirLink, "Air Link (Wi-Fi)");
+                ui.radio_value(&mut self.settings.connection_type, ConnectionType::DisplayPort, "DisplayPort");
+            }).response, &["connection_type"]);
+        });
+        
+        ui.add_space(10.0);
+        
+        let headset = self.headset();
         ui.group(|ui| {
             ui.label("Oculus Link Encoding");
+            if headset.link.is_none() {
+                ui.colored_label(egui::Color32::YELLOW, format!("{} does not stream over Link; these values are ignored", headset.name));
+            }
+            
+            ui.horizontal(|ui| {
+                ui.label("Presets:");
+                for preset in headset.presets() {
+                    let hint = format!("{}x{} at {} Mbps", preset.width, preset.height, preset.bitrate_mbps);
+                    if ui.button(preset.label).on_hover_text(hint).clicked() {
+                        self.settings.encode_resolution_width = preset.width;
+                        self.settings.encode_resolution_height = preset.height;
+                        self.settings.encode_bitrate_mbps = preset.bitrate_mbps;
+                    }
+                }
+            });
             
             ui.horizontal(|ui| {
                 ui.label("Bitrate (Mbps):");
-                ui.add(egui::Slider::new(&mut self.settings.encode_bitrate_mbps, 50..=500));
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.encode_bitrate_mbps, 50..=500)), &["encode_bitrate_mbps"]);
+                self.drift_marker(ui, &["encode_bitrate_mbps"]);
             });
             
             ui.horizontal(|ui| {
                 ui.label("Encode Width:");
-                ui.add(egui::Slider::new(&mut self.settings.encode_resolution_width, 1440..=3664));
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.encode_resolution_width, headset.encode_width_range())), &["encode_resolution_width"]);
+                self.drift_marker(ui, &["encode_resolution_width"]);
             });
             
             ui.horizontal(|ui| {
                 ui.label("Encode Height:");
-                ui.add(egui::Slider::new(&mut self.settings.encode_resolution_height, 1584..=1920));
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.encode_resolution_height, headset.encode_height_range())), &["encode_resolution_height"]);
+                self.drift_marker(ui, &["encode_resolution_height"]);
             });
             
-            ui.label("Higher = Better quality, more bandwidth. 200+ recommended for Quest 3.");
+            ui.label("Higher = Better quality, more bandwidth. Presets are tuned for the selected headset.");
         });
         
         ui.add_space(10.0);
         
         ui.group(|ui| {
             ui.label("Link Sharpening");
-            ui.add(egui::Slider::new(&mut self.settings.link_sharpening, 0.0..=1.0).text("Sharpness"));
+            ui.horizontal(|ui| {
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.link_sharpening, 0.0..=1.0).text("Sharpness")), &["link_sharpening"]);
+                self.drift_marker(ui, &["link_sharpening"]);
+            });
             ui.label("Adds post-processing sharpening to Link video stream");
         });
         
@@ -694,55 +2496,124 @@ impl VRPerformanceApp {
             
             ui.horizontal(|ui| {
                 ui.label("Pixel Density:");
-                ui.add(egui::Slider::new(&mut self.settings.pixel_density, 0.5..=2.0));
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.pixel_density, 0.5..=2.0)), &["pixel_density"]);
             });
             
             ui.horizontal(|ui| {
                 ui.label("FOV Scale:");
-                ui.add(egui::Slider::new(&mut self.settings.fov_scale, 0.8..=1.2));
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.fov_scale, 0.8..=1.2)), &["fov_scale"]);
             });
             
-            ui.add(egui::Slider::new(&mut self.settings.contrast, 0.5..=1.5).text("Contrast"));
-            ui.add(egui::Slider::new(&mut self.settings.saturation, 0.5..=1.5).text("Saturation"));
-            ui.add(egui::Slider::new(&mut self.settings.sharpening_amount, 0.0..=1.0).text("Sharpening Amount"));
-            ui.add(egui::Slider::new(&mut self.settings.super_sampling, 1.0..=2.0).text("Super Sampling"));
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.contrast, 0.5..=1.5).text("Contrast")), &["contrast"]);
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.saturation, 0.5..=1.5).text("Saturation")), &["saturation"]);
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.sharpening_amount, 0.0..=1.0).text("Sharpening Amount")), &["sharpening_amount"]);
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.super_sampling, 1.0..=2.0).text("Super Sampling")), &["super_sampling"]);
         });
         
         ui.group(|ui| {
             ui.label("Upscaling");
-            ui.checkbox(&mut self.settings.upscaling_enabled, "Enable Upscaling");
+            unapplied.mark(ui.checkbox(&mut self.settings.upscaling_enabled, "Enable Upscaling"), &["upscaling_enabled"]);
             if self.settings.upscaling_enabled {
-                ui.horizontal(|ui| {
+                unapplied.mark(ui.horizontal(|ui| {
                     ui.label("Type:");
                     ui.radio_value(&mut self.settings.upscaling_type, UpscalingType::NIS, "NIS");
                     ui.radio_value(&mut self.settings.upscaling_type, UpscalingType::FSR, "FSR");
                     ui.radio_value(&mut self.settings.upscaling_type, UpscalingType::CAS, "CAS");
-                });
-                ui.add(egui::Slider::new(&mut self.settings.upscaling_scale, 0.5..=1.0).text("Scale"));
+                }).response, &["upscaling_type"]);
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.upscaling_scale, 0.5..=1.0).text("Scale")), &["upscaling_scale"]);
             }
         });
         
-        ui.checkbox(&mut self.settings.mirror_window, "Enable Mirror Window");
-        ui.checkbox(&mut self.settings.guardian_visibility, "Show Guardian");
+        ui.horizontal(|ui| {
+            unapplied.mark(ui.checkbox(&mut self.settings.mirror_window, "Enable Mirror Window"), &["mirror_window"]);
+            self.drift_marker(ui, &["mirror_window"]);
+        });
+        ui.horizontal(|ui| {
+            unapplied.mark(ui.checkbox(&mut self.settings.guardian_visibility, "Show Guardian"), &["guardian_visibility"]);
+            self.drift_marker(ui, &["guardian_visibility"]);
+        });
     }
     
     fn show_processes_tab(&mut self, ui: &mut egui::Ui) {
+        let unapplied = Unapplied::new(&self.history);
         ui.heading("Process Management");
         ui.separator();
         
         ui.group(|ui| {
             ui.label("Auto-Recovery Settings");
-            ui.checkbox(&mut self.settings.auto_restart_on_freeze, "Auto-restart frozen processes");
-            ui.checkbox(&mut self.settings.kill_oculus_client, "Kill Oculus Client (reduces overhead)");
+            unapplied.mark(ui.checkbox(&mut self.settings.auto_restart_on_freeze, "Auto-restart frozen processes"), &["auto_restart_on_freeze"]);
+            unapplied.mark(ui.checkbox(&mut self.settings.kill_oculus_client, "Kill Oculus Client (reduces overhead)"), &["kill_oculus_client"]);
             
-            if self.settings.kill_oculus_client {
-                if ui.button("Kill Oculus Client Now").clicked() {
-                    self.kill_oculus_client();
-                }
+            if self.settings.kill_oculus_client && ui.button("Kill Oculus Client Now").clicked() {
+                self.kill_oculus_client();
+            }
+            
+            unapplied.mark(ui.checkbox(&mut self.settings.oculus_killer_enabled, "Enable OculusKiller (Disables Oculus Dash)"), &["oculus_killer_enabled"]);
+            ui.horizontal(|ui| {
+                ui.label("Replacement executable:");
+                unapplied.mark(ui.add(egui::TextEdit::singleline(&mut self.settings.oculus_killer_replacement).hint_text("none, just disable Dash")), &["oculus_killer_replacement"]);
+            });
+            
+            self.refresh_dash_swap_status();
+            if self.dash_swap_status == Some(file_swap::SwapStatus::ReplacedByUpdate) {
+                ui.colored_label(egui::Color32::YELLOW, "An Oculus update restored OculusDash.exe - apply settings to replace it again.");
+            }
+            if let Some(status) = &self.oculus_killer_status {
+                ui.label(status);
+            }
+            self.elevation_hint(ui);
+        });
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("Services");
+            ui.separator();
+            
+            let mut action = None;
+            let idle = self.service_job.is_none();
+            egui::Grid::new("service_grid")
+                .striped(true)
+                .min_col_width(150.0)
+                .show(ui, |ui| {
+                    ui.label("Service");
+                    ui.label("State");
+                    ui.label("Actions");
+                    ui.end_row();
+                    
+                    for (service, state) in services::MANAGED_SERVICES.iter().zip(&self.service_states) {
+                        ui.label(format!("{} ({})", service.label, service.name));
+                        match state {
+                            Ok(services::ServiceState::Running) => ui.colored_label(egui::Color32::GREEN, "Running"),
+                            Ok(state) => ui.label(state.label()),
+                            Err(e) => ui.colored_label(egui::Color32::RED, e),
+                        };
+                        ui.horizontal(|ui| {
+                            let enabled = idle && !matches!(state, Ok(services::ServiceState::NotInstalled));
+                            if ui.add_enabled(enabled, egui::Button::new("Start")).clicked() {
+                                action = Some((service, Some(true)));
+                            }
+                            if ui.add_enabled(enabled, egui::Button::new("Stop")).clicked() {
+                                action = Some((service, Some(false)));
+                            }
+                            if ui.add_enabled(enabled, egui::Button::new("Restart")).clicked() {
+                                action = Some((service, None));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            match action {
+                Some((service, Some(start))) => self.control_service(service, start),
+                Some((service, None)) => self.restart_service(service),
+                None => {}
             }
             
-            ui.checkbox(&mut self.settings.oculus_killer_enabled, "Enable OculusKiller (Disables Oculus Dash)");
-            ui.label("Note: Requires admin privileges to modify Oculus files.");
+            if !idle {
+                ui.label("Waiting for the service...");
+            } else if let Some(status) = &self.service_status {
+                ui.colored_label(egui::Color32::RED, status);
+            }
         });
         
         ui.add_space(10.0);
@@ -759,9 +2630,11 @@ impl VRPerformanceApp {
                     ui.label("Status");
                     ui.label("CPU %");
                     ui.label("Memory (MB)");
+                    ui.label("Priority");
                     ui.label("Actions");
                     ui.end_row();
                     
+                    let mut restart = None;
                     for proc in &self.processes {
                         ui.label(&proc.name);
                         
@@ -769,16 +2642,16 @@ impl VRPerformanceApp {
                             ProcessStatus::Running => "Running",
                             ProcessStatus::Stopped => "Stopped",
                             ProcessStatus::Frozen => "Frozen",
-                            ProcessStatus::Restarting => "Restarting",
                         };
                         ui.label(status_text);
                         
                         ui.label(format!("{:.1}%", proc.cpu_usage));
                         ui.label(format!("{} MB", proc.memory_mb));
+                        ui.label(proc.priority.as_deref().unwrap_or("-"));
                         
-                        if proc.status == ProcessStatus::Running {
+                        if matches!(proc.status, ProcessStatus::Running | ProcessStatus::Frozen) {
                             if ui.button("Restart").clicked() {
-                                self.restart_process(&proc.name);
+                                restart = Some(proc.name.clone());
                             }
                         } else {
                             ui.label("-");
@@ -786,6 +2659,9 @@ impl VRPerformanceApp {
                         
                         ui.end_row();
                     }
+                    if let Some(name) = restart {
+                        self.restart_process(&name);
+                    }
                 });
         });
         
@@ -793,59 +2669,504 @@ impl VRPerformanceApp {
         
         if ui.button("Restart All VR Services").clicked() {
             for proc in self.processes.clone() {
-                if proc.status == ProcessStatus::Running {
+                if matches!(proc.status, ProcessStatus::Running | ProcessStatus::Frozen) {
                     self.restart_process(&proc.name);
                 }
             }
-        }
-        
-        ui.add_space(10.0);
-        
-        ui.group(|ui| {
-            ui.label("CPU Affinity");
-            ui.add(egui::Slider::new(&mut self.settings.cpu_affinity, 0..=15).text("Affinity Mask"));
+        }
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("CPU Affinity");
+            
+            ui.horizontal(|ui| {
+                for preset in [affinity::AffinityPreset::AllCores, affinity::AffinityPreset::ExcludeCore0, affinity::AffinityPreset::PerformanceCores] {
+                    let available = preset != affinity::AffinityPreset::PerformanceCores || self.performance_cores.is_some();
+                    if ui.add_enabled(available, egui::Button::new(preset.label())).clicked() {
+                        self.settings.affinity_cores = affinity::preset_cores(preset, self.cpu_count, self.performance_cores.as_deref());
+                    }
+                }
+            });
+            
+            let mut cores = affinity::selected_cores(&self.settings.affinity_cores, self.cpu_count);
+            let mut changed = false;
+            
+            egui::Grid::new("affinity_grid").show(ui, |ui| {
+                for (i, enabled) in cores.iter_mut().enumerate() {
+                    changed |= ui.checkbox(enabled, format!("CPU {}", i)).changed();
+                    if i % 8 == 7 {
+                        ui.end_row();
+                    }
+                }
+            });
+            
+            if changed {
+                self.settings.affinity_cores = if affinity::is_restricted(&cores) { cores } else { Vec::new() };
+            }
+            
+            if !affinity::is_restricted(&self.settings.affinity_cores) {
+                ui.label("VR processes may run on all cores.");
+            } else if !self.settings.affinity_cores.iter().any(|c| *c) {
+                ui.label("Select at least one core.");
+            } else {
+                ui.label("Applied to VR processes on Apply All and whenever they restart.");
+            }
+        });
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            unapplied.mark(ui.checkbox(&mut self.settings.audio_switching, "Automatic Audio Switching"), &["audio_switching"]);
+            
+            if self.settings.audio_switching {
+                ui.label("Switches to the headset audio devices when the VR runtime starts and restores the desktop devices when it stops. Use * as a wildcard.");
+                ui.horizontal(|ui| {
+                    ui.label("Headset output:");
+                    unapplied.mark(ui.text_edit_singleline(&mut self.settings.audio_output_pattern), &["audio_output_pattern"]);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Headset input:");
+                    unapplied.mark(ui.text_edit_singleline(&mut self.settings.audio_input_pattern), &["audio_input_pattern"]);
+                });
+                
+                if ui.button("Refresh Devices").clicked() {
+                    let outputs = audio::list_devices(audio::Direction::Output).unwrap_or_default();
+                    let inputs = audio::list_devices(audio::Direction::Input).unwrap_or_default();
+                    self.audio_devices = Some((outputs, inputs));
+                }
+                
+                if let Some((outputs, inputs)) = &self.audio_devices {
+                    for (title, devices, pattern) in [
+                        ("Outputs", outputs, &self.settings.audio_output_pattern),
+                        ("Inputs", inputs, &self.settings.audio_input_pattern),
+                    ] {
+                        ui.label(title);
+                        for device in devices {
+                            let marker = if audio::matches_pattern(&device.name, pattern) { "[match] " } else { "" };
+                            ui.label(format!("  {}{}", marker, device.name));
+                        }
+                    }
+                }
+            }
+            
+            if let Some(status) = &self.audio_status {
+                ui.label(status);
+            }
+        });
+    }
+    
+    fn show_advanced_tab(&mut self, ui: &mut egui::Ui) {
+        let unapplied = Unapplied::new(&self.history);
+        ui.heading("Advanced Settings");
+        ui.separator();
+        
+        ui.label("WARNING: These settings may cause instability if misconfigured");
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("OpenXR Advanced");
+            unapplied.mark(ui.checkbox(&mut self.settings.force_composition_layers, "Force Composition Layers"), &["force_composition_layers"]);
+            unapplied.mark(ui.checkbox(&mut self.settings.disable_depth_submission, "Disable Depth Submission"), &["disable_depth_submission"]);
+            unapplied.mark(ui.checkbox(&mut self.settings.turbo_mode, "Turbo Mode (reduces latency)"), &["turbo_mode"]);
+        });
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("Debug Tools");
+            
+            if ui.button("Open Oculus Debug Tool").clicked() {
+                #[cfg(target_os = "windows")]
+                {
+                    let _ = launch::spawn_reaped(&mut Command::new(self.install_path(install::InstallPath::OculusDebugTool)));
+                }
+            }
+            
+            if ui.button("Open SteamVR Settings").clicked() {
+                #[cfg(target_os = "windows")]
+                {
+                    let _ = Command::new("cmd").args(&["/c", "start", "steam://open/settings"]).spawn();
+                }
+            }
+        });
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("System State");
+            ui.label("Compares the saved settings with what is currently configured on this machine.");
+            
+            ui.horizontal(|ui| {
+                if ui.button("Re-read System").clicked() {
+                    self.refresh_system_state();
+                }
+                if ui.add_enabled(self.system_state.is_some(), egui::Button::new("Import from System")).clicked() {
+                    self.import_from_system(None);
+                }
+                if ui.add_enabled(!self.drift.is_empty(), egui::Button::new("Re-apply Drifted")).clicked() {
+                    self.reapply_drift();
+                }
+                if let Some(snapshot) = &self.system_state {
+                    ui.label(format!("read {} s ago", snapshot.taken_at.elapsed().as_secs()));
+                }
+            });
+            
+            unapplied.mark(ui.checkbox(&mut self.settings.drift_monitor_enabled, "Monitor for drift in the background"), &["drift_monitor_enabled"]);
+            if self.settings.drift_monitor_enabled {
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.drift_monitor_interval_seconds, 5..=300).text("Check interval (s)")), &["drift_monitor_interval_seconds"]);
+                unapplied.mark(ui.checkbox(&mut self.settings.drift_auto_reapply, "Automatically re-apply drifted values"), &["drift_auto_reapply"]);
+            }
+            if let Some(notice) = &self.drift_notice {
+                ui.colored_label(egui::Color32::YELLOW, notice);
+            }
+            
+            if self.system_state.is_some() && self.drift.is_empty() {
+                ui.colored_label(egui::Color32::GREEN, "Saved settings match the system");
+            } else if !self.drift.is_empty() {
+                let mut import = None;
+                egui::Grid::new("drift_grid").striped(true).show(ui, |ui| {
+                    ui.label("Setting");
+                    ui.label("Saved");
+                    ui.label("System");
+                    ui.label("Source");
+                    ui.end_row();
+                    
+                    for item in &self.drift {
+                        ui.label(item.label);
+                        ui.label(settings_diff::display_value(&item.saved));
+                        ui.colored_label(egui::Color32::YELLOW, settings_diff::display_value(&item.live));
+                        ui.label(&item.source);
+                        if ui.button("Use System Value").clicked() {
+                            import = Some(item.field);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(field) = import {
+                    self.import_from_system(Some(field));
+                }
+            }
+        });
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("Install Paths");
+            ui.label("Leave a field empty to use the discovered location.");
+            
+            egui::Grid::new("install_paths_grid").num_columns(2).show(ui, |ui| {
+                for path in install::ALL_PATHS {
+                    ui.label(path.label());
+                    let discovered = self.settings.install_paths.get(*path);
+                    let hint = if discovered.is_empty() { "not found" } else { discovered };
+                    let hint = hint.to_string();
+                    ui.add(
+                        egui::TextEdit::singleline(self.settings.install_path_overrides.get_mut(*path))
+                            .hint_text(hint)
+                            .desired_width(400.0),
+                    );
+                    ui.end_row();
+                }
+            });
+            
+            if ui.button("Rediscover").clicked() {
+                self.settings.install_paths = install::discover();
+            }
+        });
+        
+        ui.add_space(10.0);
+        
+        self.show_hooks(ui);
+        
+        ui.add_space(10.0);
+        
+        self.show_scripts(ui);
+        
+        ui.add_space(10.0);
+        
+        self.show_hotkeys(ui);
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("Support");
+            ui.checkbox(&mut self.redact_support_bundle, "Redact usernames and paths");
+            
+            if ui.add_enabled(self.support_bundle_job.is_none(), egui::Button::new("Create Support Bundle")).clicked() {
+                if let Err(e) = self.save_settings() {
+                    warn!("Failed to save settings: {}", e);
+                }
+                let options = support_bundle::BundleOptions {
+                    redact: self.redact_support_bundle,
+                    ..Default::default()
+                };
+                // Collecting processes and file versions takes seconds, so it runs off the UI thread.
+                let (sender, receiver) = mpsc::channel();
+                std::thread::spawn(move || {
+                    let _ = sender.send(support_bundle::create_bundle(&options));
+                });
+                self.support_bundle_job = Some(receiver);
+                self.support_bundle_status = Some(String::from("Creating support bundle..."));
+            }
+            
+            if let Some(status) = &self.support_bundle_status {
+                ui.label(status);
+            }
+        });
+    }
+    
+    fn show_hooks(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.label("Event Hooks");
+            ui.label("Commands run through the shell when an event happens. Details arrive as VR_SUITE_* variables and as JSON on stdin; output goes to the log.");
+            
+            let mut remove = None;
+            let mut test = None;
+            egui::Grid::new("hooks_grid").striped(true).show(ui, |ui| {
+                ui.label("Event");
+                ui.label("Command");
+                ui.label("Timeout (s)");
+                ui.end_row();
+                
+                for (i, hook) in self.settings.hooks.iter_mut().enumerate() {
+                    egui::ComboBox::from_id_source(("hook_event", i)).selected_text(hook.event.label()).show_ui(ui, |ui| {
+                        for event in hooks::HookEvent::ALL {
+                            ui.selectable_value(&mut hook.event, *event, event.label());
+                        }
+                    });
+                    ui.add(egui::TextEdit::singleline(&mut hook.command).desired_width(300.0));
+                    ui.add(egui::DragValue::new(&mut hook.timeout_seconds).clamp_range(1..=600));
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut hook.enabled, "Enabled");
+                        if ui.add_enabled(!hook.command.trim().is_empty(), egui::Button::new("Test")).clicked() {
+                            test = Some(i);
+                        }
+                        if ui.button("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = test {
+                let mut hook = self.settings.hooks[i].clone();
+                hook.enabled = true;
+                info!("Testing {} hook", hook.event.name());
+                hooks::fire(std::slice::from_ref(&hook), hook.event, &[("test", String::from("1"))]);
+            }
+            if let Some(i) = remove {
+                self.settings.hooks.remove(i);
+            }
+            if ui.button("Add Hook").clicked() {
+                self.settings.hooks.push(hooks::Hook::default());
+            }
+            
+            ui.separator();
+            ui.label("Game executables (for game launched / exited):");
+            let mut remove = None;
+            for (i, name) in self.settings.game_processes.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(name).hint_text("HalfLifeAlyx.exe"));
+                    if ui.button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                self.settings.game_processes.remove(i);
+            }
+            if ui.button("Add Game").clicked() {
+                self.settings.game_processes.push(String::from(""));
+            }
+            
+            match &self.connected_headsets {
+                Some(headsets) if !headsets.is_empty() => ui.label(format!("Connected headsets: {}", headsets.join(", "))),
+                Some(_) => ui.label("No headset detected over USB"),
+                None => ui.label("Checking for headsets..."),
+            };
+        });
+    }
+    
+    fn show_scripts(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.label("Apply Scripts");
+            ui.label("Rhai scripts run after the built-in steps on every apply. They can read `settings` and the process list, log, and write the registry or files under the allowed paths.");
+            
+            let mut remove = None;
+            let mut dry_run = None;
+            egui::Grid::new("scripts_grid").striped(true).show(ui, |ui| {
+                for (i, script) in self.settings.apply_scripts.iter_mut().enumerate() {
+                    ui.add(egui::TextEdit::singleline(&mut script.path).hint_text("C:\\VR\\tweaks.rhai").desired_width(300.0));
+                    ui.checkbox(&mut script.enabled, "Enabled");
+                    if ui.add_enabled(!script.path.trim().is_empty(), egui::Button::new("Dry Run")).clicked() {
+                        dry_run = Some(i);
+                    }
+                    if ui.button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = dry_run {
+                let script = self.settings.apply_scripts[i].clone();
+                let context = self.script_context(&self.system.lock().unwrap(), true);
+                let run = scripting::run_file(Path::new(script.path.trim()), context);
+                let mut status = format!("{}: {} write(s)", script.name(), run.steps.len());
+                for step in &run.steps {
+                    status.push_str(&format!("\n  {}", step.target));
+                }
+                if let Some(error) = &run.error {
+                    status.push_str(&format!("\n  Error: {}", error));
+                }
+                self.script_status = Some(status);
+            }
+            if let Some(i) = remove {
+                self.settings.apply_scripts.remove(i);
+            }
+            if ui.button("Add Script").clicked() {
+                self.settings.apply_scripts.push(scripting::ApplyScript::default());
+            }
+            if let Some(status) = &self.script_status {
+                ui.label(status);
+            }
+            
+            ui.separator();
+            ui.label("Allowed paths for file_read, file_write and ini_set:");
+            let mut remove = None;
+            for (i, path) in self.settings.script_allowed_paths.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(path).desired_width(300.0));
+                    if ui.button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                self.settings.script_allowed_paths.remove(i);
+            }
+            if ui.button("Add Path").clicked() {
+                self.settings.script_allowed_paths.push(String::from(""));
+            }
+            
+            ui.label("Registry keys for registry_read and registry_set_*, such as HKCU\\Software\\Vendor:");
+            let mut remove = None;
+            for (i, key) in self.settings.script_registry_allowed.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(key).desired_width(300.0));
+                    if ui.button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                self.settings.script_registry_allowed.remove(i);
+            }
+            if ui.button("Add Key").clicked() {
+                self.settings.script_registry_allowed.push(String::from(""));
+            }
         });
-        
-        ui.checkbox(&mut self.settings.audio_switching, "Automatic Audio Switching");
     }
     
-    fn show_advanced_tab(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Advanced Settings");
-        ui.separator();
-        
-        ui.label("WARNING: These settings may cause instability if misconfigured");
-        
-        ui.add_space(10.0);
-        
+    fn show_hotkeys(&mut self, ui: &mut egui::Ui) {
         ui.group(|ui| {
-            ui.label("OpenXR Advanced");
-            ui.checkbox(&mut self.settings.force_composition_layers, "Force Composition Layers");
-            ui.checkbox(&mut self.settings.disable_depth_submission, "Disable Depth Submission");
-            ui.checkbox(&mut self.settings.turbo_mode, "Turbo Mode (reduces latency)");
-        });
-        
-        ui.add_space(10.0);
-        
-        ui.group(|ui| {
-            ui.label("Debug Tools");
+            ui.label("Global Hotkeys");
+            ui.label("Work while another window or the headset has focus. Press Set, then the key combination; Escape cancels.");
             
-            if ui.button("Open Oculus Debug Tool").clicked() {
-                #[cfg(target_os = "windows")]
-                {
-                    let _ = Command::new(r"C:\Program Files\Oculus\Support\oculus-diagnostics\OculusDebugTool.exe").spawn();
+            if let Some(i) = self.hotkey_capture {
+                let pressed = ui.input(|input| {
+                    input.events.iter().find_map(|event| match event {
+                        egui::Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+                        _ => None,
+                    })
+                });
+                match pressed {
+                    Some((egui::Key::Escape, _)) => self.hotkey_capture = None,
+                    Some((key, modifiers)) => {
+                        let mut text = String::from("");
+                        for (held, name) in [(modifiers.ctrl, "Ctrl+"), (modifiers.alt, "Alt+"), (modifiers.shift, "Shift+")] {
+                            if held {
+                                text.push_str(name);
+                            }
+                        }
+                        text.push_str(key.name());
+                        match hotkeys::Binding::parse(&text) {
+                            Ok(binding) => {
+                                if let Some(hotkey) = self.settings.hotkeys.get_mut(i) {
+                                    hotkey.binding = binding.to_string();
+                                }
+                                self.hotkey_capture = None;
+                            }
+                            Err(e) => self.hotkey_status = Some(format!("{}: {}", text, e)),
+                        }
+                    }
+                    None => {}
                 }
             }
             
-            if ui.button("Open SteamVR Settings").clicked() {
-                #[cfg(target_os = "windows")]
-                {
-                    let _ = Command::new("cmd").args(&["/c", "start", "steam://open/settings"]).spawn();
+            let mut remove = None;
+            let mut capture = None;
+            egui::Grid::new("hotkeys_grid").striped(true).show(ui, |ui| {
+                ui.label("Action");
+                ui.label("Keys");
+                ui.end_row();
+                
+                let bindings: Vec<Option<String>> = self.settings.hotkeys.iter()
+                    .map(|hotkey| hotkeys::Binding::parse(&hotkey.binding).ok().filter(|_| hotkey.enabled).map(|b| b.to_string()))
+                    .collect();
+                for (i, hotkey) in self.settings.hotkeys.iter_mut().enumerate() {
+                    egui::ComboBox::from_id_source(("hotkey_action", i)).selected_text(hotkey.action.label()).show_ui(ui, |ui| {
+                        for action in hotkeys::HotkeyAction::ALL {
+                            ui.selectable_value(&mut hotkey.action, *action, action.label());
+                        }
+                    });
+                    ui.add(egui::TextEdit::singleline(&mut hotkey.binding).id(hotkey_binding_id(i)).hint_text("Ctrl+Alt+F9").desired_width(150.0));
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut hotkey.enabled, "Enabled");
+                        let label = if self.hotkey_capture == Some(i) { "Press keys..." } else { "Set" };
+                        if ui.button(label).clicked() {
+                            capture = Some(i);
+                        }
+                        if ui.button("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                    if hotkey.binding.trim().is_empty() {
+                        ui.label("");
+                    } else if let Err(e) = hotkeys::Binding::parse(&hotkey.binding) {
+                        ui.colored_label(egui::Color32::RED, e);
+                    } else if bindings[i].is_some() && bindings.iter().filter(|b| **b == bindings[i]).count() > 1 {
+                        ui.colored_label(egui::Color32::YELLOW, "Used twice");
+                    } else {
+                        ui.label("");
+                    }
+                    ui.end_row();
                 }
+            });
+            if let Some(i) = capture {
+                self.hotkey_capture = Some(i);
+                self.hotkey_status = None;
+            }
+            if let Some(i) = remove {
+                self.settings.hotkeys.remove(i);
+                self.hotkey_capture = None;
+            }
+            if ui.button("Add Hotkey").clicked() {
+                self.settings.hotkeys.push(hotkeys::Hotkey::default());
+            }
+            
+            if let Some(listener) = &self.hotkey_listener {
+                ui.label(format!("{} hotkey(s) registered via {}", listener.bindings.len(), listener.backend));
+            }
+            if let Some(status) = &self.hotkey_status {
+                ui.colored_label(egui::Color32::YELLOW, status);
             }
         });
     }
     
     fn show_stats_tab(&mut self, ui: &mut egui::Ui) {
+        let unapplied = Unapplied::new(&self.history);
         ui.heading("Performance Statistics");
         ui.separator();
         
@@ -885,51 +3206,602 @@ impl VRPerformanceApp {
         ui.add_space(10.0);
         
         ui.label("Stats are read from VR runtime when available");
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("Auto-Tune");
+            ui.label("Steps render scale, foveation and upscaling between sessions to hold the target frame rate.");
+            unapplied.mark(ui.checkbox(&mut self.settings.autotune_enabled, "Enable auto-tune"), &["autotune_enabled"]);
+            
+            ui.horizontal(|ui| {
+                ui.label("Frame time log (PresentMon or MangoHud CSV):");
+                unapplied.mark(ui.add(egui::TextEdit::singleline(&mut self.settings.autotune_frame_log).desired_width(300.0)), &["autotune_frame_log"]);
+            });
+            ui.label(format!("Target: {} fps (Custom FPS)", self.settings.custom_fps));
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.autotune_headroom_percent, 0..=30).text("Headroom %")), &["autotune_headroom_percent"]);
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.autotune_min_render_scale, 0.5..=1.0).text("Min render scale")), &["autotune_min_render_scale"]);
+            unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.autotune_max_render_scale, 1.0..=2.0).text("Max render scale")), &["autotune_max_render_scale"]);
+            
+            if self.autotune_session.is_some() {
+                ui.colored_label(egui::Color32::LIGHT_BLUE, "Measuring this session");
+            }
+            if let Some(status) = &self.autotune_status {
+                ui.label(status);
+            }
+            
+            if !self.autotune_history.records.is_empty() {
+                let mut revert = None;
+                egui::ScrollArea::vertical().max_height(200.0).id_source("autotune_history").show(ui, |ui| {
+                    egui::Grid::new("autotune_grid").striped(true).show(ui, |ui| {
+                        ui.label("Target");
+                        ui.label("p50 / p95 / p99");
+                        ui.label("Decision");
+                        ui.label("Changes");
+                        ui.end_row();
+                        
+                        for (index, record) in self.autotune_history.records.iter().enumerate().rev() {
+                            ui.label(format!("{} fps", record.target_fps));
+                            ui.label(format!("{:.1} / {:.1} / {:.1} ms", record.stats.p50_ms, record.stats.p95_ms, record.stats.p99_ms));
+                            ui.label(&record.note);
+                            let changes: Vec<String> = record
+                                .changes
+                                .iter()
+                                .map(|c| format!("{}: {} -> {}", c.field, settings_diff::display_value(&c.from), settings_diff::display_value(&c.to)))
+                                .collect();
+                            ui.label(changes.join(", "));
+                            if record.reverted {
+                                ui.label("reverted");
+                            } else if !record.changes.is_empty() && ui.button("Revert").clicked() {
+                                revert = Some(index);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                if let Some(index) = revert {
+                    self.revert_autotune(index);
+                }
+            }
+        });
     }
     
     fn show_relinked_tab(&mut self, ui: &mut egui::Ui) {
+        let unapplied = Unapplied::new(&self.history);
         ui.heading("ReLinked VR Settings");
         ui.separator();
         
-        ui.checkbox(&mut self.settings.relinked_mode, "Enable ReLinked Mode (Minimal Runtime)");
+        unapplied.mark(ui.checkbox(&mut self.settings.relinked_mode, "Enable ReLinked Mode (Minimal Runtime)"), &["relinked_mode"]);
         ui.label("Note: This approximates ReLinked features. Full ReLinked requires custom runtime.");
         
-        if ui.button("Launch Runtime").clicked() {
-            self.launch_runtime();
-        }
-        
         ui.group(|ui| {
-            ui.label("General Options");
-            ui.checkbox(&mut self.settings.disable_asw, "Disable ASW");
-            ui.checkbox(&mut self.settings.enable_steamvr_autostart, "Enable SteamVR Auto-Start");
-            ui.checkbox(&mut self.settings.enable_runtime_high_priority, "Enable Runtime High Priority");
-            ui.checkbox(&mut self.settings.allow_other_software, "Allow Other Software (CAPI/OpenXR)");
-            ui.checkbox(&mut self.settings.disable_telemetry, "Disable Telemetry");
-            ui.checkbox(&mut self.settings.disable_login, "Disable Login (Approximate)");
+            ui.label("Launch Sequence");
             ui.horizontal(|ui| {
-                ui.label("Custom Startup Program:");
-                ui.text_edit_singleline(&mut self.settings.custom_startup_program);
+                ui.label("Runtime start timeout (s):");
+                unapplied.mark(ui.add(egui::Slider::new(&mut self.settings.runtime_start_timeout_seconds, 5..=120)), &["runtime_start_timeout_seconds"]);
+            });
+            
+            let running = self.launch_progress.as_ref().is_some_and(|progress| {
+                progress.lock().unwrap().iter()
+                    .any(|s| matches!(s.state, launch::StepState::Pending | launch::StepState::Running))
             });
+            if ui.add_enabled(!running, egui::Button::new("Launch Runtime")).clicked() {
+                self.launch_runtime();
+            }
+            
+            if let Some(progress) = &self.launch_progress {
+                egui::Grid::new("launch_grid").striped(true).show(ui, |ui| {
+                    for step in progress.lock().unwrap().iter() {
+                        ui.label(&step.name);
+                        match &step.state {
+                            launch::StepState::Pending => ui.label("Pending"),
+                            launch::StepState::Running => ui.label("Running..."),
+                            launch::StepState::Succeeded(detail) => ui.colored_label(egui::Color32::GREEN, format!("OK - {}", detail)),
+                            launch::StepState::Failed(detail) => ui.colored_label(egui::Color32::RED, format!("Failed - {}", detail)),
+                            launch::StepState::Skipped(detail) => ui.label(format!("Skipped - {}", detail)),
+                        };
+                        ui.end_row();
+                    }
+                });
+            }
+        });
+        
+        
+        ui.group(|ui| {
+            ui.label("General Options");
+            unapplied.mark(ui.checkbox(&mut self.settings.disable_asw, "Disable ASW"), &["disable_asw"]);
+            unapplied.mark(ui.checkbox(&mut self.settings.enable_steamvr_autostart, "Enable SteamVR Auto-Start"), &["enable_steamvr_autostart"]);
+            unapplied.mark(ui.checkbox(&mut self.settings.enable_runtime_high_priority, "Enable Runtime High Priority"), &["enable_runtime_high_priority"]);
+            unapplied.mark(ui.checkbox(&mut self.settings.allow_other_software, "Allow Other Software (CAPI/OpenXR)"), &["allow_other_software"]);
+            unapplied.mark(ui.checkbox(&mut self.settings.disable_telemetry, "Disable Telemetry"), &["disable_telemetry"]);
+            unapplied.mark(ui.checkbox(&mut self.settings.disable_login, "Disable Login (Approximate)"), &["disable_login"]);
+        });
+        
+        ui.group(|ui| {
+            ui.label("Custom Startup Programs");
+            ui.label("Started in order after the runtime (and SteamVR, if enabled) is running.");
+            
+            let mut remove = None;
+            for (i, program) in self.settings.startup_programs.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label("Program:");
+                    ui.text_edit_singleline(&mut program.path);
+                    if ui.button("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Arguments:");
+                    ui.text_edit_singleline(&mut program.args);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Working directory:");
+                    ui.text_edit_singleline(&mut program.working_dir);
+                });
+                ui.separator();
+            }
+            if let Some(i) = remove {
+                self.settings.startup_programs.remove(i);
+            }
+            
+            if ui.button("Add Program").clicked() {
+                self.settings.startup_programs.push(launch::StartupProgram::default());
+            }
         });
         
         ui.group(|ui| {
             ui.label("Quest Link Options");
-            ui.horizontal(|ui| {
+            unapplied.mark(ui.horizontal(|ui| {
                 ui.label("Custom FPS:");
-                ui.add(egui::Slider::new(&mut self.settings.custom_fps, 60..=120));
-            });
+                for rate in self.headset().refresh_rates {
+                    ui.radio_value(&mut self.settings.custom_fps, *rate, format!("{} Hz", rate));
+                }
+            }).response, &["custom_fps"]);
         });
         
         ui.group(|ui| {
             ui.label("Rift Options");
-            ui.checkbox(&mut self.settings.disable_oled_mura, "Disable OLED Mura Correction");
+            unapplied.mark(ui.checkbox(&mut self.settings.disable_oled_mura, "Disable OLED Mura Correction"), &["disable_oled_mura"]);
+        });
+        
+        unapplied.mark(ui.checkbox(&mut self.settings.debug_logging, "Enable Debug Logging"), &["debug_logging"]);
+    }
+    
+    fn show_profiles_tab(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Profiles");
+        ui.separator();
+        ui.label("Install paths, startup programs, core masks and log paths stay on this machine and are never exported.");
+        
+        self.show_layers(ui);
+        
+        ui.group(|ui| {
+            ui.label("File");
+            ui.horizontal(|ui| {
+                ui.label("Path (.json or .toml):");
+                ui.add(egui::TextEdit::singleline(&mut self.profile_path).desired_width(300.0));
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Export").clicked() {
+                    self.export_profile();
+                }
+                if ui.button("Import...").clicked() {
+                    let path = PathBuf::from(&self.profile_path);
+                    self.preview_import(profile_io::read_file(&path), path.display().to_string());
+                }
+            });
+        });
+        
+        ui.group(|ui| {
+            ui.label("Share String");
+            if ui.button("Copy Share String").clicked() {
+                match profile_io::to_share_string(&self.settings) {
+                    Ok(text) => {
+                        ui.ctx().output_mut(|o| o.copied_text = text);
+                        self.profile_status = Some(String::from("Share string copied to the clipboard"));
+                    }
+                    Err(e) => self.profile_status = Some(format!("Could not create share string: {}", e)),
+                }
+            }
+            ui.horizontal(|ui| {
+                ui.label("Paste:");
+                ui.add(egui::TextEdit::singleline(&mut self.share_input).desired_width(400.0));
+                if ui.add_enabled(!self.share_input.trim().is_empty(), egui::Button::new("Import...")).clicked() {
+                    let fields = profile_io::parse_share_string(&self.share_input);
+                    self.preview_import(fields, String::from("share string"));
+                }
+            });
         });
         
-        ui.checkbox(&mut self.settings.debug_logging, "Enable Debug Logging");
+        if let Some(status) = &self.profile_status {
+            ui.label(status);
+        }
+        
+        let preview = match &self.import_preview {
+            Some(preview) => preview,
+            None => return,
+        };
+        let mut decision = None;
+        ui.group(|ui| {
+            ui.label(format!("Import from {}", preview.source));
+            if preview.changes.is_empty() {
+                ui.label("Nothing would change.");
+            } else {
+                let current = settings_diff::to_fields(&self.settings);
+                egui::Grid::new("import_preview_grid").striped(true).show(ui, |ui| {
+                    ui.label("Setting");
+                    ui.label("Current");
+                    ui.label("Imported");
+                    ui.end_row();
+                    
+                    for change in &preview.changes {
+                        ui.label(&change.field);
+                        ui.label(settings_diff::display_value(current.get(&change.field).unwrap_or(&change.from)));
+                        ui.label(settings_diff::display_value(&change.to));
+                        ui.end_row();
+                    }
+                });
+            }
+            
+            if !preview.issues.is_empty() {
+                ui.add_space(5.0);
+                ui.colored_label(egui::Color32::YELLOW, format!("{} value(s) skipped", preview.issues.len()));
+                egui::Grid::new("import_issue_grid").striped(true).show(ui, |ui| {
+                    for issue in &preview.issues {
+                        ui.label(&issue.field);
+                        ui.colored_label(egui::Color32::YELLOW, &issue.message);
+                        ui.end_row();
+                    }
+                });
+            }
+            
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!preview.changes.is_empty(), egui::Button::new("Accept Import")).clicked() {
+                    decision = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    decision = Some(false);
+                }
+            });
+        });
+        match decision {
+            Some(true) => self.accept_import(),
+            Some(false) => self.import_preview = None,
+            None => {}
+        }
+    }
+    
+    fn show_layers(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.label("Layers");
+            ui.label("Effective values stack default, base (the other tabs), the selected game profile and temporary overrides, later layers winning.");
+            
+            let mut select = None;
+            ui.horizontal(|ui| {
+                ui.label("Game profile:");
+                let selected = if self.settings.active_game_profile.is_empty() { "None" } else { self.settings.active_game_profile.as_str() };
+                egui::ComboBox::from_id_source("game_profile").selected_text(selected).show_ui(ui, |ui| {
+                    if ui.selectable_label(self.settings.active_game_profile.is_empty(), "None").clicked() {
+                        select = Some(String::from(""));
+                    }
+                    for profile in &self.game_profiles {
+                        let label = format!("{} ({} field(s))", profile.name, profile.fields.len());
+                        if ui.selectable_label(profile.name == self.settings.active_game_profile, label).clicked() {
+                            select = Some(profile.name.clone());
+                        }
+                    }
+                });
+                if ui.add_enabled(!self.settings.active_game_profile.is_empty(), egui::Button::new("Delete")).clicked() {
+                    self.delete_game_profile();
+                }
+            });
+            if let Some(name) = select {
+                self.select_game_profile(&name);
+            }
+            ui.horizontal(|ui| {
+                ui.label("New game profile:");
+                ui.text_edit_singleline(&mut self.new_profile_name);
+                if ui.add_enabled(!self.new_profile_name.trim().is_empty(), egui::Button::new("Create")).clicked() {
+                    self.create_game_profile();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(format!("{} temporary override(s), not saved", self.overrides.len()));
+                if ui.add_enabled(!self.overrides.is_empty(), egui::Button::new("Clear Overrides")).clicked() {
+                    info!("Cleared {} temporary override(s)", self.overrides.len());
+                    self.overrides.clear();
+                    self.resolve_layers();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Filter:");
+                ui.text_edit_singleline(&mut self.layer_field);
+                ui.checkbox(&mut self.show_default_layer, "Show values left at their defaults");
+            });
+            
+            let has_game = self.active_game_profile().is_some();
+            let filter = self.layer_field.trim().to_lowercase();
+            let fields = settings_diff::to_fields(&self.layers.settings);
+            let mut start_edit = None;
+            let mut commit = false;
+            let mut cancel = false;
+            let mut clear = None;
+            egui::ScrollArea::vertical().max_height(300.0).id_source("layers_scroll").show(ui, |ui| {
+                egui::Grid::new("layers_grid").striped(true).show(ui, |ui| {
+                    ui.label("Setting");
+                    ui.label("Effective");
+                    ui.label("From");
+                    ui.end_row();
+                    
+                    for (field, value) in &fields {
+                        let source = self.layers.source(field);
+                        if field == "active_game_profile" || (source == profiles::Layer::Default && !self.show_default_layer) || !field.contains(&filter) {
+                            continue;
+                        }
+                        ui.label(field);
+                        match &mut self.layer_edit {
+                            Some((_, edit_field, text)) if edit_field == field => {
+                                ui.text_edit_singleline(text);
+                            }
+                            _ => {
+                                ui.label(settings_diff::display_value(value));
+                            }
+                        }
+                        let color = match source {
+                            profiles::Layer::Default => egui::Color32::GRAY,
+                            profiles::Layer::Base => egui::Color32::WHITE,
+                            profiles::Layer::Game => egui::Color32::LIGHT_BLUE,
+                            profiles::Layer::Override => egui::Color32::YELLOW,
+                        };
+                        ui.colored_label(color, source.label());
+                        ui.horizontal(|ui| {
+                            match &self.layer_edit {
+                                Some((layer, edit_field, _)) if edit_field == field => {
+                                    if ui.button(format!("Set in {}", layer.label())).clicked() {
+                                        commit = true;
+                                    }
+                                    if ui.button("Cancel").clicked() {
+                                        cancel = true;
+                                    }
+                                }
+                                _ => {
+                                    if ui.add_enabled(has_game, egui::Button::new("Game...")).clicked() {
+                                        start_edit = Some((profiles::Layer::Game, field.clone(), value.to_string()));
+                                    }
+                                    if ui.button("Override...").clicked() {
+                                        start_edit = Some((profiles::Layer::Override, field.clone(), value.to_string()));
+                                    }
+                                    if matches!(source, profiles::Layer::Game | profiles::Layer::Override) && ui.button("Remove").clicked() {
+                                        clear = Some((source, field.clone()));
+                                    }
+                                }
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+            
+            if let Some(edit) = start_edit {
+                self.layer_edit = Some(edit);
+            }
+            if cancel {
+                self.layer_edit = None;
+            }
+            if commit {
+                if let Some((layer, field, text)) = self.layer_edit.take() {
+                    if let Err(e) = self.set_layer_value(layer, &field, Some(profiles::parse_value(&text))) {
+                        self.profile_status = Some(format!("{}: {}", field, e));
+                        self.layer_edit = Some((layer, field, text));
+                    }
+                }
+            }
+            if let Some((layer, field)) = clear {
+                if let Err(e) = self.set_layer_value(layer, &field, None) {
+                    self.profile_status = Some(format!("{}: {}", field, e));
+                }
+            }
+        });
+    }
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
+fn run_oculus_killer(
+    swap: &file_swap::FileSwap,
+    enable: bool,
+    replacement: Option<&Path>,
+    services: &services::ServiceController,
+    report: &mut report::ApplyReport,
+) -> Option<String> {
+    // Checked here rather than when queued, so the result of any swap queued before this one counts.
+    let done = if enable {
+        swap.status().and_then(|status| Ok(status == file_swap::SwapStatus::Swapped && swap.installed_matches(replacement)?))
+    } else {
+        swap.status().map(|status| status == file_swap::SwapStatus::Original)
+    };
+    match done {
+        Ok(true) => return None,
+        Ok(false) => {}
+        Err(e) => {
+            let message = format!("Cannot read OculusDash state: {}", e);
+            report.record("OculusKiller", swap.target().display().to_string(), Err(e));
+            return Some(message);
+        }
+    }
+    
+    #[cfg(target_os = "windows")]
+    if let Err(e) = services.stop_and_wait(&services::OCULUS_SERVICE, SERVICE_TIMEOUT) {
+        let message = format!("Could not stop {}: {}", services::OCULUS_SERVICE.name, e);
+        report.record("Stop service", services::OCULUS_SERVICE.name, Err(e));
+        return Some(message);
+    }
+    
+    let result = if enable {
+        swap.install(replacement)
+    } else {
+        swap.restore()
+    };
+    
+    #[cfg(target_os = "windows")]
+    report.record(
+        "Start service",
+        services::OCULUS_SERVICE.name,
+        services.start_and_wait(&services::OCULUS_SERVICE, SERVICE_TIMEOUT),
+    );
+    
+    let target = swap.target().display().to_string();
+    match result {
+        Ok(message) => {
+            info!("OculusKiller: {}", message);
+            report.record("OculusKiller", target, Ok(()));
+            
+            #[cfg(target_os = "windows")]
+            if enable {
+                let path = "SOFTWARE\\WOW6432Node\\Oculus VR, LLC\\Oculus\\Config";
+                report.record(
+                    "Disable updates",
+                    registry::display_path(registry::Hive::LocalMachine, path, "CoreChannel"),
+                    registry::write_value(registry::Hive::LocalMachine, path, "CoreChannel", &"NO_UPDATES"),
+                );
+            }
+            Some(message)
+        }
+        Err(e) => {
+            let message = format!("OculusKiller {}", e);
+            report.record("OculusKiller", target, Err(std::io::Error::new(e.error.kind(), e.to_string())));
+            Some(message)
+        }
+    }
+}
+
+// Fields edited since the last Apply, with their applied values, so the tabs can outline those widgets.
+struct Unapplied {
+    fields: Vec<(String, String)>,
+}
+
+impl Unapplied {
+    fn new(history: &history::SettingsHistory) -> Self {
+        if history.unapplied().is_empty() {
+            return Self { fields: Vec::new() };
+        }
+        let applied = settings_diff::to_fields(history.applied());
+        let fields = history.unapplied().iter()
+            .map(|field| {
+                let value = applied.get(field).map(settings_diff::display_value).unwrap_or_default();
+                (field.clone(), value)
+            })
+            .collect();
+        Self { fields }
+    }
+    
+    fn mark(&self, response: egui::Response, fields: &[&str]) -> egui::Response {
+        let changed: Vec<String> = self.fields.iter()
+            .filter(|(field, _)| fields.contains(&field.as_str()))
+            .map(|(field, applied)| format!("{}: applied value {}", field, applied))
+            .collect();
+        if changed.is_empty() {
+            return response;
+        }
+        response.ctx.layer_painter(response.layer_id).rect_stroke(
+            response.rect.expand(2.0),
+            2.0,
+            egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE),
+        );
+        response.on_hover_text(format!("Not applied yet\n{}", changed.join("\n")))
+    }
+}
+
+fn priority_status_lines(report: &report::ApplyReport) -> Vec<String> {
+    report.entries.iter()
+        .filter(|entry| entry.step.ends_with("priority"))
+        .map(|entry| match &entry.result {
+            Ok(()) => format!("{}: {} applied", entry.target, entry.step),
+            Err(e) if e.kind == std::io::ErrorKind::PermissionDenied => {
+                format!("{}: insufficient privileges for {} - {}", entry.target, entry.step, e.message)
+            }
+            Err(e) => format!("{}: {} failed - {}", entry.target, entry.step, e.message),
+        })
+        .collect()
+}
+
+// Waits between kill and respawn so the old instance has let go of its ports and devices.
+fn restart_process(system: &Mutex<System>, name: &str, launcher: Option<launch::RuntimeLauncher>) -> std::io::Result<()> {
+    let launcher = launcher.ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::Unsupported, format!("{} comes back with its runtime and cannot be restarted alone", name))
+    })?;
+    kill_process(system, name)?;
+    std::thread::sleep(Duration::from_millis(500));
+    launch::spawn_launcher(&launcher)
+}
+
+#[cfg(target_os = "windows")]
+fn kill_process(_system: &Mutex<System>, name: &str) -> std::io::Result<()> {
+    report::check_output(Command::new("taskkill").args(["/F", "/IM", name]).output())
+}
+
+// SIGKILL, like taskkill /F; a stopped process would not act on SIGTERM.
+#[cfg(not(target_os = "windows"))]
+fn kill_process(system: &Mutex<System>, name: &str) -> std::io::Result<()> {
+    let sys = system.lock().unwrap();
+    let mut found = false;
+    for p in sys.processes_by_exact_name(name) {
+        found = true;
+        if !p.kill() {
+            return Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, format!("could not kill {} (pid {})", name, p.pid())));
+        }
+    }
+    if found {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} is not running", name)))
+    }
+}
+
+fn hotkey_binding_id(index: usize) -> egui::Id {
+    egui::Id::new(("hotkey_binding", index))
+}
+
+fn is_runtime_process(name: &str) -> bool {
+    RUNTIME_PROCESSES.iter().any(|runtime| name.contains(runtime))
+}
+
+fn init_logging(owns_logs: bool) {
+    if !owns_logs {
+        let _ = WriteLogger::init(LevelFilter::Debug, Config::default(), std::io::stderr());
+        return;
+    }
+    let dir = config::log_dir();
+    if let Err(e) = fs::create_dir_all(&dir) {
+        eprintln!("Failed to create {}: {}", dir.display(), e);
+        return;
+    }
+    rotate_logs(&dir);
+    match fs::File::create(dir.join("vr_suite.log")) {
+        Ok(file) => {
+            let _ = WriteLogger::init(LevelFilter::Debug, Config::default(), file);
+        }
+        Err(e) => eprintln!("Failed to create log file in {}: {}", dir.display(), e),
+    }
+}
+
+fn rotate_logs(dir: &Path) {
+    for i in (1..KEPT_LOG_FILES - 1).rev() {
+        let from = dir.join(format!("vr_suite.{}.log", i));
+        if from.exists() {
+            let _ = fs::rename(&from, dir.join(format!("vr_suite.{}.log", i + 1)));
+        }
+    }
+    let current = dir.join("vr_suite.log");
+    if current.exists() {
+        let _ = fs::rename(&current, dir.join("vr_suite.1.log"));
     }
 }
 
 fn main() -> Result<(), eframe::Error> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+    
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([900.0, 700.0])
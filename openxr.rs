@@ -0,0 +1,116 @@
+use serde::Serialize;
+#[cfg(not(target_os = "windows"))]
+use std::path::PathBuf;
+
+#[derive(Serialize, Default)]
+pub struct OpenXrDiscovery {
+    pub active_runtime: Option<String>,
+    pub available_runtimes: Vec<String>,
+    pub implicit_layers: Vec<String>,
+    pub explicit_layers: Vec<String>,
+}
+
+#[cfg(target_os = "windows")]
+pub fn discover() -> OpenXrDiscovery {
+    use winreg::enums::*;
+    use winreg::RegKey;
+    
+    let mut result = OpenXrDiscovery::default();
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    
+    if let Ok(key) = hklm.open_subkey("SOFTWARE\\Khronos\\OpenXR\\1") {
+        result.active_runtime = key.get_value::<String, _>("ActiveRuntime").ok();
+    }
+    if let Ok(key) = hklm.open_subkey("SOFTWARE\\Khronos\\OpenXR\\1\\AvailableRuntimes") {
+        result.available_runtimes = key.enum_values().filter_map(|v| v.ok()).map(|(name, _)| name).collect();
+    }
+    
+    for root in [&hklm, &hkcu] {
+        if let Ok(key) = root.open_subkey("SOFTWARE\\Khronos\\OpenXR\\1\\ApiLayers\\Implicit") {
+            result.implicit_layers.extend(key.enum_values().filter_map(|v| v.ok()).map(|(name, _)| name));
+        }
+        if let Ok(key) = root.open_subkey("SOFTWARE\\Khronos\\OpenXR\\1\\ApiLayers\\Explicit") {
+            result.explicit_layers.extend(key.enum_values().filter_map(|v| v.ok()).map(|(name, _)| name));
+        }
+    }
+    
+    result
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn discover() -> OpenXrDiscovery {
+    let mut result = OpenXrDiscovery::default();
+    
+    if let Ok(path) = std::env::var("XR_RUNTIME_JSON") {
+        result.active_runtime = Some(path);
+    }
+    
+    for dir in config_dirs() {
+        let manifest = dir.join("openxr/1/active_runtime.json");
+        if manifest.exists() {
+            let resolved = std::fs::canonicalize(&manifest).unwrap_or(manifest);
+            let resolved = resolved.display().to_string();
+            if result.active_runtime.is_none() {
+                result.active_runtime = Some(resolved.clone());
+            }
+            if !result.available_runtimes.contains(&resolved) {
+                result.available_runtimes.push(resolved);
+            }
+        }
+    }
+    
+    for dir in config_dirs().into_iter().chain(data_dirs()) {
+        result.implicit_layers.extend(list_manifests(&dir.join("openxr/1/api_layers/implicit.d")));
+        result.explicit_layers.extend(list_manifests(&dir.join("openxr/1/api_layers/explicit.d")));
+    }
+    
+    result
+}
+
+#[cfg(not(target_os = "windows"))]
+fn config_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = xdg_home("XDG_CONFIG_HOME", ".config") {
+        dirs.push(home);
+    }
+    let system = std::env::var("XDG_CONFIG_DIRS").unwrap_or_else(|_| String::from("/etc/xdg"));
+    dirs.extend(system.split(':').filter(|s| !s.is_empty()).map(PathBuf::from));
+    dirs.push(PathBuf::from("/etc"));
+    dirs
+}
+
+#[cfg(not(target_os = "windows"))]
+fn data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = xdg_home("XDG_DATA_HOME", ".local/share") {
+        dirs.push(home);
+    }
+    let system = std::env::var("XDG_DATA_DIRS").unwrap_or_else(|_| String::from("/usr/local/share:/usr/share"));
+    dirs.extend(system.split(':').filter(|s| !s.is_empty()).map(PathBuf::from));
+    dirs
+}
+
+#[cfg(not(target_os = "windows"))]
+fn xdg_home(var: &str, fallback: &str) -> Option<PathBuf> {
+    match std::env::var(var) {
+        Ok(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => std::env::var("HOME").ok().map(|home| PathBuf::from(home).join(fallback)),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn list_manifests(dir: &std::path::Path) -> Vec<String> {
+    let mut manifests: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .map(|p| p.display().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    manifests.sort();
+    manifests
+}
@@ -0,0 +1,338 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::info;
+
+use crate::PowerPlan;
+
+const PROFILES_DAEMON_NAME: &str = "net.hadess.PowerProfiles";
+const PROFILES_DAEMON_PATH: &str = "/net/hadess/PowerProfiles";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub enum PowerState {
+    ProfilesDaemon(String),
+    CpuFreq(Vec<PolicyState>),
+}
+
+impl PowerState {
+    // The elevated helper restores states sent by the app, so only known policies and offered values pass.
+    pub fn validate(&self, cpufreq: &CpuFreq) -> io::Result<()> {
+        let invalid = |message: String| Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        match self {
+            PowerState::ProfilesDaemon(profile) => {
+                if !["performance", "balanced", "power-saver"].contains(&profile.as_str()) {
+                    return invalid(format!("unknown power profile '{}'", profile));
+                }
+            }
+            PowerState::CpuFreq(states) => {
+                let policies = cpufreq.policies();
+                for state in states {
+                    if !policies.contains(&state.path) {
+                        return invalid(format!("{} is not a cpufreq policy", state.path.display()));
+                    }
+                    if !read_list(&state.path.join("scaling_available_governors")).contains(&state.governor) {
+                        return invalid(format!("governor '{}' is not available", state.governor));
+                    }
+                    if let Some(preference) = &state.energy_preference {
+                        if !read_list(&state.path.join("energy_performance_available_preferences")).contains(preference) {
+                            return invalid(format!("energy preference '{}' is not available", preference));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PolicyState {
+    path: PathBuf,
+    governor: String,
+    energy_preference: Option<String>,
+}
+
+pub struct CpuFreq {
+    root: PathBuf,
+}
+
+impl CpuFreq {
+    pub fn new() -> Self {
+        Self::with_root("/sys/devices/system/cpu")
+    }
+    
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+    
+    fn policies(&self) -> Vec<PathBuf> {
+        let mut policies: Vec<PathBuf> = fs::read_dir(self.root.join("cpufreq"))
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_name().to_string_lossy().starts_with("policy"))
+                    .map(|e| e.path())
+                    .collect()
+            })
+            .unwrap_or_default();
+        policies.sort();
+        policies
+    }
+    
+    pub fn snapshot(&self) -> io::Result<Vec<PolicyState>> {
+        let mut states = Vec::new();
+        for path in self.policies() {
+            let governor = read_trimmed(&path.join("scaling_governor"))?;
+            let energy_preference = read_trimmed(&path.join("energy_performance_preference")).ok();
+            states.push(PolicyState { path, governor, energy_preference });
+        }
+        Ok(states)
+    }
+    
+    pub fn apply(&self, plan: &PowerPlan) -> io::Result<()> {
+        let policies = self.policies();
+        if policies.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no cpufreq policies found"));
+        }
+        
+        for path in policies {
+            let governors = read_list(&path.join("scaling_available_governors"));
+            if let Some(governor) = governor_for(plan, &governors) {
+                fs::write(path.join("scaling_governor"), governor)?;
+            }
+            
+            // With the performance governor, intel_pstate and amd-pstate pin the
+            // preference themselves and reject writes with EBUSY.
+            if *plan == PowerPlan::HighPerformance {
+                continue;
+            }
+            let preferences = read_list(&path.join("energy_performance_available_preferences"));
+            if let Some(preference) = preference_for(plan, &preferences) {
+                fs::write(path.join("energy_performance_preference"), preference)?;
+            }
+        }
+        Ok(())
+    }
+    
+    // Reads the plan back from the first policy; apply() sets all of them alike.
+    pub fn current_plan(&self) -> Option<PowerPlan> {
+        let path = self.policies().into_iter().next()?;
+        let governor = read_trimmed(&path.join("scaling_governor")).ok()?;
+        let preference = read_trimmed(&path.join("energy_performance_preference")).unwrap_or_default();
+        Some(match governor.as_str() {
+            "performance" => PowerPlan::HighPerformance,
+            "conservative" => PowerPlan::PowerSaver,
+            "powersave" if preference == "power" || preference == "balance_power" => PowerPlan::PowerSaver,
+            _ => PowerPlan::Balanced,
+        })
+    }
+    
+    pub fn restore(&self, states: &[PolicyState]) -> io::Result<()> {
+        for state in states {
+            fs::write(state.path.join("scaling_governor"), &state.governor)?;
+            if let Some(preference) = &state.energy_preference {
+                fs::write(state.path.join("energy_performance_preference"), preference)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct PowerManager {
+    cpufreq: CpuFreq,
+    saved: Option<PowerState>,
+}
+
+impl PowerManager {
+    pub fn new(cpufreq: CpuFreq) -> Self {
+        Self { cpufreq, saved: None }
+    }
+    
+    pub fn apply(&mut self, plan: &PowerPlan) -> io::Result<()> {
+        // The previous state is saved before writing, so restore() also undoes a partly applied plan.
+        if let Some(current) = profiles_daemon::active_profile() {
+            let profile = profile_for(plan);
+            if self.saved.is_none() {
+                self.saved = Some(PowerState::ProfilesDaemon(current));
+            }
+            profiles_daemon::set_active_profile(profile)?;
+            info!("Power profile set to {}", profile);
+            return Ok(());
+        }
+        
+        let snapshot = self.cpufreq.snapshot()?;
+        if self.saved.is_none() {
+            self.saved = Some(PowerState::CpuFreq(snapshot));
+        }
+        self.cpufreq.apply(plan)?;
+        info!("CPU governor set for {} plan", profile_for(plan));
+        Ok(())
+    }
+    
+    // The saved state is kept when restoring fails, so it can be handed to the elevated helper.
+    pub fn restore(&mut self) -> io::Result<()> {
+        if let Some(state) = &self.saved {
+            self.restore_state(state)?;
+            self.saved = None;
+        }
+        Ok(())
+    }
+    
+    pub fn take_saved(&mut self) -> Option<PowerState> {
+        self.saved.take()
+    }
+    
+    pub fn restore_state(&self, state: &PowerState) -> io::Result<()> {
+        match state {
+            PowerState::ProfilesDaemon(profile) => {
+                profiles_daemon::set_active_profile(profile)?;
+                info!("Power profile restored to {}", profile);
+            }
+            PowerState::CpuFreq(states) => {
+                self.cpufreq.restore(states)?;
+                info!("CPU governor restored");
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn active_plan() -> Option<(PowerPlan, &'static str)> {
+    if let Some(profile) = profiles_daemon::active_profile() {
+        let plan = match profile.as_str() {
+            "performance" => PowerPlan::HighPerformance,
+            "power-saver" => PowerPlan::PowerSaver,
+            _ => PowerPlan::Balanced,
+        };
+        return Some((plan, "power-profiles-daemon"));
+    }
+    CpuFreq::new().current_plan().map(|plan| (plan, "cpufreq"))
+}
+
+fn governor_for<'a>(plan: &PowerPlan, available: &'a [String]) -> Option<&'a str> {
+    let preferred: &[&str] = match plan {
+        PowerPlan::HighPerformance => &["performance"],
+        PowerPlan::Balanced => &["schedutil", "ondemand", "powersave"],
+        PowerPlan::PowerSaver => &["powersave", "conservative"],
+    };
+    preferred
+        .iter()
+        .find_map(|wanted| available.iter().find(|g| g == wanted))
+        .map(String::as_str)
+}
+
+fn preference_for<'a>(plan: &PowerPlan, available: &'a [String]) -> Option<&'a str> {
+    let preferred: &[&str] = match plan {
+        PowerPlan::HighPerformance => &["performance"],
+        PowerPlan::Balanced => &["balance_performance", "default"],
+        PowerPlan::PowerSaver => &["power", "balance_power"],
+    };
+    preferred
+        .iter()
+        .find_map(|wanted| available.iter().find(|p| p == wanted))
+        .map(String::as_str)
+}
+
+fn profile_for(plan: &PowerPlan) -> &'static str {
+    match plan {
+        PowerPlan::HighPerformance => "performance",
+        PowerPlan::Balanced => "balanced",
+        PowerPlan::PowerSaver => "power-saver",
+    }
+}
+
+fn read_trimmed(path: &Path) -> io::Result<String> {
+    Ok(fs::read_to_string(path)?.trim().to_string())
+}
+
+fn read_list(path: &Path) -> Vec<String> {
+    read_trimmed(path)
+        .map(|s| s.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+mod profiles_daemon {
+    use std::io;
+    use zbus::blocking::{Connection, Proxy};
+    
+    use super::{PROFILES_DAEMON_NAME, PROFILES_DAEMON_PATH};
+    
+    fn proxy(conn: &Connection) -> zbus::Result<Proxy<'_>> {
+        Proxy::new(conn, PROFILES_DAEMON_NAME, PROFILES_DAEMON_PATH, PROFILES_DAEMON_NAME)
+    }
+    
+    pub fn active_profile() -> Option<String> {
+        let conn = Connection::system().ok()?;
+        let proxy = proxy(&conn).ok()?;
+        proxy.get_property::<String>("ActiveProfile").ok()
+    }
+    
+    pub fn set_active_profile(profile: &str) -> io::Result<()> {
+        let to_io = |e: zbus::Error| io::Error::other(e.to_string());
+        let conn = Connection::system().map_err(to_io)?;
+        let proxy = proxy(&conn).map_err(to_io)?;
+        proxy
+            .set_property("ActiveProfile", profile)
+            .map_err(|e| io::Error::other(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn fake_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("vr_suite_power_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for policy in ["policy0", "policy1"] {
+            let dir = root.join("cpufreq").join(policy);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("scaling_governor"), "powersave\n").unwrap();
+            fs::write(dir.join("scaling_available_governors"), "performance powersave\n").unwrap();
+            fs::write(dir.join("energy_performance_preference"), "balance_power\n").unwrap();
+            fs::write(dir.join("energy_performance_available_preferences"), "performance balance_performance balance_power power\n").unwrap();
+        }
+        root
+    }
+    
+    #[test]
+    fn apply_and_restore_round_trip() {
+        let root = fake_root("round_trip");
+        let cpufreq = CpuFreq::with_root(&root);
+        let snapshot = cpufreq.snapshot().unwrap();
+        
+        let governor = root.join("cpufreq").join("policy1").join("scaling_governor");
+        cpufreq.apply(&PowerPlan::HighPerformance).unwrap();
+        assert_eq!(read_trimmed(&governor).unwrap(), "performance");
+        
+        cpufreq.restore(&snapshot).unwrap();
+        assert_eq!(read_trimmed(&governor).unwrap(), "powersave");
+        fs::remove_dir_all(&root).unwrap();
+    }
+    
+    #[test]
+    fn validate_rejects_foreign_paths_and_values() {
+        let root = fake_root("validate");
+        let cpufreq = CpuFreq::with_root(&root);
+        let snapshot = cpufreq.snapshot().unwrap();
+        assert!(PowerState::CpuFreq(snapshot.clone()).validate(&cpufreq).is_ok());
+        
+        let mut foreign = snapshot.clone();
+        foreign[0].path = root.join("cpufreq").join("..").join("policy0");
+        assert!(PowerState::CpuFreq(foreign).validate(&cpufreq).is_err());
+        
+        let mut governor = snapshot.clone();
+        governor[1].governor = String::from("userspace");
+        assert!(PowerState::CpuFreq(governor).validate(&cpufreq).is_err());
+        
+        let mut preference = snapshot;
+        preference[0].energy_preference = Some(String::from("root:x:0:0"));
+        assert!(PowerState::CpuFreq(preference).validate(&cpufreq).is_err());
+        
+        assert!(PowerState::ProfilesDaemon(String::from("balanced")).validate(&cpufreq).is_ok());
+        assert!(PowerState::ProfilesDaemon(String::from("turbo")).validate(&cpufreq).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
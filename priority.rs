@@ -0,0 +1,272 @@
+use std::io;
+
+use crate::{CpuPriority, GPUPriority, IoPriority};
+
+#[cfg(target_os = "linux")]
+const REALTIME_PRIORITY: libc::c_int = 10;
+#[cfg(target_os = "linux")]
+const HIGH_NICE: libc::c_int = -10;
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+
+#[cfg(target_os = "windows")]
+pub fn win32_error(e: windows::core::Error) -> io::Error {
+    io::Error::from_raw_os_error(e.code().0 & 0xFFFF)
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_process_priority(pid: u32, priority: &CpuPriority, _io_priority: &IoPriority) -> io::Result<()> {
+    use windows::Win32::Foundation::*;
+    use windows::Win32::System::Threading::*;
+    
+    let class = match priority {
+        CpuPriority::Realtime => REALTIME_PRIORITY_CLASS,
+        CpuPriority::High => HIGH_PRIORITY_CLASS,
+        CpuPriority::Normal => NORMAL_PRIORITY_CLASS,
+    };
+    unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION, false, pid).map_err(win32_error)?;
+        let result = SetPriorityClass(handle, class);
+        let _ = CloseHandle(handle);
+        result.map_err(win32_error)
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn describe_priority(pid: u32) -> Option<String> {
+    use windows::Win32::Foundation::*;
+    use windows::Win32::System::Threading::*;
+    
+    let class = unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let class = GetPriorityClass(handle);
+        let _ = CloseHandle(handle);
+        class
+    };
+    let name = match PROCESS_CREATION_FLAGS(class) {
+        REALTIME_PRIORITY_CLASS => "Realtime",
+        HIGH_PRIORITY_CLASS => "High",
+        ABOVE_NORMAL_PRIORITY_CLASS => "Above Normal",
+        NORMAL_PRIORITY_CLASS => "Normal",
+        BELOW_NORMAL_PRIORITY_CLASS => "Below Normal",
+        IDLE_PRIORITY_CLASS => "Idle",
+        _ => return None,
+    };
+    
+    match describe_gpu_priority(pid) {
+        Some(gpu) => Some(format!("{}, GPU {}", name, gpu)),
+        None => Some(name.to_string()),
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn gpu_priority_supported() -> bool {
+    true
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_gpu_priority(pid: u32, priority: &GPUPriority) -> io::Result<()> {
+    use windows::Wdk::Graphics::Direct3D::*;
+    use windows::Win32::Foundation::*;
+    use windows::Win32::System::Threading::*;
+    
+    let class = match priority {
+        GPUPriority::Realtime => D3DKMT_SCHEDULINGPRIORITYCLASS_REALTIME,
+        GPUPriority::High => D3DKMT_SCHEDULINGPRIORITYCLASS_HIGH,
+        GPUPriority::Normal => D3DKMT_SCHEDULINGPRIORITYCLASS_NORMAL,
+    };
+    let status = unsafe {
+        let handle = OpenProcess(PROCESS_SET_INFORMATION | PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+            .map_err(win32_error)?;
+        let status = D3DKMTSetProcessSchedulingPriorityClass(handle, class);
+        let _ = CloseHandle(handle);
+        status
+    };
+    if status.is_ok() {
+        Ok(())
+    } else if status == STATUS_PRIVILEGE_NOT_HELD {
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "realtime GPU scheduling needs SeIncreaseBasePriorityPrivilege (run as administrator)",
+        ))
+    } else {
+        Err(io::Error::other(format!("D3DKMTSetProcessSchedulingPriorityClass failed: 0x{:08X}", status.0)))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn describe_gpu_priority(pid: u32) -> Option<&'static str> {
+    use windows::Wdk::Graphics::Direct3D::*;
+    use windows::Win32::Foundation::*;
+    use windows::Win32::System::Threading::*;
+    
+    let mut class = D3DKMT_SCHEDULINGPRIORITYCLASS::default();
+    let status = unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let status = D3DKMTGetProcessSchedulingPriorityClass(handle, &mut class);
+        let _ = CloseHandle(handle);
+        status
+    };
+    if !status.is_ok() {
+        return None;
+    }
+    match class {
+        D3DKMT_SCHEDULINGPRIORITYCLASS_REALTIME => Some("Realtime"),
+        D3DKMT_SCHEDULINGPRIORITYCLASS_HIGH => Some("High"),
+        D3DKMT_SCHEDULINGPRIORITYCLASS_ABOVE_NORMAL => Some("Above Normal"),
+        D3DKMT_SCHEDULINGPRIORITYCLASS_NORMAL => Some("Normal"),
+        D3DKMT_SCHEDULINGPRIORITYCLASS_BELOW_NORMAL => Some("Below Normal"),
+        D3DKMT_SCHEDULINGPRIORITYCLASS_IDLE => Some("Idle"),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn gpu_priority_supported() -> bool {
+    false
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn set_gpu_priority(_pid: u32, _priority: &GPUPriority) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "GPU scheduling priority is not supported on this platform",
+    ))
+}
+
+#[cfg(target_os = "linux")]
+pub fn for_each_thread(pid: u32, mut f: impl FnMut(libc::pid_t) -> io::Result<()>) -> io::Result<()> {
+    for task in std::fs::read_dir(format!("/proc/{}/task", pid))?.flatten() {
+        let tid = match task.file_name().to_string_lossy().parse::<libc::pid_t>() {
+            Ok(tid) => tid,
+            Err(_) => continue,
+        };
+        if let Err(e) = f(tid) {
+            // Threads can exit while we walk the list.
+            if e.raw_os_error() != Some(libc::ESRCH) {
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_process_priority(pid: u32, priority: &CpuPriority, io_priority: &IoPriority) -> io::Result<()> {
+    let (policy, rt_priority, nice) = match priority {
+        CpuPriority::Realtime => (libc::SCHED_RR, REALTIME_PRIORITY, HIGH_NICE),
+        CpuPriority::High => (libc::SCHED_OTHER, 0, HIGH_NICE),
+        CpuPriority::Normal => (libc::SCHED_OTHER, 0, 0),
+    };
+    
+    if policy == libc::SCHED_RR && !is_root() {
+        let limit = rtprio_limit(pid)?;
+        if limit < rt_priority as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "SCHED_RR priority {} exceeds RLIMIT_RTPRIO ({}); run as root or raise the limit in /etc/security/limits.conf",
+                    rt_priority, limit
+                ),
+            ));
+        }
+    }
+    
+    let ioprio = match io_priority {
+        IoPriority::Normal => (2 << IOPRIO_CLASS_SHIFT) | 4,
+        IoPriority::High => 2 << IOPRIO_CLASS_SHIFT,
+        IoPriority::Realtime => (1 << IOPRIO_CLASS_SHIFT) | 4,
+    };
+    
+    for_each_thread(pid, |tid| {
+        let param = libc::sched_param { sched_priority: rt_priority };
+        if unsafe { libc::sched_setscheduler(tid, policy, &param) } != 0 {
+            return Err(privilege_hint(io::Error::last_os_error(), "changing the scheduling policy needs CAP_SYS_NICE"));
+        }
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) } != 0 {
+            return Err(privilege_hint(io::Error::last_os_error(), "a negative nice value needs CAP_SYS_NICE or RLIMIT_NICE"));
+        }
+        if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid, ioprio) } != 0 {
+            return Err(privilege_hint(io::Error::last_os_error(), "the realtime I/O class needs CAP_SYS_ADMIN"));
+        }
+        Ok(())
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn describe_priority(pid: u32) -> Option<String> {
+    let pid = pid as libc::pid_t;
+    let policy = unsafe { libc::sched_getscheduler(pid) };
+    if policy < 0 {
+        return None;
+    }
+    
+    let mut description = match policy {
+        libc::SCHED_RR | libc::SCHED_FIFO => {
+            let mut param = libc::sched_param { sched_priority: 0 };
+            unsafe { libc::sched_getparam(pid, &mut param) };
+            let name = if policy == libc::SCHED_RR { "RR" } else { "FIFO" };
+            format!("{} {}", name, param.sched_priority)
+        }
+        _ => {
+            unsafe { *libc::__errno_location() = 0 };
+            let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid as libc::id_t) };
+            if nice == -1 && io::Error::last_os_error().raw_os_error().unwrap_or(0) != 0 {
+                return None;
+            }
+            format!("nice {}", nice)
+        }
+    };
+    
+    let ioprio = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid) };
+    if ioprio >= 0 {
+        let class = match ioprio >> IOPRIO_CLASS_SHIFT {
+            1 => "rt",
+            2 => "be",
+            3 => "idle",
+            _ => "none",
+        };
+        let level = ioprio & ((1 << IOPRIO_CLASS_SHIFT) - 1);
+        if class == "none" || class == "idle" {
+            description.push_str(&format!(", io {}", class));
+        } else {
+            description.push_str(&format!(", io {}/{}", class, level));
+        }
+    }
+    Some(description)
+}
+
+#[cfg(target_os = "linux")]
+fn is_root() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(target_os = "linux")]
+fn rtprio_limit(pid: u32) -> io::Result<u64> {
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    if unsafe { libc::prlimit(pid as libc::pid_t, libc::RLIMIT_RTPRIO, std::ptr::null(), &mut limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(limit.rlim_cur)
+}
+
+#[cfg(target_os = "linux")]
+fn privilege_hint(err: io::Error, hint: &str) -> io::Error {
+    if err.kind() == io::ErrorKind::PermissionDenied {
+        io::Error::new(io::ErrorKind::PermissionDenied, format!("{} ({})", err, hint))
+    } else {
+        err
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_process_priority(_pid: u32, _priority: &CpuPriority, _io_priority: &IoPriority) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "process priority is not supported on this platform"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn describe_priority(_pid: u32) -> Option<String> {
+    None
+}
@@ -0,0 +1,307 @@
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::Value;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::headsets;
+use crate::settings_diff::{self, FieldChange, Fields};
+use crate::VRSettings;
+
+const SHARE_PREFIX: &str = "vrsuite1:";
+// Share strings are pasted from chat; refuse anything that inflates past this.
+const MAX_SHARE_BYTES: u64 = 256 * 1024;
+
+// Paths, core masks and discovered installs only make sense on the machine they came from.
+pub const MACHINE_FIELDS: &[&str] = &[
+    "install_paths",
+    "install_path_overrides",
+    "startup_programs",
+    "custom_startup_program",
+    "oculus_killer_replacement",
+    "affinity_cores",
+    "autotune_frame_log",
+    "active_game_profile",
+    // Imported commands would run on this machine.
+    "hooks",
+    "apply_scripts",
+    "script_allowed_paths",
+    "script_registry_allowed",
+];
+
+// Mirrors the slider ranges in the UI; encode size and frame rate are checked against the headset instead.
+const RANGES: &[(&str, f64, f64)] = &[
+    ("render_scale", 0.5, 2.0),
+    ("encode_bitrate_mbps", 50.0, 500.0),
+    ("link_sharpening", 0.0, 1.0),
+    ("pixel_density", 0.5, 2.0),
+    ("fov_scale", 0.8, 1.2),
+    ("contrast", 0.5, 1.5),
+    ("saturation", 0.5, 1.5),
+    ("sharpening_amount", 0.0, 1.0),
+    ("super_sampling", 1.0, 2.0),
+    ("upscaling_scale", 0.5, 1.0),
+    ("runtime_start_timeout_seconds", 5.0, 120.0),
+    ("drift_monitor_interval_seconds", 5.0, 300.0),
+    ("autotune_headroom_percent", 0.0, 30.0),
+    ("autotune_min_render_scale", 0.5, 1.0),
+    ("autotune_max_render_scale", 1.0, 2.0),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProfileFormat {
+    Json,
+    Toml,
+}
+
+impl ProfileFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => ProfileFormat::Toml,
+            _ => ProfileFormat::Json,
+        }
+    }
+}
+
+pub struct ImportIssue {
+    pub field: String,
+    pub message: String,
+}
+
+pub struct ImportPreview {
+    pub source: String,
+    pub changes: Vec<FieldChange>,
+    pub issues: Vec<ImportIssue>,
+    // Only the fields that passed validation; these are what gets applied.
+    pub accepted: Fields,
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+pub fn shareable_fields(settings: &VRSettings) -> Fields {
+    let mut fields = settings_diff::to_fields(settings);
+    fields.retain(|key, _| !MACHINE_FIELDS.contains(&key.as_str()));
+    fields
+}
+
+pub fn export_file(settings: &VRSettings, path: &Path) -> io::Result<()> {
+    let fields = shareable_fields(settings);
+    let contents = match ProfileFormat::from_path(path) {
+        ProfileFormat::Json => serde_json::to_string_pretty(&fields)?,
+        ProfileFormat::Toml => toml::to_string_pretty(&fields).map_err(|e| invalid_data(e.to_string()))?,
+    };
+    fs::write(path, contents)
+}
+
+pub fn read_file(path: &Path) -> io::Result<Fields> {
+    let contents = fs::read_to_string(path)?;
+    match ProfileFormat::from_path(path) {
+        ProfileFormat::Json => serde_json::from_str(&contents).map_err(|e| invalid_data(e.to_string())),
+        ProfileFormat::Toml => toml::from_str(&contents).map_err(|e| invalid_data(e.to_string())),
+    }
+}
+
+pub fn to_share_string(settings: &VRSettings) -> io::Result<String> {
+    let json = serde_json::to_vec(&shareable_fields(settings))?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(&json)?;
+    let compressed = encoder.finish()?;
+    Ok(format!("{}{}", SHARE_PREFIX, base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed)))
+}
+
+pub fn parse_share_string(text: &str) -> io::Result<Fields> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let encoded = text
+        .strip_prefix(SHARE_PREFIX)
+        .ok_or_else(|| invalid_data(format!("share strings start with {}", SHARE_PREFIX)))?;
+    let compressed = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|e| invalid_data(format!("not a valid share string: {}", e)))?;
+    
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed.as_slice()).take(MAX_SHARE_BYTES + 1).read_to_end(&mut json)?;
+    if json.len() as u64 > MAX_SHARE_BYTES {
+        return Err(invalid_data("share string is too large"));
+    }
+    serde_json::from_slice(&json).map_err(|e| invalid_data(e.to_string()))
+}
+
+// Checks every imported field on its own so one bad value does not reject the whole profile.
+pub fn prepare_import(current: &VRSettings, imported: Fields, source: impl Into<String>) -> ImportPreview {
+    let known = settings_diff::to_fields(current);
+    let mut accepted = Fields::new();
+    let mut issues = Vec::new();
+    let mut issue = |field: &str, message: String| issues.push(ImportIssue { field: field.to_string(), message });
+    
+    for (field, value) in imported {
+        if MACHINE_FIELDS.contains(&field.as_str()) {
+            issue(&field, String::from("machine-specific, ignored"));
+            continue;
+        }
+        if !known.contains_key(&field) {
+            issue(&field, String::from("unknown setting, ignored"));
+            continue;
+        }
+        let single: Fields = std::iter::once((field.clone(), value.clone())).collect();
+        if let Err(e) = settings_diff::with_fields(current, &single) {
+            issue(&field, format!("invalid value: {}", e));
+            continue;
+        }
+        if let Some(message) = range_error(&field, &value) {
+            issue(&field, message);
+            continue;
+        }
+        accepted.insert(field, value);
+    }
+    
+    // Encode size and frame rate depend on the headset the profile ends up with.
+    let headset_id = accepted
+        .get("headset_model")
+        .and_then(Value::as_str)
+        .unwrap_or(&current.headset_model)
+        .to_string();
+    let headset = headsets::find_or_default(&headset_id);
+    let checks = [
+        ("encode_resolution_width", headset.encode_width_range()),
+        ("encode_resolution_height", headset.encode_height_range()),
+        ("frame_throttle_fps", 30..=headset.max_refresh_rate()),
+    ];
+    for (field, range) in checks {
+        if let Some(value) = accepted.get(field).and_then(Value::as_u64) {
+            if !range.contains(&(value as u32)) {
+                accepted.remove(field);
+                issue(field, format!("{} is outside {}..={} for the {}", value, range.start(), range.end(), headset.name));
+            }
+        }
+    }
+    if let Some(fps) = accepted.get("custom_fps").and_then(Value::as_u64) {
+        if !headset.refresh_rates.contains(&(fps as u32)) {
+            accepted.remove("custom_fps");
+            issue("custom_fps", format!("{} Hz is not supported by the {}", fps, headset.name));
+        }
+    }
+    
+    let merged = settings_diff::with_fields(current, &accepted)
+        .map(|settings| settings_diff::to_fields(&settings))
+        .unwrap_or_else(|_| known.clone());
+    ImportPreview { source: source.into(), changes: settings_diff::changes(&known, &merged), issues, accepted }
+}
+
+fn range_error(field: &str, value: &Value) -> Option<String> {
+    let (_, min, max) = RANGES.iter().find(|(name, _, _)| *name == field)?;
+    let number = value.as_f64()?;
+    if number < *min || number > *max {
+        Some(format!("{} is outside {}..={}", settings_diff::display_value(value), min, max))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn default_settings_round_trip_without_issues() {
+        let settings = VRSettings::default();
+        let share = to_share_string(&settings).unwrap();
+        assert!(share.starts_with(SHARE_PREFIX));
+        
+        let fields = parse_share_string(&format!(" {}\n", share)).unwrap();
+        assert!(fields == shareable_fields(&settings));
+        let preview = prepare_import(&settings, fields, "share string");
+        assert!(preview.issues.is_empty(), "{:?}", preview.issues.iter().map(|i| &i.field).collect::<Vec<_>>());
+        assert!(preview.changes.is_empty());
+    }
+    
+    #[test]
+    fn share_string_carries_changes_but_not_machine_fields() {
+        let source = VRSettings {
+            render_scale: 1.5,
+            autotune_frame_log: String::from("/home/someone/frames.csv"),
+            ..Default::default()
+        };
+        let fields = parse_share_string(&to_share_string(&source).unwrap()).unwrap();
+        assert!(!fields.contains_key("autotune_frame_log"));
+        
+        let preview = prepare_import(&VRSettings::default(), fields, "share string");
+        assert_eq!(preview.changes.len(), 1);
+        assert_eq!(preview.changes[0].field, "render_scale");
+    }
+    
+    #[test]
+    fn malformed_share_strings_are_rejected() {
+        assert!(parse_share_string("not a share string").is_err());
+        assert!(parse_share_string("vrsuite1:!!!").is_err());
+        let garbage = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(b"not deflate data");
+        assert!(parse_share_string(&format!("{}{}", SHARE_PREFIX, garbage)).is_err());
+    }
+    
+    #[test]
+    fn oversized_share_string_is_rejected() {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![b' '; MAX_SHARE_BYTES as usize + 10]).unwrap();
+        let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(encoder.finish().unwrap());
+        let error = parse_share_string(&format!("{}{}", SHARE_PREFIX, encoded)).unwrap_err();
+        assert_eq!(error.to_string(), "share string is too large");
+    }
+    
+    #[test]
+    fn import_keeps_valid_fields_and_reports_the_rest() {
+        let imported: Fields = json!({
+            "render_scale": 1.3,
+            "contrast": 4.0,
+            "asw_mode": "Sometimes",
+            "no_such_setting": true,
+            "hooks": [],
+            "encode_resolution_height": 1472,
+            "custom_fps": 144,
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let preview = prepare_import(&VRSettings::default(), imported, "test");
+        
+        assert_eq!(preview.accepted.keys().collect::<Vec<_>>(), ["render_scale"]);
+        let mut rejected: Vec<&str> = preview.issues.iter().map(|issue| issue.field.as_str()).collect();
+        rejected.sort();
+        assert_eq!(rejected, ["asw_mode", "contrast", "custom_fps", "encode_resolution_height", "hooks", "no_such_setting"]);
+    }
+    
+    #[test]
+    fn encode_size_is_checked_against_the_imported_headset() {
+        let imported = |headset: &str| -> Fields {
+            json!({ "headset_model": headset, "encode_resolution_height": 2208 }).as_object().unwrap().clone()
+        };
+        let quest3 = prepare_import(&VRSettings::default(), imported("quest3"), "test");
+        assert!(quest3.issues.is_empty());
+        
+        let quest_pro = prepare_import(&VRSettings::default(), imported("quest_pro"), "test");
+        assert!(quest_pro.accepted.contains_key("headset_model"));
+        assert!(!quest_pro.accepted.contains_key("encode_resolution_height"));
+        assert_eq!(quest_pro.issues.len(), 1);
+    }
+    
+    #[test]
+    fn exported_files_read_back() {
+        let dir = std::env::temp_dir().join(format!("vr_suite_profile_io_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let settings = VRSettings {
+            render_scale: 1.25,
+            ..Default::default()
+        };
+        
+        for name in ["profile.json", "profile.toml"] {
+            let path = dir.join(name);
+            export_file(&settings, &path).unwrap();
+            assert!(read_file(&path).unwrap() == shareable_fields(&settings), "{}", name);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,248 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use log::warn;
+
+use crate::config;
+use crate::profile_io;
+use crate::settings_diff::{self, Fields};
+use crate::VRSettings;
+
+pub const PROFILE_DIR: &str = "profiles";
+
+// Fields that select layers rather than configure anything, so an overlay cannot set them.
+const NOT_LAYERED: &[&str] = &["active_game_profile"];
+
+// Profile files are shared like exports, so what an import refuses (paths, commands, scripts) stays base-only too.
+fn is_layered(field: &str) -> bool {
+    !NOT_LAYERED.contains(&field) && !profile_io::MACHINE_FIELDS.contains(&field)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Layer {
+    Default,
+    Base,
+    Game,
+    Override,
+}
+
+impl Layer {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Layer::Default => "default",
+            Layer::Base => "base",
+            Layer::Game => "game",
+            Layer::Override => "override",
+        }
+    }
+}
+
+// A partial profile: only the fields it sets, keyed like settings.json.
+pub type Overlay = Fields;
+
+pub struct GameProfile {
+    pub name: String,
+    pub fields: Overlay,
+}
+
+pub fn profile_dir() -> PathBuf {
+    config::path(PROFILE_DIR)
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    !name.trim().is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+}
+
+impl GameProfile {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.trim().to_string(), fields: Overlay::new() }
+    }
+    
+    pub fn path(&self) -> PathBuf {
+        profile_dir().join(format!("{}.json", self.name))
+    }
+    
+    pub fn save(&self) -> io::Result<()> {
+        config::write_atomic(&self.path(), serde_json::to_string_pretty(&self.fields)?.as_bytes())
+    }
+    
+    pub fn delete(&self) -> io::Result<()> {
+        let _ = fs::remove_file(config::backup_path(&self.path()));
+        fs::remove_file(self.path())
+    }
+}
+
+// Reads every overlay in the profile directory, dropping fields that do not fit VRSettings.
+pub fn load_all() -> Vec<GameProfile> {
+    let mut profiles = Vec::new();
+    for entry in fs::read_dir(profile_dir()).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let name = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let fields: Overlay = match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string())) {
+            Ok(fields) => fields,
+            Err(e) => {
+                warn!("Skipping game profile {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let mut profile = GameProfile::new(&name);
+        for (field, value) in fields {
+            match check_field(&field, &value) {
+                Ok(()) => {
+                    profile.fields.insert(field, value);
+                }
+                Err(e) => warn!("Ignoring {} in game profile {}: {}", field, name, e),
+            }
+        }
+        profiles.push(profile);
+    }
+    profiles.sort_by_key(|profile| profile.name.to_lowercase());
+    profiles
+}
+
+// Whether a single overlay value can be merged into VRSettings at all.
+pub fn check_field(field: &str, value: &Value) -> Result<(), String> {
+    let defaults = VRSettings::default();
+    if !is_layered(field) || !settings_diff::to_fields(&defaults).contains_key(field) {
+        return Err(String::from("not a layered setting"));
+    }
+    let single: Fields = std::iter::once((field.to_string(), value.clone())).collect();
+    settings_diff::with_fields(&defaults, &single).map(|_| ()).map_err(|e| e.to_string())
+}
+
+pub struct Resolved {
+    pub settings: VRSettings,
+    // Fields missing here come from the defaults.
+    pub sources: HashMap<String, Layer>,
+}
+
+impl Resolved {
+    pub fn source(&self, field: &str) -> Layer {
+        self.sources.get(field).copied().unwrap_or(Layer::Default)
+    }
+}
+
+// Stacks default, base, game and override, later layers winning field by field.
+pub fn resolve(base: &VRSettings, game: Option<&GameProfile>, overrides: &Overlay) -> serde_json::Result<Resolved> {
+    let defaults = settings_diff::to_fields(&VRSettings::default());
+    let mut fields = settings_diff::to_fields(base);
+    let mut sources: HashMap<String, Layer> = settings_diff::changed_fields(&defaults, &fields)
+        .into_iter()
+        .map(|field| (field, Layer::Base))
+        .collect();
+    
+    let overlays = [(Layer::Game, game.map(|profile| &profile.fields)), (Layer::Override, Some(overrides))];
+    for (layer, overlay) in overlays {
+        for (field, value) in overlay.into_iter().flatten() {
+            if fields.contains_key(field) && is_layered(field) {
+                fields.insert(field.clone(), value.clone());
+                sources.insert(field.clone(), layer);
+            }
+        }
+    }
+    
+    let settings = serde_json::from_value(Value::Object(fields))?;
+    Ok(Resolved { settings, sources })
+}
+
+// Accepts JSON literals, and anything else as a plain string so enum names can be typed bare.
+pub fn parse_value(text: &str) -> Value {
+    serde_json::from_str(text.trim()).unwrap_or_else(|_| Value::String(text.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    fn overlay(value: Value) -> Overlay {
+        value.as_object().unwrap().clone()
+    }
+    
+    #[test]
+    fn later_layers_win_field_by_field() {
+        let base = VRSettings {
+            render_scale: 1.2,
+            contrast: 1.1,
+            ..Default::default()
+        };
+        let mut game = GameProfile::new("Racing");
+        game.fields = overlay(json!({ "render_scale": 1.4, "saturation": 1.2 }));
+        let overrides = overlay(json!({ "render_scale": 0.9 }));
+        
+        let resolved = resolve(&base, Some(&game), &overrides).unwrap();
+        assert_eq!(resolved.settings.render_scale, 0.9);
+        assert_eq!(resolved.settings.contrast, 1.1);
+        assert_eq!(resolved.settings.saturation, 1.2);
+        assert!(resolved.source("render_scale") == Layer::Override);
+        assert!(resolved.source("contrast") == Layer::Base);
+        assert!(resolved.source("saturation") == Layer::Game);
+        assert!(resolved.source("pixel_density") == Layer::Default);
+    }
+    
+    #[test]
+    fn base_alone_resolves_to_itself() {
+        let base = VRSettings {
+            sharpening_amount: 0.7,
+            ..Default::default()
+        };
+        let resolved = resolve(&base, None, &Overlay::new()).unwrap();
+        assert!(settings_diff::to_fields(&resolved.settings) == settings_diff::to_fields(&base));
+        assert_eq!(resolved.sources.len(), 1);
+    }
+    
+    #[test]
+    fn overlays_cannot_add_fields_or_switch_profiles() {
+        let base = VRSettings {
+            active_game_profile: String::from("Racing"),
+            ..Default::default()
+        };
+        let overrides = overlay(json!({ "no_such_setting": 1, "active_game_profile": "Other" }));
+        let resolved = resolve(&base, None, &overrides).unwrap();
+        assert_eq!(resolved.settings.active_game_profile, "Racing");
+        assert!(!resolved.sources.contains_key("no_such_setting"));
+        assert!(resolved.source("active_game_profile") == Layer::Base);
+    }
+    
+    #[test]
+    fn overlays_cannot_supply_hooks() {
+        let mut game = GameProfile::new("Shared");
+        game.fields = overlay(json!({ "hooks": [{ "event": "RuntimeStarted", "command": "echo shared" }] }));
+        let resolved = resolve(&VRSettings::default(), Some(&game), &game.fields.clone()).unwrap();
+        assert!(resolved.settings.hooks.is_empty());
+        assert!(resolved.source("hooks") == Layer::Default);
+        assert!(check_field("hooks", &json!([])).is_err());
+    }
+    
+    #[test]
+    fn overlays_cannot_supply_scripts_or_widen_their_allowlists() {
+        let mut game = GameProfile::new("Shared");
+        game.fields = overlay(json!({
+            "apply_scripts": [{ "path": "/tmp/shared.rhai" }],
+            "script_allowed_paths": ["/"],
+            "script_registry_allowed": ["HKLM"],
+        }));
+        let resolved = resolve(&VRSettings::default(), Some(&game), &game.fields.clone()).unwrap();
+        assert!(resolved.settings.apply_scripts.is_empty());
+        assert!(resolved.settings.script_allowed_paths.is_empty());
+        assert!(resolved.settings.script_registry_allowed.is_empty());
+    }
+    
+    #[test]
+    fn mistyped_overlay_value_is_an_error() {
+        let overrides = overlay(json!({ "render_scale": "high" }));
+        assert!(resolve(&VRSettings::default(), None, &overrides).is_err());
+        assert!(check_field("render_scale", &json!("high")).is_err());
+        assert!(check_field("active_game_profile", &json!("Other")).is_err());
+        assert!(check_field("render_scale", &parse_value(" 1.3 ")).is_ok());
+    }
+}
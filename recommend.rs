@@ -0,0 +1,353 @@
+use serde::Serialize;
+use serde_json::Value;
+use sysinfo::System;
+
+use log::info;
+
+use crate::headsets::HeadsetModel;
+use crate::settings_diff;
+use crate::{ConnectionType, FoveatedLevel, PowerPlan, UpscalingType, VRSettings};
+
+// Panels above this many pixels per eye get one render-scale step less.
+const DENSE_PANEL_PIXELS: u32 = 4_000_000;
+const AIR_LINK_MAX_BITRATE: u32 = 200;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum GpuTier {
+    Low,
+    Mid,
+    High,
+    Ultra,
+}
+
+impl GpuTier {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GpuTier::Low => "entry level",
+            GpuTier::Mid => "mid range",
+            GpuTier::High => "high end",
+            GpuTier::Ultra => "enthusiast",
+        }
+    }
+}
+
+pub struct Hardware {
+    pub cpu_brand: String,
+    pub cpu_threads: usize,
+    pub memory_gb: f32,
+    pub gpus: Vec<String>,
+}
+
+impl Hardware {
+    // The strongest adapter wins, so an iGPU next to a discrete card is ignored.
+    pub fn gpu(&self) -> Option<(&str, GpuTier)> {
+        self.gpus
+            .iter()
+            .map(|name| (name.as_str(), gpu_tier(name)))
+            .fold(None, |best: Option<(&str, GpuTier)>, candidate| match best {
+                Some(best) if best.1 >= candidate.1 => Some(best),
+                _ => Some(candidate),
+            })
+    }
+    
+    pub fn summary(&self) -> String {
+        let gpu = match self.gpu() {
+            Some((name, tier)) => format!("{} ({})", name, tier.label()),
+            None => String::from("unknown GPU"),
+        };
+        format!("{}, {} threads, {:.0} GB RAM, {}", self.cpu_brand, self.cpu_threads, self.memory_gb, gpu)
+    }
+}
+
+pub struct Recommendation {
+    pub field: &'static str,
+    pub label: &'static str,
+    pub value: Value,
+    pub rationale: String,
+}
+
+pub fn detect(system: &System) -> Hardware {
+    let hardware = Hardware {
+        cpu_brand: system.cpus().first().map(|cpu| cpu.brand().trim().to_string()).unwrap_or_default(),
+        cpu_threads: system.cpus().len(),
+        memory_gb: system.total_memory() as f32 / (1024.0 * 1024.0 * 1024.0),
+        gpus: detect_gpus(),
+    };
+    info!("Detected hardware: {}", hardware.summary());
+    hardware
+}
+
+#[cfg(target_os = "windows")]
+fn detect_gpus() -> Vec<String> {
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", "Get-CimInstance Win32_VideoController | ForEach-Object { $_.Name }"])
+        .output();
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn detect_gpus() -> Vec<String> {
+    if let Ok(output) = std::process::Command::new("lspci").output() {
+        let gpus: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| line.contains("VGA compatible controller") || line.contains("3D controller"))
+            .filter_map(|line| line.split_once(": ").map(|(_, name)| name))
+            .map(String::from)
+            .collect();
+        if !gpus.is_empty() {
+            return gpus;
+        }
+    }
+    
+    // Without lspci only the vendor is known.
+    let mut gpus = Vec::new();
+    for entry in std::fs::read_dir("/sys/class/drm").into_iter().flatten().flatten() {
+        let vendor = std::fs::read_to_string(entry.path().join("device").join("vendor")).unwrap_or_default();
+        let name = match vendor.trim() {
+            "0x10de" => "NVIDIA",
+            "0x1002" => "AMD",
+            "0x8086" => "Intel",
+            _ => continue,
+        };
+        if !gpus.iter().any(|gpu| gpu == name) {
+            gpus.push(name.to_string());
+        }
+    }
+    gpus
+}
+
+// Workstation RTX cards carry no consumer class digits, so they are matched to their nearest consumer card.
+const WORKSTATION_TIERS: &[(&str, GpuTier)] = &[
+    ("a2000", GpuTier::Mid),
+    ("a4000", GpuTier::High),
+    ("a4500", GpuTier::High),
+    ("a5000", GpuTier::High),
+    ("a5500", GpuTier::High),
+    ("a6000", GpuTier::Ultra),
+    ("2000", GpuTier::Mid),
+    ("4000", GpuTier::High),
+    ("4500", GpuTier::High),
+    ("5000", GpuTier::Ultra),
+    ("6000", GpuTier::Ultra),
+];
+
+// Rough tiering from the marketing name; anything unrecognised is treated as integrated graphics.
+fn gpu_tier(name: &str) -> GpuTier {
+    let lower = name.to_lowercase();
+    let tokens: Vec<&str> = lower.split(|c: char| !c.is_ascii_alphanumeric()).filter(|t| !t.is_empty()).collect();
+    let model_after = |keyword: &str| -> Option<u32> {
+        let index = tokens.iter().position(|t| *t == keyword)?;
+        let digits: String = tokens.get(index + 1)?.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse().ok()
+    };
+    
+    if let Some(index) = tokens.iter().position(|t| *t == "rtx") {
+        let model = tokens.iter().skip(index + 1).find(|t| **t != "pro");
+        if let Some((_, tier)) = WORKSTATION_TIERS.iter().find(|(name, _)| Some(name) == model) {
+            return *tier;
+        }
+    }
+    if let Some(model) = model_after("rtx") {
+        let (generation, class) = (model / 100, model % 100);
+        return if (generation >= 40 && class >= 80) || (generation >= 30 && class >= 90) {
+            GpuTier::Ultra
+        } else if (generation >= 40 && class >= 60) || (generation >= 30 && class >= 70) || class >= 80 {
+            GpuTier::High
+        } else {
+            GpuTier::Mid
+        };
+    }
+    if let Some(model) = model_after("gtx") {
+        return if model >= 1070 { GpuTier::Mid } else { GpuTier::Low };
+    }
+    if let Some(model) = model_after("rx") {
+        // The RX 9000 series moved the class to the tens digit: 9070 is a 70-class card.
+        let class = match (model % 1000) / 100 {
+            0 => (model % 100) / 10,
+            class => class,
+        };
+        let generation = model / 1000;
+        return if generation >= 7 && class >= 9 {
+            GpuTier::Ultra
+        } else if (generation >= 6 && class >= 8) || (generation >= 7 && class >= 7) {
+            GpuTier::High
+        } else if generation >= 5 && class >= 6 {
+            GpuTier::Mid
+        } else {
+            GpuTier::Low
+        };
+    }
+    if tokens.contains(&"arc") {
+        return if tokens.iter().any(|t| t.starts_with("a7")) { GpuTier::Mid } else { GpuTier::Low };
+    }
+    GpuTier::Low
+}
+
+pub fn recommend(hardware: &Hardware, headset: &HeadsetModel, connection: &ConnectionType) -> Vec<Recommendation> {
+    let mut recommendations = Vec::new();
+    
+    let (gpu_name, tier) = match hardware.gpu() {
+        Some((name, tier)) => (name.to_string(), tier),
+        None => (String::from("An undetected GPU"), GpuTier::Mid),
+    };
+    let gpu = format!("{} ({})", gpu_name, tier.label());
+    
+    let dense = headset.panel_width * headset.panel_height > DENSE_PANEL_PIXELS;
+    let base_scale: f32 = match tier {
+        GpuTier::Low => 0.8,
+        GpuTier::Mid => 1.0,
+        GpuTier::High => 1.2,
+        GpuTier::Ultra => 1.4,
+    };
+    let render_scale = if dense { ((base_scale - 0.1) * 10.0).round() / 10.0 } else { base_scale };
+    add(
+        &mut recommendations,
+        "render_scale",
+        "Render scale",
+        render_scale,
+        if dense {
+            format!("{} with {}x{} per eye on the {}; dense panels cost one step", gpu, headset.panel_width, headset.panel_height, headset.name)
+        } else {
+            format!("{} can drive the {} at this scale", gpu, headset.name)
+        },
+    );
+    
+    let target_fps = match tier {
+        GpuTier::Low => 72,
+        GpuTier::Mid => 80,
+        GpuTier::High => 90,
+        GpuTier::Ultra => 120,
+    };
+    let fps = headset
+        .refresh_rates
+        .iter()
+        .copied()
+        .filter(|rate| *rate <= target_fps)
+        .max()
+        .or_else(|| headset.refresh_rates.iter().copied().min())
+        .unwrap_or(target_fps);
+    add(&mut recommendations, "custom_fps", "Refresh rate", fps, format!("Highest {} rate a {} GPU holds without reprojection", headset.name, tier.label()));
+    add(&mut recommendations, "frame_throttle_fps", "FPS limit", fps, String::from("Matches the recommended refresh rate"));
+    
+    match tier {
+        GpuTier::Ultra => add(&mut recommendations, "foveated_rendering", "Foveated rendering", false, format!("{} has headroom for full-resolution periphery", gpu)),
+        _ => {
+            let level = match tier {
+                GpuTier::Low => FoveatedLevel::High,
+                GpuTier::Mid => FoveatedLevel::Medium,
+                _ => FoveatedLevel::Low,
+            };
+            add(&mut recommendations, "foveated_rendering", "Foveated rendering", true, format!("Saves GPU time on a {} card", tier.label()));
+            add(&mut recommendations, "foveated_level", "Foveated level", level, format!("Stronger foveation the less headroom {} has", gpu_name));
+        }
+    }
+    
+    if tier <= GpuTier::Mid {
+        add(&mut recommendations, "asw_enabled", "ASW", true, String::from("Covers dropped frames instead of stuttering"));
+    }
+    if tier == GpuTier::Low {
+        let upscaler = if gpu_name.to_lowercase().contains("nvidia") { UpscalingType::NIS } else { UpscalingType::FSR };
+        add(&mut recommendations, "upscaling_enabled", "Upscaling", true, format!("{} needs to render below native resolution", gpu));
+        add(&mut recommendations, "upscaling_type", "Upscaler", upscaler, String::from("NIS on NVIDIA, FSR everywhere else"));
+        add(&mut recommendations, "upscaling_scale", "Upscaling scale", 0.77f32, String::from("Quality preset of both upscalers"));
+    }
+    
+    let presets = headset.presets();
+    let streams = *connection != ConnectionType::DisplayPort && !presets.is_empty();
+    if streams {
+        let mut index: usize = match tier {
+            GpuTier::Low => 0,
+            GpuTier::Mid => 1,
+            _ => 2,
+        };
+        if *connection == ConnectionType::AirLink {
+            index = index.saturating_sub(1);
+        }
+        let preset = &presets[index.min(presets.len() - 1)];
+        let reason = format!("{} preset for the {} on a {} GPU", preset.label, headset.name, tier.label());
+        add(&mut recommendations, "encode_resolution_width", "Encode width", preset.width, reason.clone());
+        add(&mut recommendations, "encode_resolution_height", "Encode height", preset.height, reason);
+        if *connection == ConnectionType::AirLink {
+            add(
+                &mut recommendations,
+                "encode_bitrate_mbps",
+                "Bitrate (Mbps)",
+                preset.bitrate_mbps.min(AIR_LINK_MAX_BITRATE),
+                format!("Air Link rarely sustains more than {} Mbps over Wi-Fi", AIR_LINK_MAX_BITRATE),
+            );
+        } else {
+            add(&mut recommendations, "encode_bitrate_mbps", "Bitrate (Mbps)", preset.bitrate_mbps, format!("{} preset bitrate over a USB cable", preset.label));
+        }
+    }
+    
+    if hardware.cpu_threads < 8 {
+        add(
+            &mut recommendations,
+            "cpu_priority_boost",
+            "Runtime priority boost",
+            true,
+            format!("Only {} CPU threads, so background work competes with the runtime", hardware.cpu_threads),
+        );
+    }
+    if hardware.memory_gb < 15.5 {
+        add(
+            &mut recommendations,
+            "kill_oculus_client",
+            "Close Oculus app",
+            true,
+            format!("{:.0} GB RAM; the Oculus app uses about 1 GB while idle", hardware.memory_gb),
+        );
+    }
+    add(&mut recommendations, "power_plan", "Power plan", PowerPlan::HighPerformance, String::from("Keeps clocks up between frames instead of ramping per frame"));
+    
+    recommendations
+}
+
+fn add(recommendations: &mut Vec<Recommendation>, field: &'static str, label: &'static str, value: impl Serialize, rationale: String) {
+    if let Ok(value) = serde_json::to_value(value) {
+        recommendations.push(Recommendation { field, label, value, rationale });
+    }
+}
+
+// Recommendations whose value differs from the current settings.
+pub fn pending<'a>(recommendations: &'a [Recommendation], settings: &VRSettings) -> Vec<&'a Recommendation> {
+    let current = settings_diff::to_fields(settings);
+    recommendations.iter().filter(|r| current.get(r.field) != Some(&r.value)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn gpu_tiers_from_marketing_names() {
+        let cases = [
+            ("NVIDIA GeForce RTX 4090", GpuTier::Ultra),
+            ("NVIDIA GeForce RTX 3070", GpuTier::High),
+            ("NVIDIA GeForce RTX 2060 SUPER", GpuTier::Mid),
+            ("NVIDIA GeForce GTX 1080 Ti", GpuTier::Mid),
+            ("NVIDIA RTX A4000", GpuTier::High),
+            ("NVIDIA RTX A6000", GpuTier::Ultra),
+            ("NVIDIA RTX 4000 Ada Generation", GpuTier::High),
+            ("NVIDIA RTX PRO 6000 Blackwell Workstation Edition", GpuTier::Ultra),
+            ("AMD Radeon RX 7900 XTX", GpuTier::Ultra),
+            ("AMD Radeon RX 6800 XT", GpuTier::High),
+            ("AMD Radeon RX 9070 XT", GpuTier::High),
+            ("AMD Radeon RX 9060 XT", GpuTier::Mid),
+            ("AMD Radeon RX 580", GpuTier::Low),
+            ("Intel(R) Arc(TM) A770 Graphics", GpuTier::Mid),
+            ("Intel(R) UHD Graphics 770", GpuTier::Low),
+        ];
+        for (name, tier) in cases {
+            assert_eq!(gpu_tier(name), tier, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum Hive {
+    CurrentUser,
+    LocalMachine,
+}
+
+pub struct ManagedValue {
+    pub hive: Hive,
+    pub path: &'static str,
+    pub name: &'static str,
+}
+
+pub const MANAGED_VALUES: &[ManagedValue] = &[
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\RemoteHeadset", name: "BitrateMbps" },
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\RemoteHeadset", name: "EncodeResolutionWidth" },
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\RemoteHeadset", name: "EncodeResolutionHeight" },
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\RemoteHeadset", name: "LinkSharpeningEnabled" },
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\RemoteHeadset", name: "LinkSharpeningStrength" },
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\RemoteHeadset", name: "MirrorWindow" },
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\RemoteHeadset", name: "GuardianVisibility" },
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\Debug", name: "ASW" },
+    ManagedValue { hive: Hive::CurrentUser, path: "Software\\Oculus\\Telemetry", name: "Enabled" },
+    ManagedValue { hive: Hive::LocalMachine, path: "SOFTWARE\\Khronos\\OpenXR\\1", name: "ActiveRuntime" },
+    ManagedValue { hive: Hive::LocalMachine, path: "SOFTWARE\\WOW6432Node\\Oculus VR, LLC\\Oculus\\Config", name: "CoreChannel" },
+];
+
+impl ManagedValue {
+    pub fn display_path(&self) -> String {
+        display_path(self.hive, self.path, self.name)
+    }
+}
+
+pub fn display_path(hive: Hive, path: &str, name: &str) -> String {
+    let hive = match hive {
+        Hive::CurrentUser => "HKCU",
+        Hive::LocalMachine => "HKLM",
+    };
+    format!("{}\\{}\\{}", hive, path, name)
+}
+
+#[cfg(target_os = "windows")]
+fn root(hive: Hive) -> winreg::RegKey {
+    use winreg::enums::*;
+    
+    match hive {
+        Hive::CurrentUser => winreg::RegKey::predef(HKEY_CURRENT_USER),
+        Hive::LocalMachine => winreg::RegKey::predef(HKEY_LOCAL_MACHINE),
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn write_value<T: winreg::types::ToRegValue>(hive: Hive, path: &str, name: &str, value: &T) -> std::io::Result<()> {
+    let (key, _) = root(hive).create_subkey(path)?;
+    key.set_value(name, value)
+}
+
+pub fn read_value(value: &ManagedValue) -> Option<String> {
+    read_string(value.hive, value.path, value.name)
+}
+
+#[cfg(target_os = "windows")]
+pub fn read_string(hive: Hive, path: &str, name: &str) -> Option<String> {
+    let key = root(hive).open_subkey(path).ok()?;
+    let raw = key.get_raw_value(name).ok()?;
+    Some(raw.to_string())
+}
+
+#[cfg(target_os = "windows")]
+pub fn read_u32(hive: Hive, path: &str, name: &str) -> Option<u32> {
+    root(hive).open_subkey(path).ok()?.get_value(name).ok()
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn read_string(_hive: Hive, _path: &str, _name: &str) -> Option<String> {
+    None
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+#[cfg(target_os = "windows")]
+use std::process::Output;
+
+use log::{info, warn};
+
+use crate::elevation::HelperCommand;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApplyError {
+    #[serde(with = "error_kind")]
+    pub kind: io::ErrorKind,
+    pub os_error: Option<i32>,
+    pub message: String,
+}
+
+impl ApplyError {
+    pub fn from_io(e: &io::Error) -> Self {
+        Self { kind: e.kind(), os_error: e.raw_os_error(), message: e.to_string() }
+    }
+    
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self.kind {
+            io::ErrorKind::PermissionDenied => Some(if cfg!(target_os = "windows") {
+                "run as administrator"
+            } else {
+                "run as root or grant the needed capability"
+            }),
+            io::ErrorKind::NotFound => Some("check the install paths under Advanced"),
+            io::ErrorKind::TimedOut => Some("the service may be hung, try again or restart it"),
+            io::ErrorKind::Unsupported => Some("not available on this platform"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReportEntry {
+    pub step: String,
+    pub target: String,
+    pub result: Result<(), ApplyError>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ApplyReport {
+    pub entries: Vec<ReportEntry>,
+    // When set, permission failures are queued for the elevated helper instead of recorded.
+    #[serde(skip)]
+    pub escalate: bool,
+    #[serde(skip)]
+    pub privileged: Vec<HelperCommand>,
+}
+
+impl ApplyReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn record(&mut self, step: &str, target: impl Into<String>, result: io::Result<()>) {
+        let target = target.into();
+        let result = match result {
+            Ok(()) => {
+                info!("{}: {} ok", step, target);
+                Ok(())
+            }
+            Err(e) => {
+                let error = ApplyError::from_io(&e);
+                match error.suggestion() {
+                    Some(suggestion) => warn!("{}: {} failed: {} ({})", step, target, e, suggestion),
+                    None => warn!("{}: {} failed: {}", step, target, e),
+                }
+                Err(error)
+            }
+        };
+        self.entries.push(ReportEntry { step: step.to_string(), target, result });
+    }
+    
+    pub fn record_or_escalate(&mut self, step: &str, target: impl Into<String>, result: io::Result<()>, command: HelperCommand) {
+        match result {
+            Err(e) if self.escalate && e.kind() == io::ErrorKind::PermissionDenied => {
+                info!("{}: {} needs elevation, deferring to the helper", step, target.into());
+                self.privileged.push(command);
+            }
+            result => self.record(step, target, result),
+        }
+    }
+    
+    pub fn failures(&self) -> impl Iterator<Item = (&ReportEntry, &ApplyError)> {
+        self.entries.iter().filter_map(|entry| entry.result.as_ref().err().map(|e| (entry, e)))
+    }
+    
+    pub fn failure_count(&self) -> usize {
+        self.failures().count()
+    }
+}
+
+// Turns a finished command into an error when it exited unsuccessfully.
+#[cfg(target_os = "windows")]
+pub fn check_output(output: io::Result<Output>) -> io::Result<()> {
+    let output = output?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let detail = if stderr.is_empty() { stdout } else { stderr };
+    Err(io::Error::other(format!("exited with {}: {}", output.status, detail)))
+}
+
+mod error_kind {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::io::ErrorKind;
+    
+    const KINDS: &[(ErrorKind, &str)] = &[
+        (ErrorKind::NotFound, "not_found"),
+        (ErrorKind::PermissionDenied, "permission_denied"),
+        (ErrorKind::AlreadyExists, "already_exists"),
+        (ErrorKind::InvalidInput, "invalid_input"),
+        (ErrorKind::InvalidData, "invalid_data"),
+        (ErrorKind::TimedOut, "timed_out"),
+        (ErrorKind::Unsupported, "unsupported"),
+    ];
+    
+    pub fn serialize<S: Serializer>(kind: &ErrorKind, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = KINDS.iter().find(|(k, _)| k == kind).map_or("other", |(_, name)| name);
+        serializer.serialize_str(name)
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ErrorKind, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(KINDS.iter().find(|(_, n)| *n == name).map_or(ErrorKind::Other, |(kind, _)| *kind))
+    }
+}
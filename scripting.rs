@@ -0,0 +1,493 @@
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Position, Scope};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+
+use log::{info, warn};
+
+use crate::registry::{self, Hive};
+use crate::report::ApplyReport;
+use crate::settings_diff;
+use crate::VRSettings;
+
+const MAX_OPERATIONS: u64 = 5_000_000;
+const MAX_RUN_TIME: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ApplyScript {
+    pub path: String,
+    pub enabled: bool,
+}
+
+impl Default for ApplyScript {
+    fn default() -> Self {
+        Self { path: String::from(""), enabled: true }
+    }
+}
+
+impl ApplyScript {
+    pub fn name(&self) -> String {
+        Path::new(&self.path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| self.path.clone())
+    }
+}
+
+// Everything a script can see. Scripts get no other access to the machine.
+pub struct ScriptContext {
+    pub settings: serde_json::Map<String, Value>,
+    pub processes: Vec<(String, u32)>,
+    // Files outside these directories (or files) cannot be read or written.
+    pub allowed_paths: Vec<PathBuf>,
+    // Registry keys, with everything below them, that can be read or written.
+    pub allowed_registry: Vec<(Hive, String)>,
+    // Writes are recorded but not performed.
+    pub dry_run: bool,
+}
+
+impl ScriptContext {
+    pub fn new(settings: &VRSettings, sys: &System, dry_run: bool) -> Self {
+        Self {
+            settings: settings_diff::to_fields(settings),
+            processes: sys.processes().values().map(|p| (p.name().to_string(), p.pid().as_u32())).collect(),
+            allowed_paths: settings.script_allowed_paths.iter().filter(|p| !p.trim().is_empty()).map(PathBuf::from).collect(),
+            allowed_registry: settings.script_registry_allowed.iter().filter_map(|key| parse_registry_key(key)).collect(),
+            dry_run,
+        }
+    }
+}
+
+pub struct ScriptStep {
+    pub target: String,
+    pub result: io::Result<()>,
+}
+
+pub struct ScriptRun {
+    pub steps: Vec<ScriptStep>,
+    pub error: Option<String>,
+}
+
+impl ScriptRun {
+    pub fn record_into(self, step: &str, report: &mut ApplyReport) {
+        for entry in self.steps {
+            report.record(step, entry.target, entry.result);
+        }
+        if let Some(error) = self.error {
+            report.record(step, "script", Err(io::Error::other(error)));
+        }
+    }
+}
+
+struct State {
+    context: ScriptContext,
+    steps: Vec<ScriptStep>,
+}
+
+type Shared = Rc<RefCell<State>>;
+
+fn runtime_error(message: impl Into<String>) -> Box<EvalAltResult> {
+    EvalAltResult::ErrorRuntime(Dynamic::from(message.into()), Position::NONE).into()
+}
+
+pub fn run_file(path: &Path, context: ScriptContext) -> ScriptRun {
+    match fs::read_to_string(path) {
+        Ok(source) => run(&path.display().to_string(), &source, context),
+        Err(e) => ScriptRun { steps: Vec::new(), error: Some(format!("could not read {}: {}", path.display(), e)) },
+    }
+}
+
+pub fn run(name: &str, source: &str, context: ScriptContext) -> ScriptRun {
+    let settings = to_dynamic(&Value::Object(context.settings.clone()));
+    let state: Shared = Rc::new(RefCell::new(State { context, steps: Vec::new() }));
+    let engine = engine(name, &state);
+    
+    let mut scope = Scope::new();
+    scope.push_constant("settings", settings);
+    let error = match engine.run_with_scope(&mut scope, source) {
+        Ok(()) => None,
+        Err(e) => {
+            warn!("Script {} failed: {}", name, e);
+            Some(e.to_string())
+        }
+    };
+    drop(engine);
+    
+    let steps = match Rc::try_unwrap(state) {
+        Ok(state) => state.into_inner().steps,
+        Err(state) => std::mem::take(&mut state.borrow_mut().steps),
+    };
+    ScriptRun { steps, error }
+}
+
+fn engine(name: &str, state: &Shared) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(1 << 20);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    let started = Instant::now();
+    engine.on_progress(move |_| if started.elapsed() > MAX_RUN_TIME { Some(Dynamic::from("time limit exceeded")) } else { None });
+    
+    let script = name.to_string();
+    engine.on_print(move |text| info!("[{}] {}", script, text));
+    let script = name.to_string();
+    engine.on_debug(move |text, _, position| info!("[{}] {} {}", script, position, text));
+    let script = name.to_string();
+    engine.register_fn("log_info", move |text: &str| info!("[{}] {}", script, text));
+    let script = name.to_string();
+    engine.register_fn("log_warn", move |text: &str| warn!("[{}] {}", script, text));
+    
+    let shared = state.clone();
+    engine.register_fn("processes", move || -> Array {
+        shared
+            .borrow()
+            .context
+            .processes
+            .iter()
+            .map(|(name, pid)| {
+                let mut process = Map::new();
+                process.insert("name".into(), Dynamic::from(name.clone()));
+                process.insert("pid".into(), Dynamic::from(*pid as i64));
+                Dynamic::from_map(process)
+            })
+            .collect()
+    });
+    let shared = state.clone();
+    engine.register_fn("process_running", move |name: &str| -> bool {
+        let name = name.to_lowercase();
+        shared.borrow().context.processes.iter().any(|(process, _)| process.to_lowercase() == name)
+    });
+    
+    let shared = state.clone();
+    engine.register_fn("registry_read", move |hive: &str, path: &str, name: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+        let hive = parse_hive(hive)?;
+        registry_allowed(&shared, hive, path)?;
+        Ok(registry::read_string(hive, path, name).map_or(Dynamic::UNIT, Dynamic::from))
+    });
+    let shared = state.clone();
+    engine.register_fn("registry_set_dword", move |hive: &str, path: &str, name: &str, value: i64| -> Result<(), Box<EvalAltResult>> {
+        let hive = parse_hive(hive)?;
+        registry_allowed(&shared, hive, path)?;
+        let value = u32::try_from(value).map_err(|_| runtime_error(format!("{} does not fit a DWORD", value)))?;
+        record(&shared, registry::display_path(hive, path, name), || write_registry(hive, path, name, RegistryValue::Dword(value)));
+        Ok(())
+    });
+    let shared = state.clone();
+    engine.register_fn("registry_set_string", move |hive: &str, path: &str, name: &str, value: &str| -> Result<(), Box<EvalAltResult>> {
+        let hive = parse_hive(hive)?;
+        registry_allowed(&shared, hive, path)?;
+        let value = value.to_string();
+        record(&shared, registry::display_path(hive, path, name), || write_registry(hive, path, name, RegistryValue::String(value)));
+        Ok(())
+    });
+    
+    let shared = state.clone();
+    engine.register_fn("file_read", move |path: &str| -> Result<String, Box<EvalAltResult>> {
+        let path = allowed(&shared, path)?;
+        fs::read_to_string(&path).map_err(|e| runtime_error(format!("{}: {}", path.display(), e)))
+    });
+    let shared = state.clone();
+    engine.register_fn("file_write", move |path: &str, contents: &str| -> Result<(), Box<EvalAltResult>> {
+        let path = allowed(&shared, path)?;
+        let contents = contents.to_string();
+        record(&shared, path.display().to_string(), || fs::write(&path, contents));
+        Ok(())
+    });
+    let shared = state.clone();
+    engine.register_fn("ini_set", move |path: &str, section: &str, key: &str, value: &str| -> Result<(), Box<EvalAltResult>> {
+        let path = allowed(&shared, path)?;
+        let (section, key, value) = (section.to_string(), key.to_string(), value.to_string());
+        let target = format!("{} [{}] {}", path.display(), section, key);
+        record(&shared, target, || {
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => String::from(""),
+                Err(e) => return Err(e),
+            };
+            fs::write(&path, set_ini_value(&contents, &section, &key, &value))
+        });
+        Ok(())
+    });
+    
+    engine
+}
+
+fn record(state: &Shared, target: String, write: impl FnOnce() -> io::Result<()>) {
+    let dry_run = state.borrow().context.dry_run;
+    let (target, result) = if dry_run { (format!("{} (dry run)", target), Ok(())) } else { (target, write()) };
+    state.borrow_mut().steps.push(ScriptStep { target, result });
+}
+
+fn hive_from_name(hive: &str) -> Option<Hive> {
+    match hive.to_uppercase().as_str() {
+        "HKCU" | "HKEY_CURRENT_USER" => Some(Hive::CurrentUser),
+        "HKLM" | "HKEY_LOCAL_MACHINE" => Some(Hive::LocalMachine),
+        _ => None,
+    }
+}
+
+fn parse_hive(hive: &str) -> Result<Hive, Box<EvalAltResult>> {
+    hive_from_name(hive).ok_or_else(|| runtime_error(format!("unknown registry hive '{}', use HKCU or HKLM", hive)))
+}
+
+// Registry key names are case-insensitive, so keys are compared lowercased.
+fn normalize_key(path: &str) -> String {
+    path.trim().trim_matches('\\').to_lowercase()
+}
+
+// Reads an allowlist entry such as `HKCU\Software\Vendor`.
+fn parse_registry_key(key: &str) -> Option<(Hive, String)> {
+    let key = key.trim().trim_matches('\\');
+    let (hive, path) = key.split_once('\\').unwrap_or((key, ""));
+    match hive_from_name(hive) {
+        Some(hive) => Some((hive, normalize_key(path))),
+        None => {
+            warn!("Ignoring allowed registry key {}: unknown hive", key);
+            None
+        }
+    }
+}
+
+fn registry_allowed(state: &Shared, hive: Hive, path: &str) -> Result<(), Box<EvalAltResult>> {
+    let key = normalize_key(path);
+    let permitted = state.borrow().context.allowed_registry.iter().any(|(root_hive, root)| {
+        *root_hive == hive && (root.is_empty() || key == *root || key.starts_with(&format!("{}\\", root)))
+    });
+    if permitted {
+        Ok(())
+    } else {
+        Err(runtime_error(format!("{} is outside the allowed registry keys", registry::display_path(hive, path, "").trim_end_matches('\\'))))
+    }
+}
+
+enum RegistryValue {
+    Dword(u32),
+    String(String),
+}
+
+#[cfg(target_os = "windows")]
+fn write_registry(hive: Hive, path: &str, name: &str, value: RegistryValue) -> io::Result<()> {
+    match value {
+        RegistryValue::Dword(value) => registry::write_value(hive, path, name, &value),
+        RegistryValue::String(value) => registry::write_value(hive, path, name, &value),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn write_registry(_hive: Hive, _path: &str, _name: &str, value: RegistryValue) -> io::Result<()> {
+    let _ = match value {
+        RegistryValue::Dword(value) => value.to_string(),
+        RegistryValue::String(value) => value,
+    };
+    Err(io::Error::new(io::ErrorKind::Unsupported, "the registry only exists on Windows"))
+}
+
+// Resolves `path` and checks it lies under one of the allowed paths, so `..` and symlinks cannot escape.
+fn allowed(state: &Shared, path: &str) -> Result<PathBuf, Box<EvalAltResult>> {
+    let path = PathBuf::from(path);
+    let resolved = match fs::canonicalize(&path) {
+        Ok(resolved) => resolved,
+        // A dangling symlink would be followed by the write, wherever it points.
+        Err(_) if fs::symlink_metadata(&path).is_ok() => {
+            return Err(runtime_error(format!("{} is a broken link", path.display())));
+        }
+        Err(_) => {
+            let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            let name = path.file_name().ok_or_else(|| runtime_error(format!("{} is not a file", path.display())))?;
+            fs::canonicalize(parent).map_err(|e| runtime_error(format!("{}: {}", parent.display(), e)))?.join(name)
+        }
+    };
+    let state = state.borrow();
+    let permitted = state
+        .context
+        .allowed_paths
+        .iter()
+        .filter_map(|root| fs::canonicalize(root).ok())
+        .any(|root| resolved.starts_with(&root));
+    if permitted {
+        Ok(resolved)
+    } else {
+        Err(runtime_error(format!("{} is outside the allowed script paths", resolved.display())))
+    }
+}
+
+fn to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(b) => Dynamic::from(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Dynamic::from(i),
+            None => Dynamic::from(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => Dynamic::from(s.clone()),
+        Value::Array(items) => Dynamic::from_array(items.iter().map(to_dynamic).collect()),
+        Value::Object(fields) => {
+            let mut map = Map::new();
+            for (key, value) in fields {
+                map.insert(key.as_str().into(), to_dynamic(value));
+            }
+            Dynamic::from_map(map)
+        }
+    }
+}
+
+// Replaces or adds `key=value` in `section`, leaving comments, ordering and line endings alone.
+pub fn set_ini_value(contents: &str, section: &str, key: &str, value: &str) -> String {
+    let newline = if contents.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+    let entry = format!("{}={}", key, value);
+    
+    let mut in_section = section.is_empty();
+    let mut section_end = if in_section { Some(0) } else { None };
+    for (i, line) in lines.iter_mut().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            in_section = trimmed[1..trimmed.len() - 1].trim().eq_ignore_ascii_case(section);
+            if in_section {
+                section_end = Some(i + 1);
+            }
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((name, _)) = trimmed.split_once('=') {
+            if name.trim().eq_ignore_ascii_case(key) {
+                *line = entry;
+                return lines.join(newline) + newline;
+            }
+        }
+        if !trimmed.is_empty() {
+            section_end = Some(i + 1);
+        }
+    }
+    
+    match section_end {
+        Some(index) => lines.insert(index, entry),
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::from(""));
+            }
+            lines.push(format!("[{}]", section));
+            lines.push(entry);
+        }
+    }
+    lines.join(newline) + newline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn context(allowed_paths: Vec<PathBuf>, dry_run: bool) -> ScriptContext {
+        ScriptContext {
+            settings: serde_json::Map::new(),
+            processes: Vec::new(),
+            allowed_paths,
+            allowed_registry: vec![parse_registry_key("HKEY_CURRENT_USER\\Software\\Vendor\\").unwrap()],
+            dry_run,
+        }
+    }
+    
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vr_suite_script_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("allowed")).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn ini_value_is_replaced_in_place() {
+        let contents = "; settings\r\n[Display]\r\nScale = 1.0\r\nMode=2\r\n[Other]\r\nscale=5\r\n";
+        assert_eq!(
+            set_ini_value(contents, "display", "SCALE", "1.5"),
+            "; settings\r\n[Display]\r\nSCALE=1.5\r\nMode=2\r\n[Other]\r\nscale=5\r\n"
+        );
+    }
+    
+    #[test]
+    fn ini_value_is_added_to_the_end_of_its_section() {
+        let contents = "[Display]\nMode=2\n\n[Other]\nx=1\n";
+        assert_eq!(set_ini_value(contents, "Display", "Scale", "1.5"), "[Display]\nMode=2\nScale=1.5\n\n[Other]\nx=1\n");
+    }
+    
+    #[test]
+    fn ini_section_is_created_when_missing() {
+        assert_eq!(set_ini_value("", "Display", "Scale", "1.5"), "[Display]\nScale=1.5\n");
+        assert_eq!(set_ini_value("[Other]\nx=1", "Display", "Scale", "1.5"), "[Other]\nx=1\n\n[Display]\nScale=1.5\n");
+        assert_eq!(set_ini_value("x=1\n[Other]\ny=2\n", "", "z", "3"), "x=1\nz=3\n[Other]\ny=2\n");
+    }
+    
+    #[test]
+    fn files_outside_the_allowed_paths_are_refused() {
+        let dir = temp_dir("escape");
+        fs::write(dir.join("secret.txt"), "secret").unwrap();
+        fs::write(dir.join("allowed").join("ok.txt"), "ok").unwrap();
+        let source = format!(
+            r#"file_read("{0}/allowed/ok.txt"); file_read("{0}/allowed/../secret.txt");"#,
+            dir.display()
+        );
+        let run = run("test", &source, context(vec![dir.join("allowed")], false));
+        assert!(run.error.unwrap().contains("outside the allowed script paths"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_cannot_leave_the_allowed_paths() {
+        use std::os::unix::fs::symlink;
+        
+        let dir = temp_dir("symlink");
+        fs::write(dir.join("secret.txt"), "secret").unwrap();
+        symlink(dir.join("secret.txt"), dir.join("allowed").join("link.txt")).unwrap();
+        symlink(dir.join("missing.txt"), dir.join("allowed").join("dangling.txt")).unwrap();
+        
+        let read = format!(r#"file_read("{}/allowed/link.txt")"#, dir.display());
+        assert!(run("test", &read, context(vec![dir.join("allowed")], false)).error.unwrap().contains("outside the allowed script paths"));
+        let write = format!(r#"file_write("{}/allowed/dangling.txt", "x")"#, dir.display());
+        assert!(run("test", &write, context(vec![dir.join("allowed")], false)).error.is_some());
+        assert!(!dir.join("missing.txt").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn dry_run_records_writes_without_performing_them() {
+        let dir = temp_dir("dry_run");
+        let target = dir.join("allowed").join("out.ini");
+        let source = format!(
+            r#"file_write("{0}", "a"); ini_set("{0}", "Display", "Scale", "1.5"); registry_set_dword("HKCU", "Software\\Vendor\\App", "Level", 3);"#,
+            target.display()
+        );
+        let run = run("test", &source, context(vec![dir.join("allowed")], true));
+        assert!(run.error.is_none(), "{:?}", run.error);
+        assert_eq!(run.steps.len(), 3);
+        assert!(run.steps.iter().all(|step| step.target.ends_with("(dry run)") && step.result.is_ok()));
+        assert!(!target.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn registry_access_is_limited_to_allowed_keys() {
+        let allowed = r#"registry_set_string("HKCU", "software\\vendor\\App", "Name", "x");"#;
+        assert!(run("test", allowed, context(Vec::new(), true)).error.is_none());
+        
+        for denied in [
+            r#"registry_set_string("HKLM", "Software\\Vendor", "Name", "x");"#,
+            r#"registry_set_dword("HKCU", "Software\\VendorTools", "Level", 1);"#,
+            r#"registry_read("HKCU", "Software\\Other", "Name");"#,
+        ] {
+            let run = run("test", denied, context(Vec::new(), true));
+            assert!(run.error.unwrap().contains("outside the allowed registry keys"), "{}", denied);
+            assert!(run.steps.is_empty());
+        }
+    }
+}
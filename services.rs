@@ -0,0 +1,420 @@
+use std::io;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use log::info;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ServiceScope {
+    System,
+    User,
+}
+
+pub struct Service {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub scope: ServiceScope,
+}
+
+#[cfg(target_os = "windows")]
+pub const OCULUS_SERVICE: Service = Service { name: "OVRService", label: "Oculus VR Runtime Service", scope: ServiceScope::System };
+
+#[cfg(target_os = "windows")]
+pub const MANAGED_SERVICES: &[Service] = &[OCULUS_SERVICE];
+
+#[cfg(not(target_os = "windows"))]
+pub const MANAGED_SERVICES: &[Service] = &[
+    Service { name: "monado.service", label: "Monado", scope: ServiceScope::User },
+    Service { name: "wivrn.service", label: "WiVRn", scope: ServiceScope::User },
+    Service { name: "avahi-daemon.service", label: "Avahi (WiVRn discovery)", scope: ServiceScope::System },
+];
+
+#[derive(Clone, PartialEq)]
+pub enum ServiceState {
+    Running,
+    Stopped,
+    StartPending,
+    StopPending,
+    NotInstalled,
+    Unknown(String),
+}
+
+impl ServiceState {
+    pub fn label(&self) -> String {
+        match self {
+            ServiceState::Running => String::from("Running"),
+            ServiceState::Stopped => String::from("Stopped"),
+            ServiceState::StartPending => String::from("Starting"),
+            ServiceState::StopPending => String::from("Stopping"),
+            ServiceState::NotInstalled => String::from("Not installed"),
+            ServiceState::Unknown(state) => format!("Unknown ({})", state),
+        }
+    }
+}
+
+pub trait ServiceBackend: Send {
+    fn state(&self, service: &Service) -> io::Result<ServiceState>;
+    fn start(&self, service: &Service) -> io::Result<()>;
+    fn stop(&self, service: &Service) -> io::Result<()>;
+}
+
+pub struct ServiceController {
+    backend: Box<dyn ServiceBackend>,
+    poll_interval: Duration,
+}
+
+impl ServiceController {
+    pub fn new() -> Self {
+        Self::with_backend(Box::new(backend::SystemBackend))
+    }
+    
+    pub fn with_backend(backend: Box<dyn ServiceBackend>) -> Self {
+        Self { backend, poll_interval: POLL_INTERVAL }
+    }
+    
+    pub fn state(&self, service: &Service) -> io::Result<ServiceState> {
+        self.backend.state(service)
+    }
+    
+    pub fn start(&self, service: &Service) -> io::Result<()> {
+        match self.state(service)? {
+            ServiceState::Running | ServiceState::StartPending => Ok(()),
+            ServiceState::NotInstalled => Err(not_installed(service)),
+            _ => self.backend.start(service),
+        }
+    }
+    
+    pub fn stop(&self, service: &Service) -> io::Result<()> {
+        match self.state(service)? {
+            ServiceState::Stopped | ServiceState::StopPending | ServiceState::NotInstalled => Ok(()),
+            _ => self.backend.stop(service),
+        }
+    }
+    
+    pub fn start_and_wait(&self, service: &Service, timeout: Duration) -> io::Result<()> {
+        self.start(service)?;
+        self.wait_for(service, ServiceState::Running, timeout)?;
+        info!("Service {} started", service.name);
+        Ok(())
+    }
+    
+    pub fn stop_and_wait(&self, service: &Service, timeout: Duration) -> io::Result<()> {
+        if self.state(service)? == ServiceState::NotInstalled {
+            return Ok(());
+        }
+        self.stop(service)?;
+        self.wait_for(service, ServiceState::Stopped, timeout)?;
+        info!("Service {} stopped", service.name);
+        Ok(())
+    }
+    
+    fn wait_for(&self, service: &Service, wanted: ServiceState, timeout: Duration) -> io::Result<()> {
+        let started = Instant::now();
+        loop {
+            let state = self.state(service)?;
+            if state == wanted {
+                return Ok(());
+            }
+            if started.elapsed() >= timeout {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("{} is {} after {} s, expected {}", service.name, state.label(), timeout.as_secs(), wanted.label()),
+                ));
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce(&ServiceController) + Send>;
+
+pub type ServiceStates = Vec<Result<ServiceState, String>>;
+
+// Owns the controller on a background thread, since waiting on a service can take many seconds.
+pub struct ServiceWorker {
+    jobs: mpsc::Sender<Job>,
+    states: mpsc::Receiver<ServiceStates>,
+}
+
+impl ServiceWorker {
+    pub fn start(controller: ServiceController, services: &'static [Service], interval: Duration) -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<Job>();
+        let (state_sender, states) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut last = None;
+            let mut next_refresh = Instant::now();
+            loop {
+                if Instant::now() >= next_refresh {
+                    let current: ServiceStates = services.iter()
+                        .map(|service| controller.state(service).map_err(|e| e.to_string()))
+                        .collect();
+                    if last.as_ref() != Some(&current) {
+                        if state_sender.send(current.clone()).is_err() {
+                            return;
+                        }
+                        last = Some(current);
+                    }
+                    next_refresh = Instant::now() + interval;
+                }
+                match job_receiver.recv_timeout(next_refresh.saturating_duration_since(Instant::now())) {
+                    Ok(job) => {
+                        job(&controller);
+                        next_refresh = Instant::now();
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+        Self { jobs, states }
+    }
+    
+    // Queues the job behind any running one; its result arrives on the returned receiver.
+    pub fn run<T: Send + 'static>(&self, job: impl FnOnce(&ServiceController) -> T + Send + 'static) -> mpsc::Receiver<T> {
+        let (sender, receiver) = mpsc::channel();
+        let _ = self.jobs.send(Box::new(move |controller| {
+            let _ = sender.send(job(controller));
+        }));
+        receiver
+    }
+    
+    pub fn poll_states(&self) -> Option<ServiceStates> {
+        self.states.try_iter().last()
+    }
+}
+
+fn not_installed(service: &Service) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("service {} is not installed", service.name))
+}
+
+#[cfg(target_os = "windows")]
+mod backend {
+    use std::io;
+    
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::*;
+    use windows::Win32::Security::SC_HANDLE;
+    use windows::Win32::System::Services::*;
+    
+    use super::{Service, ServiceBackend, ServiceState};
+    use crate::priority::win32_error;
+    
+    pub struct SystemBackend;
+    
+    struct Handle(SC_HANDLE);
+    
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = CloseServiceHandle(self.0);
+            }
+        }
+    }
+    
+    fn open(service: &Service, access: u32) -> windows::core::Result<Handle> {
+        let name: Vec<u16> = service.name.encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            let manager = Handle(OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_CONNECT)?);
+            Ok(Handle(OpenServiceW(manager.0, PCWSTR(name.as_ptr()), access)?))
+        }
+    }
+    
+    impl ServiceBackend for SystemBackend {
+        fn state(&self, service: &Service) -> io::Result<ServiceState> {
+            let handle = match open(service, SERVICE_QUERY_STATUS) {
+                Ok(handle) => handle,
+                Err(e) if e.code() == ERROR_SERVICE_DOES_NOT_EXIST.to_hresult() => return Ok(ServiceState::NotInstalled),
+                Err(e) => return Err(win32_error(e)),
+            };
+            let mut status = SERVICE_STATUS::default();
+            unsafe { QueryServiceStatus(handle.0, &mut status).map_err(win32_error)? };
+            Ok(match status.dwCurrentState {
+                SERVICE_RUNNING => ServiceState::Running,
+                SERVICE_STOPPED => ServiceState::Stopped,
+                SERVICE_START_PENDING => ServiceState::StartPending,
+                SERVICE_STOP_PENDING => ServiceState::StopPending,
+                other => ServiceState::Unknown(other.0.to_string()),
+            })
+        }
+        
+        fn start(&self, service: &Service) -> io::Result<()> {
+            let handle = open(service, SERVICE_START).map_err(win32_error)?;
+            match unsafe { StartServiceW(handle.0, None) } {
+                Err(e) if e.code() == ERROR_SERVICE_ALREADY_RUNNING.to_hresult() => Ok(()),
+                result => result.map_err(win32_error),
+            }
+        }
+        
+        fn stop(&self, service: &Service) -> io::Result<()> {
+            let handle = open(service, SERVICE_STOP).map_err(win32_error)?;
+            let mut status = SERVICE_STATUS::default();
+            match unsafe { ControlService(handle.0, SERVICE_CONTROL_STOP, &mut status) } {
+                Err(e) if e.code() == ERROR_SERVICE_NOT_ACTIVE.to_hresult() => Ok(()),
+                result => result.map_err(win32_error),
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod backend {
+    use std::io;
+    use std::process::Command;
+    
+    use super::{Service, ServiceBackend, ServiceScope, ServiceState};
+    
+    pub struct SystemBackend;
+    
+    fn systemctl(service: &Service, args: &[&str]) -> io::Result<String> {
+        let mut command = Command::new("systemctl");
+        if service.scope == ServiceScope::User {
+            command.arg("--user");
+        }
+        let output = command.args(args).arg(service.name).output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "systemctl {} {} failed: {}", args.join(" "), service.name, String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+    
+    impl ServiceBackend for SystemBackend {
+        fn state(&self, service: &Service) -> io::Result<ServiceState> {
+            let output = systemctl(service, &["show", "--property=LoadState,ActiveState"])?;
+            let property = |name: &str| {
+                output
+                    .lines()
+                    .find_map(|line| line.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')))
+                    .unwrap_or("")
+                    .to_string()
+            };
+            if property("LoadState") == "not-found" {
+                return Ok(ServiceState::NotInstalled);
+            }
+            Ok(match property("ActiveState").as_str() {
+                "active" | "reloading" => ServiceState::Running,
+                "inactive" | "failed" => ServiceState::Stopped,
+                "activating" => ServiceState::StartPending,
+                "deactivating" => ServiceState::StopPending,
+                other => ServiceState::Unknown(other.to_string()),
+            })
+        }
+        
+        fn start(&self, service: &Service) -> io::Result<()> {
+            systemctl(service, &["start", "--no-block"]).map(|_| ())
+        }
+        
+        fn stop(&self, service: &Service) -> io::Result<()> {
+            systemctl(service, &["stop", "--no-block"]).map(|_| ())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    
+    const TEST_SERVICE: Service = Service { name: "test.service", label: "Test", scope: ServiceScope::User };
+    
+    // Reports the pending state for a few polls before settling, or forever when stuck.
+    struct FakeBackend {
+        state: Arc<Mutex<ServiceState>>,
+        pending_polls: Arc<Mutex<u32>>,
+        stuck: bool,
+    }
+    
+    impl ServiceBackend for FakeBackend {
+        fn state(&self, _service: &Service) -> io::Result<ServiceState> {
+            let mut state = self.state.lock().unwrap();
+            let mut pending = self.pending_polls.lock().unwrap();
+            if !self.stuck && *pending == 0 {
+                match *state {
+                    ServiceState::StartPending => *state = ServiceState::Running,
+                    ServiceState::StopPending => *state = ServiceState::Stopped,
+                    _ => {}
+                }
+            }
+            *pending = pending.saturating_sub(1);
+            Ok(state.clone())
+        }
+        
+        fn start(&self, _service: &Service) -> io::Result<()> {
+            *self.state.lock().unwrap() = ServiceState::StartPending;
+            *self.pending_polls.lock().unwrap() = 3;
+            Ok(())
+        }
+        
+        fn stop(&self, _service: &Service) -> io::Result<()> {
+            *self.state.lock().unwrap() = ServiceState::StopPending;
+            *self.pending_polls.lock().unwrap() = 3;
+            Ok(())
+        }
+    }
+    
+    fn fake_controller(initial: ServiceState, stuck: bool) -> (ServiceController, Arc<Mutex<ServiceState>>) {
+        let state = Arc::new(Mutex::new(initial));
+        let backend = FakeBackend { state: state.clone(), pending_polls: Arc::new(Mutex::new(0)), stuck };
+        let mut controller = ServiceController::with_backend(Box::new(backend));
+        controller.poll_interval = Duration::from_millis(1);
+        (controller, state)
+    }
+    
+    #[test]
+    fn start_and_wait_waits_for_running() {
+        let (controller, state) = fake_controller(ServiceState::Stopped, false);
+        controller.start_and_wait(&TEST_SERVICE, Duration::from_secs(5)).unwrap();
+        assert!(*state.lock().unwrap() == ServiceState::Running);
+    }
+    
+    #[test]
+    fn stop_and_wait_waits_for_stopped() {
+        let (controller, state) = fake_controller(ServiceState::Running, false);
+        controller.stop_and_wait(&TEST_SERVICE, Duration::from_secs(5)).unwrap();
+        assert!(*state.lock().unwrap() == ServiceState::Stopped);
+    }
+    
+    #[test]
+    fn stuck_service_times_out() {
+        let (controller, _) = fake_controller(ServiceState::Running, true);
+        let error = controller.stop_and_wait(&TEST_SERVICE, Duration::from_millis(20)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        
+        let (controller, _) = fake_controller(ServiceState::Stopped, true);
+        let error = controller.start_and_wait(&TEST_SERVICE, Duration::from_millis(20)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+    
+    #[test]
+    fn missing_service_cannot_start_but_counts_as_stopped() {
+        let (controller, _) = fake_controller(ServiceState::NotInstalled, false);
+        assert_eq!(controller.start_and_wait(&TEST_SERVICE, Duration::from_secs(1)).unwrap_err().kind(), io::ErrorKind::NotFound);
+        controller.stop_and_wait(&TEST_SERVICE, Duration::from_secs(1)).unwrap();
+    }
+    
+    #[test]
+    fn worker_runs_jobs_and_reports_states() {
+        static SERVICES: &[Service] = &[TEST_SERVICE];
+        let (controller, _) = fake_controller(ServiceState::Stopped, false);
+        let worker = ServiceWorker::start(controller, SERVICES, Duration::from_secs(60));
+        let job = worker.run(|services| services.start_and_wait(&TEST_SERVICE, Duration::from_secs(5)).is_ok());
+        assert!(job.recv_timeout(Duration::from_secs(5)).unwrap());
+        
+        // The worker refreshes right after a job, so the new state follows the initial one.
+        let started = Instant::now();
+        let mut states = None;
+        while started.elapsed() < Duration::from_secs(5) {
+            if let Some(latest) = worker.poll_states() {
+                states = Some(latest);
+                if states == Some(vec![Ok(ServiceState::Running)]) {
+                    break;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(states == Some(vec![Ok(ServiceState::Running)]));
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::VRSettings;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+// Settings as a flat map keyed by their field names in settings.json.
+pub type Fields = Map<String, Value>;
+
+pub fn to_fields(settings: &VRSettings) -> Fields {
+    match serde_json::to_value(settings) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Fields::new(),
+    }
+}
+
+// Keys of `other` whose values differ from `base`, in the order they appear in `other`.
+pub fn changed_fields(base: &Fields, other: &Fields) -> Vec<String> {
+    other
+        .iter()
+        .filter(|(key, value)| base.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+pub fn changes(before: &Fields, after: &Fields) -> Vec<FieldChange> {
+    changed_fields(before, after)
+        .into_iter()
+        .map(|field| FieldChange {
+            from: before.get(&field).cloned().unwrap_or(Value::Null),
+            to: after[&field].clone(),
+            field,
+        })
+        .collect()
+}
+
+// Returns a copy of `settings` with the given fields overwritten.
+pub fn with_fields(settings: &VRSettings, fields: &Fields) -> serde_json::Result<VRSettings> {
+    let mut merged = to_fields(settings);
+    for (key, value) in fields {
+        merged.insert(key.clone(), value.clone());
+    }
+    serde_json::from_value(Value::Object(merged))
+}
+
+pub fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if n.is_f64() => format!("{:.2}", f),
+            _ => n.to_string(),
+        },
+        Value::Bool(true) => String::from("on"),
+        Value::Bool(false) => String::from("off"),
+        Value::Null => String::from("none"),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,107 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+use crate::config;
+use crate::profiles;
+use crate::VRSettings;
+
+// Editors and scripts often write a file in several steps; wait for them to settle before reading.
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+// An external edit to settings.json that touches fields also edited, unsaved, in the app.
+pub struct ReloadConflict {
+    pub external: VRSettings,
+    pub fields: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct Changes {
+    pub settings: bool,
+    pub profiles: bool,
+}
+
+pub struct SettingsWatcher {
+    watcher: RecommendedWatcher,
+    receiver: mpsc::Receiver<notify::Result<Event>>,
+    // Canonical, since events carry absolute paths.
+    dir: PathBuf,
+    profile_dir: PathBuf,
+    watching_profiles: bool,
+    pending: Changes,
+    last_event: Option<Instant>,
+}
+
+impl SettingsWatcher {
+    pub fn start() -> notify::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+        let dir = fs::canonicalize(config::config_dir())?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        let mut this = Self {
+            watcher,
+            receiver,
+            profile_dir: dir.join(profiles::PROFILE_DIR),
+            dir,
+            watching_profiles: false,
+            pending: Changes::default(),
+            last_event: None,
+        };
+        this.watch_profiles();
+        Ok(this)
+    }
+    
+    // The profile directory is created with the first game profile, possibly after the watcher started.
+    fn watch_profiles(&mut self) {
+        if self.watching_profiles || !self.profile_dir.is_dir() {
+            return;
+        }
+        match self.watcher.watch(&self.profile_dir, RecursiveMode::NonRecursive) {
+            Ok(()) => self.watching_profiles = true,
+            Err(e) => warn!("Failed to watch {}: {}", self.profile_dir.display(), e),
+        }
+    }
+    
+    // Returns what changed once no new events have arrived for SETTLE_TIME.
+    pub fn poll(&mut self) -> Option<Changes> {
+        while let Ok(event) = self.receiver.try_recv() {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Settings watcher error: {}", e);
+                    continue;
+                }
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            for path in &event.paths {
+                if *path == self.profile_dir && matches!(event.kind, EventKind::Remove(_)) {
+                    self.watching_profiles = false;
+                }
+                if path.parent() == Some(self.dir.as_path()) && path.file_name().is_some_and(|name| name == config::SETTINGS_FILE) {
+                    self.pending.settings = true;
+                    self.last_event = Some(Instant::now());
+                } else if *path == self.profile_dir || (path.parent() == Some(self.profile_dir.as_path()) && path.extension().is_some_and(|ext| ext == "json")) {
+                    self.pending.profiles = true;
+                    self.last_event = Some(Instant::now());
+                }
+            }
+        }
+        self.watch_profiles();
+        
+        match self.last_event {
+            Some(at) if at.elapsed() >= SETTLE_TIME => {
+                self.last_event = None;
+                Some(std::mem::take(&mut self.pending))
+            }
+            _ => None,
+        }
+    }
+}
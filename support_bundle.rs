@@ -0,0 +1,243 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use sysinfo::System;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::config;
+use crate::openxr;
+use crate::registry::{self, MANAGED_VALUES};
+use crate::{KEPT_LOG_FILES, VR_PROCESSES};
+
+pub struct BundleOptions {
+    pub output: PathBuf,
+    pub redact: bool,
+}
+
+impl Default for BundleOptions {
+    fn default() -> Self {
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self {
+            output: PathBuf::from(format!("vr_support_bundle_{}.zip", stamp)),
+            redact: false,
+        }
+    }
+}
+
+pub fn create_bundle(options: &BundleOptions) -> io::Result<PathBuf> {
+    let redactor = Redactor::new(options.redact);
+    let file = fs::File::create(&options.output)?;
+    let mut zip = ZipWriter::new(file);
+    let file_options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    
+    if let Ok(settings) = fs::read_to_string(config::settings_path()) {
+        zip.start_file("settings.json", file_options)?;
+        zip.write_all(redactor.apply(&settings).as_bytes())?;
+    }
+    
+    // A missing log directory just means nothing has been logged yet.
+    for log in recent_logs(&config::log_dir()).unwrap_or_default() {
+        let name = log.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if let Ok(contents) = fs::read(&log) {
+            let contents = String::from_utf8_lossy(&contents);
+            zip.start_file(format!("logs/{}", name), file_options)?;
+            zip.write_all(redactor.apply(&contents).as_bytes())?;
+        }
+    }
+    
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    
+    zip.start_file("registry.txt", file_options)?;
+    zip.write_all(redactor.apply(&registry_report()).as_bytes())?;
+    
+    zip.start_file("processes.txt", file_options)?;
+    zip.write_all(redactor.apply(&process_report(&sys)).as_bytes())?;
+    
+    zip.start_file("openxr.json", file_options)?;
+    let discovery = serde_json::to_string_pretty(&openxr::discover()).unwrap_or_default();
+    zip.write_all(redactor.apply(&discovery).as_bytes())?;
+    
+    zip.start_file("system.txt", file_options)?;
+    zip.write_all(redactor.apply(&system_report(&sys)).as_bytes())?;
+    
+    zip.finish()?;
+    Ok(options.output.clone())
+}
+
+fn recent_logs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut logs: Vec<(SystemTime, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.starts_with("vr_suite") && name.ends_with(".log")
+        })
+        .map(|e| {
+            let modified = e.metadata().and_then(|m| m.modified()).unwrap_or(UNIX_EPOCH);
+            (modified, e.path())
+        })
+        .collect();
+    logs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    Ok(logs.into_iter().take(KEPT_LOG_FILES).map(|(_, path)| path).collect())
+}
+
+fn registry_report() -> String {
+    let mut report = String::new();
+    for value in MANAGED_VALUES {
+        let current = registry::read_value(value).unwrap_or_else(|| String::from("<not set>"));
+        report.push_str(&format!("{} = {}\n", value.display_path(), current));
+    }
+    report
+}
+
+fn process_report(sys: &System) -> String {
+    let mut report = String::from("VR processes:\n");
+    for name in VR_PROCESSES {
+        match sys.processes_by_name(name).next() {
+            Some(p) => {
+                let exe = p.exe().map(|e| e.display().to_string()).unwrap_or_default();
+                let version = p.exe().and_then(file_version).unwrap_or_else(|| String::from("unknown"));
+                report.push_str(&format!("  {} (pid {}) version {} at {}\n", name, p.pid(), version, exe));
+            }
+            None => report.push_str(&format!("  {} not running\n", name)),
+        }
+    }
+    
+    report.push_str("\nAll processes:\n");
+    let mut processes: Vec<_> = sys.processes().values().collect();
+    processes.sort_by_key(|p| p.pid());
+    for p in processes {
+        let exe = p.exe().map(|e| e.display().to_string()).unwrap_or_default();
+        report.push_str(&format!("  {:>7} {:<32} {:>8} MB  {}\n", p.pid(), p.name(), p.memory() / 1024 / 1024, exe));
+    }
+    report
+}
+
+fn system_report(sys: &System) -> String {
+    let mut report = String::new();
+    report.push_str(&format!("OS: {} {}\n", System::name().unwrap_or_default(), System::os_version().unwrap_or_default()));
+    report.push_str(&format!("Kernel: {}\n", System::kernel_version().unwrap_or_default()));
+    report.push_str(&format!("Host: {}\n", System::host_name().unwrap_or_default()));
+    report.push_str(&format!("Uptime: {} s\n", System::uptime()));
+    if let Some(cpu) = sys.cpus().first() {
+        report.push_str(&format!("CPU: {} ({} logical cores)\n", cpu.brand(), sys.cpus().len()));
+    }
+    report.push_str(&format!(
+        "Memory: {} MB used / {} MB total\n",
+        sys.used_memory() / 1024 / 1024,
+        sys.total_memory() / 1024 / 1024
+    ));
+    report.push_str(&format!("Toolkit version: {}\n", env!("CARGO_PKG_VERSION")));
+    report
+}
+
+#[cfg(target_os = "windows")]
+fn file_version(path: &Path) -> Option<String> {
+    use std::process::Command;
+    
+    // Single-quoted PowerShell strings escape an apostrophe by doubling it.
+    let literal = path.display().to_string().replace('\'', "''");
+    let script = format!("(Get-Item -LiteralPath '{}').VersionInfo.FileVersion", literal);
+    let output = Command::new("powershell").args(["-NoProfile", "-Command", &script]).output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if version.is_empty() { None } else { Some(version) }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn file_version(_path: &Path) -> Option<String> {
+    None
+}
+
+struct Redactor {
+    replacements: Vec<(String, &'static str)>,
+    // Matched as whole words only, since short user or host names like "al" also occur inside ordinary words.
+    words: Vec<(String, &'static str)>,
+}
+
+impl Redactor {
+    fn new(enabled: bool) -> Self {
+        let mut replacements = Vec::new();
+        let mut words = Vec::new();
+        if enabled {
+            for var in ["USERPROFILE", "HOME"] {
+                if let Ok(home) = std::env::var(var) {
+                    if !home.is_empty() {
+                        replacements.push((home.replace('\\', "\\\\"), "<HOME>"));
+                        replacements.push((home, "<HOME>"));
+                    }
+                }
+            }
+            if let Some(host) = System::host_name().filter(|host| !host.is_empty()) {
+                words.push((host, "<HOST>"));
+            }
+            for var in ["USERNAME", "USER"] {
+                if let Ok(user) = std::env::var(var) {
+                    if !user.is_empty() {
+                        words.push((user, "<USER>"));
+                    }
+                }
+            }
+        }
+        Self { replacements, words }
+    }
+    
+    fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (needle, placeholder) in &self.replacements {
+            text = text.replace(needle.as_str(), placeholder);
+        }
+        for (word, placeholder) in &self.words {
+            text = replace_word(&text, word, placeholder);
+        }
+        text
+    }
+}
+
+// Replaces `word` where it is not directly preceded or followed by a letter or digit.
+fn replace_word(text: &str, word: &str, placeholder: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(word) {
+        let end = index + word.len();
+        let before = rest[..index].chars().next_back();
+        let after = rest[end..].chars().next();
+        result.push_str(&rest[..index]);
+        if before.is_none_or(|c| !c.is_alphanumeric()) && after.is_none_or(|c| !c.is_alphanumeric()) {
+            result.push_str(placeholder);
+        } else {
+            result.push_str(word);
+        }
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn short_usernames_are_redacted_as_whole_words() {
+        let text = "C:\\Users\\al\\AppData and /home/al/.config, but not always or alpha";
+        assert_eq!(
+            replace_word(text, "al", "<USER>"),
+            "C:\\Users\\<USER>\\AppData and /home/<USER>/.config, but not always or alpha"
+        );
+    }
+    
+    #[test]
+    fn words_at_the_edges_are_redacted() {
+        assert_eq!(replace_word("al", "al", "<USER>"), "<USER>");
+        assert_eq!(replace_word("al:al", "al", "<USER>"), "<USER>:<USER>");
+        assert_eq!(replace_word("", "al", "<USER>"), "");
+    }
+    
+    #[test]
+    fn short_host_names_are_redacted_as_whole_words() {
+        let redactor = Redactor { replacements: Vec::new(), words: vec![(String::from("pc"), "<HOST>")] };
+        assert_eq!(redactor.apply("Host: pc, spec sheet from topcoat"), "Host: <HOST>, spec sheet from topcoat");
+    }
+}
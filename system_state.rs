@@ -0,0 +1,218 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+use crate::settings_diff::{self, Fields};
+use crate::VRSettings;
+
+// The registry keeps sharpening as a whole percentage, so saved fractions only round-trip to 0.01.
+const FLOAT_TOLERANCE: f64 = 0.01;
+
+pub struct LiveValue {
+    pub field: &'static str,
+    pub label: &'static str,
+    pub source: String,
+    pub value: Value,
+}
+
+pub struct Drift {
+    pub field: &'static str,
+    pub label: &'static str,
+    pub source: String,
+    pub saved: Value,
+    pub live: Value,
+}
+
+pub struct SystemSnapshot {
+    pub values: Vec<LiveValue>,
+    pub taken_at: Instant,
+}
+
+impl SystemSnapshot {
+    pub fn read() -> Self {
+        let mut values = Vec::new();
+        read_platform(&mut values);
+        read_power_plan(&mut values);
+        read_openxr_runtime(&mut values);
+        debug!("Read {} live value(s) from the system", values.len());
+        Self { values, taken_at: Instant::now() }
+    }
+    
+    pub fn fields(&self) -> Fields {
+        self.values.iter().map(|v| (v.field.to_string(), v.value.clone())).collect()
+    }
+    
+    pub fn drift(&self, settings: &VRSettings) -> Vec<Drift> {
+        let saved = settings_diff::to_fields(settings);
+        self.values
+            .iter()
+            .filter_map(|live| {
+                let saved = saved.get(live.field).cloned().unwrap_or(Value::Null);
+                if matches(&saved, &live.value) {
+                    return None;
+                }
+                Some(Drift {
+                    field: live.field,
+                    label: live.label,
+                    source: live.source.clone(),
+                    saved,
+                    live: live.value.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+// Re-reads the system on a background thread; the UI picks up the latest snapshot with poll().
+pub struct DriftMonitor {
+    pub interval: Duration,
+    receiver: mpsc::Receiver<SystemSnapshot>,
+    stop: Arc<AtomicBool>,
+}
+
+impl DriftMonitor {
+    pub fn start(interval: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        std::thread::spawn(move || loop {
+            let started = Instant::now();
+            while started.elapsed() < interval {
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(250));
+            }
+            if stopped.load(Ordering::Relaxed) || sender.send(SystemSnapshot::read()).is_err() {
+                return;
+            }
+        });
+        Self { interval, receiver, stop }
+    }
+    
+    pub fn poll(&self) -> Option<SystemSnapshot> {
+        self.receiver.try_iter().last()
+    }
+}
+
+impl Drop for DriftMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn matches(saved: &Value, live: &Value) -> bool {
+    match (saved.as_f64(), live.as_f64()) {
+        (Some(a), Some(b)) => (a - b).abs() < FLOAT_TOLERANCE,
+        _ => saved == live,
+    }
+}
+
+fn push(values: &mut Vec<LiveValue>, field: &'static str, label: &'static str, source: String, value: impl Serialize) {
+    if let Ok(value) = serde_json::to_value(value) {
+        values.push(LiveValue { field, label, source, value });
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_platform(values: &mut Vec<LiveValue>) {
+    use crate::registry::{self, Hive};
+    use crate::ASWMode;
+    
+    let link = "Software\\Oculus\\RemoteHeadset";
+    let source = |path: &str, name: &str| registry::display_path(Hive::CurrentUser, path, name);
+    let read = |path: &str, name: &str| registry::read_u32(Hive::CurrentUser, path, name);
+    
+    for (field, label, name) in [
+        ("encode_bitrate_mbps", "Encode bitrate (Mbps)", "BitrateMbps"),
+        ("encode_resolution_width", "Encode width", "EncodeResolutionWidth"),
+        ("encode_resolution_height", "Encode height", "EncodeResolutionHeight"),
+    ] {
+        if let Some(value) = read(link, name) {
+            push(values, field, label, source(link, name), value);
+        }
+    }
+    
+    let strength = read(link, "LinkSharpeningStrength");
+    match read(link, "LinkSharpeningEnabled") {
+        Some(0) => push(values, "link_sharpening", "Link sharpening", source(link, "LinkSharpeningEnabled"), 0.0f32),
+        _ => {
+            if let Some(strength) = strength {
+                push(values, "link_sharpening", "Link sharpening", source(link, "LinkSharpeningStrength"), strength as f32 / 100.0);
+            }
+        }
+    }
+    
+    for (field, label, name) in [
+        ("mirror_window", "Mirror window", "MirrorWindow"),
+        ("guardian_visibility", "Guardian visibility", "GuardianVisibility"),
+    ] {
+        if let Some(value) = read(link, name) {
+            push(values, field, label, source(link, name), value != 0);
+        }
+    }
+    
+    let debug = "Software\\Oculus\\Debug";
+    let asw = match read(debug, "ASW") {
+        Some(0) => Some(ASWMode::Off),
+        Some(1) => Some(ASWMode::Auto),
+        Some(2) => Some(ASWMode::Force45FPS),
+        Some(3) => Some(ASWMode::Force30FPS),
+        _ => None,
+    };
+    if let Some(asw) = asw {
+        push(values, "asw_mode", "ASW mode", source(debug, "ASW"), asw);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_platform(_values: &mut Vec<LiveValue>) {}
+
+#[cfg(target_os = "windows")]
+fn read_power_plan(values: &mut Vec<LiveValue>) {
+    use crate::PowerPlan;
+    
+    let output = match std::process::Command::new("powercfg").arg("/getactivescheme").output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_lowercase(),
+        _ => return,
+    };
+    let plan = [PowerPlan::Balanced, PowerPlan::HighPerformance, PowerPlan::PowerSaver]
+        .into_iter()
+        .find(|plan| output.contains(plan.scheme_guid()));
+    if let Some(plan) = plan {
+        push(values, "power_plan", "Power plan", String::from("powercfg /getactivescheme"), plan);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_power_plan(values: &mut Vec<LiveValue>) {
+    if let Some((plan, source)) = crate::power::active_plan() {
+        push(values, "power_plan", "Power plan", source.to_string(), plan);
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn read_power_plan(_values: &mut Vec<LiveValue>) {}
+
+// The settings only know the Oculus and SteamVR runtimes; anything else reads as neither.
+#[cfg(target_os = "windows")]
+fn read_openxr_runtime(values: &mut Vec<LiveValue>) {
+    let runtime = match crate::openxr::discover().active_runtime {
+        Some(runtime) => runtime,
+        None => return,
+    };
+    let lower = runtime.to_lowercase();
+    let oculus = lower.contains("oculus");
+    let steamvr = !oculus && lower.contains("steamvr");
+    push(values, "use_openxr", "Oculus OpenXR runtime", runtime.clone(), oculus);
+    push(values, "use_steamvr", "SteamVR OpenXR runtime", runtime, steamvr);
+}
+
+// Apply only sets the runtime on Windows, so elsewhere it would always read as drift.
+#[cfg(not(target_os = "windows"))]
+fn read_openxr_runtime(_values: &mut Vec<LiveValue>) {}